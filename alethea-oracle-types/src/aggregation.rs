@@ -0,0 +1,760 @@
+// Copyright (c) Alethea Network
+// SPDX-License-Identifier: MIT
+
+//! Vote aggregation, one strategy per [`AggregationMethod`]
+//!
+//! Each strategy gives every reveal a weight and the outcome with the most
+//! weight wins. Confidence is the winner's share of all counted weight,
+//! rounded down.
+//!
+//! Weights are integers and never go through `f64`, so every validator gets
+//! the same tally whatever order the reveals come in. Ties go to the lowest
+//! outcome index.
+//!
+//! Scalar markets have their own strategies, [`Median`] and [`TrimmedMean`],
+//! which pick a value from the reveals weighted by voting power. Multi-select
+//! and ranked markets have [`Approval`] and [`Borda`], which combine whole
+//! ballots into one, also weighted by voting power.
+
+use std::cmp::Reverse;
+
+use crate::{AggregationMethod, Ballot, MarketKind, VoteReveal};
+
+/// Result of aggregating a market's reveals
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Aggregate {
+    pub winning_outcome: usize,
+    /// Share of the counted weight behind the winner (0-100)
+    pub confidence: u8,
+}
+
+/// A way of turning reveals into an outcome
+pub trait Aggregator {
+    /// Weight `reveal` adds to the outcome it voted for
+    ///
+    /// Only ratios between weights matter, so a strategy may scale them freely.
+    fn weight(&self, reveal: &VoteReveal) -> u128;
+
+    /// Tally `reveals` over `num_outcomes` outcomes
+    ///
+    /// Reveals for outcomes out of range are ignored. None if no outcome got
+    /// any weight.
+    fn aggregate(&self, reveals: &[VoteReveal], num_outcomes: usize) -> Option<Aggregate> {
+        let mut tally = vec![0u128; num_outcomes];
+        for reveal in reveals {
+            if let Some(weight) = tally.get_mut(reveal.outcome_index) {
+                *weight = weight.saturating_add(self.weight(reveal));
+            }
+        }
+
+        let total = tally.iter().fold(0u128, |total, weight| total.saturating_add(*weight));
+        if total == 0 {
+            return None;
+        }
+
+        // Strictly greater, so the lowest index keeps a tie
+        let mut winning_outcome = 0;
+        for (index, weight) in tally.iter().enumerate() {
+            if *weight > tally[winning_outcome] {
+                winning_outcome = index;
+            }
+        }
+
+        // Weights stay far below u128::MAX / 100, so this cannot saturate in practice
+        let confidence = (tally[winning_outcome].saturating_mul(100) / total).min(100) as u8;
+        Some(Aggregate { winning_outcome, confidence })
+    }
+}
+
+/// Result of aggregating a scalar market's reveals
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScalarAggregate {
+    pub value: i128,
+    /// Share of the counted weight that agrees with `value` (0-100), see [`MarketKind::agrees`]
+    pub confidence: u8,
+}
+
+/// A way of turning scalar reveals into a value
+pub trait ScalarAggregator {
+    /// The result for `votes`, sorted by value, as (value, weight) with weights summing to `total` > 0
+    fn pick(&self, votes: &[(i128, u128)], total: u128) -> i128;
+
+    /// Tally `reveals` on a market of `kind`, each weighted by its voting power
+    ///
+    /// Reveals without a value or outside the market's range are ignored.
+    /// None if no reveal has any weight.
+    fn aggregate(&self, reveals: &[VoteReveal], kind: &MarketKind) -> Option<ScalarAggregate> {
+        let mut votes: Vec<(i128, u128)> = reveals
+            .iter()
+            .filter_map(|reveal| {
+                let value = reveal.value.filter(|value| kind.contains(*value))?;
+                Some((value, u128::from(reveal.voting_power)))
+            })
+            .collect();
+        // Sorting the pairs, not just the values, keeps the input order out of it
+        votes.sort_unstable();
+
+        let total = votes.iter().fold(0u128, |total, (_, weight)| total.saturating_add(*weight));
+        if total == 0 {
+            return None;
+        }
+
+        let value = self.pick(&votes, total);
+        let agreeing = votes
+            .iter()
+            .filter(|(vote, _)| kind.agrees(*vote, value))
+            .fold(0u128, |agreeing, (_, weight)| agreeing.saturating_add(*weight));
+        let confidence = (agreeing.saturating_mul(100) / total).min(100) as u8;
+        Some(ScalarAggregate { value, confidence })
+    }
+}
+
+/// Result of aggregating a multi-select or ranked market's reveals
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BallotAggregate {
+    pub ballot: Ballot,
+    /// How settled the result is (0-100); each strategy says how it measures it
+    pub confidence: u8,
+}
+
+/// A way of turning ballots into one resulting ballot
+pub trait BallotAggregator {
+    /// Tally `reveals` on a market with `num_outcomes` outcomes, each weighted by its voting power
+    ///
+    /// Reveals without a ballot that fits the market are ignored. With no
+    /// weight behind any ballot the result is what an empty tally gives,
+    /// with confidence 0.
+    fn aggregate(&self, reveals: &[VoteReveal], num_outcomes: usize) -> BallotAggregate;
+}
+
+/// Ballots that fit a market of `kind`, with their voting power
+fn ballots<'a>(reveals: &'a [VoteReveal], kind: &MarketKind, num_outcomes: usize) -> Vec<(&'a Ballot, u128)> {
+    reveals
+        .iter()
+        .filter_map(|reveal| {
+            let ballot = reveal.ballot.as_ref().filter(|ballot| ballot.fits(kind, num_outcomes))?;
+            Some((ballot, u128::from(reveal.voting_power)))
+        })
+        .collect()
+}
+
+/// One vote per voter
+pub struct SimpleMajority;
+
+impl Aggregator for SimpleMajority {
+    fn weight(&self, _reveal: &VoteReveal) -> u128 {
+        1
+    }
+}
+
+/// Reputation and/or stake, scaled by the voter's confidence
+///
+/// Each flag brings in its own factor of voting power, so with both on the
+/// weight is the full voting power. With both off every voter counts the same
+/// before confidence.
+pub struct WeightedMajority {
+    pub weight_by_reputation: bool,
+    pub weight_by_stake: bool,
+}
+
+impl Aggregator for WeightedMajority {
+    fn weight(&self, reveal: &VoteReveal) -> u128 {
+        let factors = reveal.power_factors;
+        let reputation = if self.weight_by_reputation { u128::from(factors.reputation) } else { 1 };
+        let stake = if self.weight_by_stake { u128::from(factors.stake) } else { 1 };
+        // Confidence in whole percent, kept as the multiplier instead of dividing by 100
+        reputation
+            .saturating_mul(stake)
+            .saturating_mul(u128::from(reveal.confidence.min(100)))
+    }
+}
+
+/// Confidence only; every voter's power counts the same
+pub struct ConfidenceWeighted;
+
+impl Aggregator for ConfidenceWeighted {
+    fn weight(&self, reveal: &VoteReveal) -> u128 {
+        u128::from(reveal.confidence.min(100))
+    }
+}
+
+/// Square root of voting power, so large voters gain less from their size
+pub struct QuadraticVoting;
+
+impl QuadraticVoting {
+    /// Fractional bits kept in the square root
+    const FRACTION_BITS: u32 = 16;
+}
+
+impl Aggregator for QuadraticVoting {
+    fn weight(&self, reveal: &VoteReveal) -> u128 {
+        // sqrt(power << 32) is sqrt(power) in 16.16 fixed point, rounded down
+        (u128::from(reveal.voting_power) << (2 * Self::FRACTION_BITS)).isqrt()
+    }
+}
+
+/// Weighted median: the lowest value with at least half the weight at or below it
+pub struct Median;
+
+impl ScalarAggregator for Median {
+    fn pick(&self, votes: &[(i128, u128)], total: u128) -> i128 {
+        let mut below = 0u128;
+        for (value, weight) in votes {
+            below = below.saturating_add(*weight);
+            if below.saturating_mul(2) >= total {
+                return *value;
+            }
+        }
+        votes.last().map_or(0, |(value, _)| *value)
+    }
+}
+
+/// Weighted mean, rounded down, of the votes left after dropping
+/// `trim_percentage` of the weight from each end
+///
+/// Trimming half or more leaves nothing to average, and so does a range too
+/// wide to sum exactly; both fall back to the [`Median`].
+pub struct TrimmedMean {
+    pub trim_percentage: u8,
+}
+
+impl TrimmedMean {
+    fn mean(&self, votes: &[(i128, u128)], total: u128) -> Option<i128> {
+        let trim = total / 100 * u128::from(self.trim_percentage) + total % 100 * u128::from(self.trim_percentage) / 100;
+        let (keep_from, keep_to) = (trim, total.checked_sub(trim)?);
+        if keep_from >= keep_to {
+            return None;
+        }
+
+        // Sum offsets from the lowest value so every term is non-negative
+        let lowest = votes.first()?.0;
+        let mut start = 0u128;
+        let mut sum = 0u128;
+        for (value, weight) in votes {
+            let end = start.saturating_add(*weight);
+            let kept = end.min(keep_to).saturating_sub(start.max(keep_from));
+            sum = sum.checked_add(value.abs_diff(lowest).checked_mul(kept)?)?;
+            start = end;
+        }
+
+        let offset = sum / (keep_to - keep_from);
+        lowest.checked_add_unsigned(offset)
+    }
+}
+
+impl ScalarAggregator for TrimmedMean {
+    fn pick(&self, votes: &[(i128, u128)], total: u128) -> i128 {
+        self.mean(votes, total).unwrap_or_else(|| Median.pick(votes, total))
+    }
+}
+
+/// Every outcome approved by more than half the weight; an even split leaves it out
+///
+/// Confidence is the narrowest of those calls: the smallest share of weight
+/// on the winning side of any one outcome.
+pub struct Approval;
+
+impl BallotAggregator for Approval {
+    fn aggregate(&self, reveals: &[VoteReveal], num_outcomes: usize) -> BallotAggregate {
+        let mut approvals = vec![0u128; num_outcomes];
+        let mut total = 0u128;
+        for (ballot, weight) in ballots(reveals, &MarketKind::MultiSelect, num_outcomes) {
+            total = total.saturating_add(weight);
+            for (index, approved) in approvals.iter_mut().enumerate() {
+                if ballot.approves(index) {
+                    *approved = approved.saturating_add(weight);
+                }
+            }
+        }
+        if total == 0 {
+            return BallotAggregate { ballot: Ballot::Approval(0), confidence: 0 };
+        }
+
+        let mut result = 0u16;
+        let mut confidence = 100u128;
+        for (index, approved) in approvals.iter().enumerate() {
+            let side = if approved.saturating_mul(2) > total {
+                result |= 1 << index;
+                *approved
+            } else {
+                total - approved
+            };
+            confidence = confidence.min(side.saturating_mul(100) / total);
+        }
+        BallotAggregate { ballot: Ballot::Approval(result), confidence: confidence as u8 }
+    }
+}
+
+/// Borda count: on a ranking of n outcomes the first gets n - 1 points times
+/// the voter's weight, the next n - 2 and so on, down to none for the last
+///
+/// The result ranks outcomes by points, ties to the lowest index. Confidence
+/// is the winner's points as a share of what it would get if every voter
+/// ranked it first.
+pub struct Borda;
+
+impl BallotAggregator for Borda {
+    fn aggregate(&self, reveals: &[VoteReveal], num_outcomes: usize) -> BallotAggregate {
+        let mut points = vec![0u128; num_outcomes];
+        let mut total = 0u128;
+        for (ballot, weight) in ballots(reveals, &MarketKind::Ranked, num_outcomes) {
+            let Ballot::Ranking(order) = ballot else {
+                continue;
+            };
+            total = total.saturating_add(weight);
+            for (place, index) in order.iter().enumerate() {
+                let earned = weight.saturating_mul((num_outcomes - 1 - place) as u128);
+                points[usize::from(*index)] = points[usize::from(*index)].saturating_add(earned);
+            }
+        }
+
+        let mut ranking: Vec<u8> = (0..num_outcomes.min(Ballot::MAX_OUTCOMES) as u8).collect();
+        ranking.sort_by_key(|index| (Reverse(points[usize::from(*index)]), *index));
+
+        let most = total.saturating_mul(num_outcomes.saturating_sub(1) as u128);
+        let confidence = match ranking.first() {
+            Some(winner) if most > 0 => (points[usize::from(*winner)].saturating_mul(100) / most).min(100) as u8,
+            _ => 0,
+        };
+        BallotAggregate { ballot: Ballot::Ranking(ranking), confidence }
+    }
+}
+
+impl AggregationMethod {
+    /// Whether this method is for scalar markets
+    pub fn is_scalar(&self) -> bool {
+        matches!(self, AggregationMethod::Median | AggregationMethod::TrimmedMean { .. })
+    }
+
+    /// Whether this method is for multi-select or ranked markets
+    pub fn is_ballot(&self) -> bool {
+        matches!(self, AggregationMethod::Approval | AggregationMethod::Borda)
+    }
+
+    /// Whether this method can aggregate a market of `kind`
+    pub fn fits(&self, kind: &MarketKind) -> bool {
+        match kind {
+            MarketKind::Categorical => !self.is_scalar() && !self.is_ballot(),
+            MarketKind::Scalar { .. } => self.is_scalar(),
+            MarketKind::MultiSelect => *self == AggregationMethod::Approval,
+            MarketKind::Ranked => *self == AggregationMethod::Borda,
+        }
+    }
+
+    /// Method used for a market of `kind` created without one; None for a
+    /// categorical market, which takes `Parameters::aggregation_method`
+    pub fn default_for(kind: &MarketKind) -> Option<AggregationMethod> {
+        match kind {
+            MarketKind::Categorical => None,
+            MarketKind::Scalar { .. } => Some(AggregationMethod::Median),
+            MarketKind::MultiSelect => Some(AggregationMethod::Approval),
+            MarketKind::Ranked => Some(AggregationMethod::Borda),
+        }
+    }
+
+    /// Consensus threshold this method sets itself, if any
+    pub fn min_consensus(&self) -> Option<u8> {
+        match self {
+            AggregationMethod::WeightedMajority { min_consensus, .. } => Some(*min_consensus),
+            _ => None,
+        }
+    }
+
+    /// The strategy implementing this method; None for a scalar or ballot method
+    pub fn aggregator(&self) -> Option<Box<dyn Aggregator>> {
+        let aggregator: Box<dyn Aggregator> = match self {
+            AggregationMethod::SimpleMajority => Box::new(SimpleMajority),
+            AggregationMethod::WeightedMajority { weight_by_reputation, weight_by_stake, .. } => {
+                Box::new(WeightedMajority {
+                    weight_by_reputation: *weight_by_reputation,
+                    weight_by_stake: *weight_by_stake,
+                })
+            }
+            AggregationMethod::ConfidenceWeighted => Box::new(ConfidenceWeighted),
+            AggregationMethod::QuadraticVoting => Box::new(QuadraticVoting),
+            AggregationMethod::Median
+            | AggregationMethod::TrimmedMean { .. }
+            | AggregationMethod::Approval
+            | AggregationMethod::Borda => return None,
+        };
+        Some(aggregator)
+    }
+
+    /// The scalar strategy implementing this method; None for any other method
+    pub fn scalar_aggregator(&self) -> Option<Box<dyn ScalarAggregator>> {
+        match self {
+            AggregationMethod::Median => Some(Box::new(Median)),
+            AggregationMethod::TrimmedMean { trim_percentage } => {
+                Some(Box::new(TrimmedMean { trim_percentage: *trim_percentage }))
+            }
+            _ => None,
+        }
+    }
+
+    /// The ballot strategy implementing this method; None for any other method
+    pub fn ballot_aggregator(&self) -> Option<Box<dyn BallotAggregator>> {
+        match self {
+            AggregationMethod::Approval => Some(Box::new(Approval)),
+            AggregationMethod::Borda => Some(Box::new(Borda)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PowerFactors, ScalarValue};
+    use linera_sdk::linera_base_types::{ChainId, CryptoHash, Timestamp};
+    use proptest::prelude::*;
+
+    fn reveal(voter: u8, outcome_index: usize, confidence: u8, voting_power: u64) -> VoteReveal {
+        VoteReveal {
+            voter_chain: ChainId(CryptoHash::from([voter; 32])),
+            market_id: 1,
+            outcome_index,
+            value: None,
+            ballot: None,
+            salt: [voter; 32],
+            confidence,
+            voting_power,
+            power_factors: PowerFactors { reputation: voting_power, stake: 1 },
+            revealed_at: Timestamp::from(0),
+        }
+    }
+
+    fn weighted() -> WeightedMajority {
+        WeightedMajority { weight_by_reputation: true, weight_by_stake: true }
+    }
+
+    fn strategies() -> Vec<Box<dyn Aggregator>> {
+        vec![Box::new(SimpleMajority), Box::new(weighted()), Box::new(ConfidenceWeighted), Box::new(QuadraticVoting)]
+    }
+
+    #[test]
+    fn test_weighted_majority() {
+        let reveals = [reveal(0, 0, 95, 150), reveal(1, 0, 90, 120), reveal(2, 1, 80, 100)];
+        let aggregate = weighted().aggregate(&reveals, 2).unwrap();
+        assert_eq!(aggregate.winning_outcome, 0);
+        assert_eq!(aggregate.confidence, 75); // 25050 of 33050
+
+        // Power without the flags: only confidence separates voters
+        let unweighted = WeightedMajority { weight_by_reputation: false, weight_by_stake: false };
+        let reveals = [reveal(0, 0, 50, 1_000), reveal(1, 1, 60, 1)];
+        assert_eq!(unweighted.aggregate(&reveals, 2).unwrap().winning_outcome, 1);
+        assert_eq!(weighted().aggregate(&reveals, 2).unwrap().winning_outcome, 0);
+    }
+
+    #[test]
+    fn test_weighted_majority_flags_are_separate() {
+        let factored = |voter, outcome_index, reputation, stake| {
+            let power_factors = PowerFactors { reputation, stake };
+            VoteReveal { power_factors, ..reveal(voter, outcome_index, 100, power_factors.voting_power()) }
+        };
+        // A well-reputed small staker against a large staker with little reputation, plus a nobody
+        let reveals = [factored(0, 0, 300, 1), factored(1, 1, 10, 50), factored(2, 1, 10, 1)];
+        let tally = |weight_by_reputation, weight_by_stake| {
+            WeightedMajority { weight_by_reputation, weight_by_stake }.aggregate(&reveals, 2).unwrap()
+        };
+
+        // 1 against 1 + 1
+        assert_eq!(tally(false, false), Aggregate { winning_outcome: 1, confidence: 66 });
+        // 300 against 10 + 10
+        assert_eq!(tally(true, false), Aggregate { winning_outcome: 0, confidence: 93 });
+        // 1 against 50 + 1
+        assert_eq!(tally(false, true), Aggregate { winning_outcome: 1, confidence: 98 });
+        // Full voting power: 300 against 500 + 10
+        assert_eq!(tally(true, true), Aggregate { winning_outcome: 1, confidence: 62 });
+    }
+
+    #[test]
+    fn test_strategies_disagree_where_they_should() {
+        // Two small voters against one large, sure voter
+        let reveals = [reveal(0, 0, 50, 16), reveal(1, 0, 50, 16), reveal(2, 1, 100, 100)];
+
+        assert_eq!(SimpleMajority.aggregate(&reveals, 2).unwrap(), Aggregate { winning_outcome: 0, confidence: 66 });
+        assert_eq!(weighted().aggregate(&reveals, 2).unwrap(), Aggregate { winning_outcome: 1, confidence: 86 });
+        assert_eq!(ConfidenceWeighted.aggregate(&reveals, 2).unwrap(), Aggregate { winning_outcome: 0, confidence: 50 });
+        // sqrt: 4 + 4 against 10
+        assert_eq!(QuadraticVoting.aggregate(&reveals, 2).unwrap(), Aggregate { winning_outcome: 1, confidence: 55 });
+    }
+
+    #[test]
+    fn test_ties_go_to_lowest_outcome() {
+        // Equal on every measure, listed highest outcome first
+        let reveals = [reveal(0, 2, 80, 25), reveal(1, 1, 80, 25)];
+        for strategy in strategies() {
+            assert_eq!(strategy.aggregate(&reveals, 3), Some(Aggregate { winning_outcome: 1, confidence: 50 }));
+        }
+
+        // A whale against two voters a quarter its size ties under quadratic voting
+        let reveals = [reveal(0, 1, 90, 100), reveal(1, 0, 90, 25), reveal(2, 0, 90, 25)];
+        assert_eq!(QuadraticVoting.aggregate(&reveals, 2).unwrap().winning_outcome, 0);
+    }
+
+    #[test]
+    fn test_quadratic_keeps_fractions() {
+        // 1.73 + 1.73 against 3.16; whole square roots would make it 1 + 1 against 3
+        let reveals = [reveal(0, 0, 90, 3), reveal(1, 0, 90, 3), reveal(2, 1, 90, 10)];
+        assert_eq!(QuadraticVoting.aggregate(&reveals, 2), Some(Aggregate { winning_outcome: 0, confidence: 52 }));
+        assert_eq!(QuadraticVoting.weight(&reveal(0, 0, 90, 16)), 4 << 16);
+    }
+
+    #[test]
+    fn test_degenerate_inputs() {
+        for strategy in strategies() {
+            assert_eq!(strategy.aggregate(&[], 2), None);
+            assert_eq!(strategy.aggregate(&[reveal(0, 0, 90, 100)], 0), None);
+            // Votes for outcomes that do not exist are not counted
+            assert_eq!(strategy.aggregate(&[reveal(0, 5, 90, 100)], 2), None);
+            let reveals = [reveal(0, 5, 90, 100), reveal(1, 1, 90, 100)];
+            assert_eq!(strategy.aggregate(&reveals, 2), Some(Aggregate { winning_outcome: 1, confidence: 100 }));
+        }
+
+        // Nothing behind any vote
+        let reveals = [reveal(0, 0, 0, 100), reveal(1, 1, 0, 100)];
+        assert_eq!(weighted().aggregate(&reveals, 2), None);
+        assert_eq!(ConfidenceWeighted.aggregate(&reveals, 2), None);
+        assert_eq!(QuadraticVoting.aggregate(&[reveal(0, 0, 90, 0)], 2), None);
+        assert!(SimpleMajority.aggregate(&reveals, 2).is_some());
+    }
+
+    #[test]
+    fn test_method_selects_strategy() {
+        let reveals = [reveal(0, 0, 50, 16), reveal(1, 0, 50, 16), reveal(2, 1, 100, 100)];
+        let winner = |method: AggregationMethod| method.aggregator().unwrap().aggregate(&reveals, 2).unwrap().winning_outcome;
+
+        assert_eq!(winner(AggregationMethod::SimpleMajority), 0);
+        assert_eq!(winner(AggregationMethod::default()), 1);
+        assert_eq!(winner(AggregationMethod::ConfidenceWeighted), 0);
+        assert_eq!(winner(AggregationMethod::QuadraticVoting), 1);
+
+        assert!(AggregationMethod::Median.aggregator().is_none());
+        assert!(AggregationMethod::default().scalar_aggregator().is_none());
+        assert!(AggregationMethod::TrimmedMean { trim_percentage: 10 }.fits(&rainfall()));
+        assert!(!AggregationMethod::SimpleMajority.fits(&rainfall()));
+        assert!(AggregationMethod::SimpleMajority.fits(&MarketKind::Categorical));
+
+        assert!(AggregationMethod::Approval.ballot_aggregator().is_some());
+        assert!(AggregationMethod::Borda.aggregator().is_none() && AggregationMethod::Median.ballot_aggregator().is_none());
+        assert!(AggregationMethod::Borda.fits(&MarketKind::Ranked) && !AggregationMethod::Borda.fits(&MarketKind::MultiSelect));
+        assert!(!AggregationMethod::Approval.fits(&MarketKind::Categorical));
+        assert_eq!(AggregationMethod::default_for(&MarketKind::MultiSelect), Some(AggregationMethod::Approval));
+        assert_eq!(AggregationMethod::default_for(&MarketKind::Categorical), None);
+    }
+
+    fn ballot_reveal(voter: u8, ballot: Ballot, voting_power: u64) -> VoteReveal {
+        VoteReveal { ballot: Some(ballot), ..reveal(voter, 0, 90, voting_power) }
+    }
+
+    #[test]
+    fn test_approval() {
+        // Most of the weight picks outcomes 0 and 1; outcome 2 is a close call
+        let reveals = [
+            ballot_reveal(0, Ballot::Approval(0b011), 55),
+            ballot_reveal(1, Ballot::Approval(0b111), 10),
+            ballot_reveal(2, Ballot::Approval(0b100), 35),
+        ];
+        // Outcome 2 has 45 of 100 for it, so 55 against sets the confidence
+        let aggregate = Approval.aggregate(&reveals, 3);
+        assert_eq!(aggregate, BallotAggregate { ballot: Ballot::Approval(0b011), confidence: 55 });
+
+        // Exactly half is not more than half
+        let reveals = [ballot_reveal(0, Ballot::Approval(0b01), 10), ballot_reveal(1, Ballot::Approval(0b00), 10)];
+        assert_eq!(Approval.aggregate(&reveals, 2), BallotAggregate { ballot: Ballot::Approval(0), confidence: 50 });
+
+        // Ballots that do not fit, and other kinds of vote, are not counted
+        let reveals = [
+            ballot_reveal(0, Ballot::Approval(0b1000), 100),
+            ballot_reveal(1, Ballot::Ranking(vec![0, 1, 2]), 100),
+            reveal(2, 0, 90, 100),
+            ballot_reveal(3, Ballot::Approval(0b001), 5),
+        ];
+        assert_eq!(Approval.aggregate(&reveals, 3), BallotAggregate { ballot: Ballot::Approval(0b001), confidence: 100 });
+        assert_eq!(Approval.aggregate(&[], 3), BallotAggregate { ballot: Ballot::Approval(0), confidence: 0 });
+    }
+
+    #[test]
+    fn test_borda() {
+        // A compromise everyone ranks second can beat a polarising favourite
+        let reveals = [
+            ballot_reveal(0, Ballot::Ranking(vec![0, 1, 2]), 30),
+            ballot_reveal(1, Ballot::Ranking(vec![2, 1, 0]), 30),
+            ballot_reveal(2, Ballot::Ranking(vec![1, 0, 2]), 20),
+        ];
+        // Points: outcome 0 gets 60 + 20, outcome 1 gets 30 + 30 + 40, outcome 2 gets 60
+        let aggregate = Borda.aggregate(&reveals, 3);
+        assert_eq!(aggregate, BallotAggregate { ballot: Ballot::Ranking(vec![1, 0, 2]), confidence: 62 });
+
+        // Ties keep the lower index first
+        let reveals = [ballot_reveal(0, Ballot::Ranking(vec![1, 0]), 10), ballot_reveal(1, Ballot::Ranking(vec![0, 1]), 10)];
+        assert_eq!(Borda.aggregate(&reveals, 2), BallotAggregate { ballot: Ballot::Ranking(vec![0, 1]), confidence: 50 });
+
+        let reveals = [ballot_reveal(0, Ballot::Ranking(vec![1, 1]), 10), ballot_reveal(1, Ballot::Approval(0b10), 10)];
+        assert_eq!(Borda.aggregate(&reveals, 2), BallotAggregate { ballot: Ballot::Ranking(vec![0, 1]), confidence: 0 });
+        assert_eq!(Borda.aggregate(&[], 0), BallotAggregate { ballot: Ballot::Ranking(vec![]), confidence: 0 });
+    }
+
+    fn value_reveal(voter: u8, value: i128, voting_power: u64) -> VoteReveal {
+        VoteReveal { value: Some(value), ..reveal(voter, 0, 90, voting_power) }
+    }
+
+    /// Rainfall in millimetres with two decimals, 0 to 500.00
+    fn rainfall() -> MarketKind {
+        MarketKind::Scalar { min: ScalarValue(0), max: ScalarValue(50_000), decimals: 2 }
+    }
+
+    #[test]
+    fn test_median() {
+        let reveals = [value_reveal(0, 1_200, 10), value_reveal(1, 1_250, 10), value_reveal(2, 40_000, 10)];
+        // The outlier moves neither the result nor, much, the agreement
+        assert_eq!(Median.aggregate(&reveals, &rainfall()), Some(ScalarAggregate { value: 1_250, confidence: 66 }));
+
+        // By weight, not by head: one heavy voter holds the middle
+        let reveals = [value_reveal(0, 100, 1), value_reveal(1, 300, 5), value_reveal(2, 900, 1)];
+        assert_eq!(Median.aggregate(&reveals, &rainfall()).unwrap().value, 300);
+
+        // An even split takes the lower middle value
+        let reveals = [value_reveal(0, 100, 5), value_reveal(1, 900, 5)];
+        assert_eq!(Median.aggregate(&reveals, &rainfall()).unwrap().value, 100);
+    }
+
+    #[test]
+    fn test_trimmed_mean() {
+        let reveals = [
+            value_reveal(0, 0, 10),
+            value_reveal(1, 1_000, 10),
+            value_reveal(2, 1_100, 10),
+            value_reveal(3, 1_300, 10),
+            value_reveal(4, 50_000, 10),
+        ];
+        // 20% off each end drops both outliers: (1000 + 1100 + 1300) / 3, rounded down
+        let trimmed = TrimmedMean { trim_percentage: 20 };
+        assert_eq!(trimmed.aggregate(&reveals, &rainfall()), Some(ScalarAggregate { value: 1_133, confidence: 60 }));
+        // Untrimmed, the outliers drag it
+        assert_eq!(TrimmedMean { trim_percentage: 0 }.aggregate(&reveals, &rainfall()).unwrap().value, 10_680);
+        // Trimming everything falls back to the median
+        assert_eq!(TrimmedMean { trim_percentage: 50 }.aggregate(&reveals, &rainfall()).unwrap().value, 1_100);
+
+        // Partial weights are trimmed too: 10% of 20 cuts half of each end vote
+        let reveals = [value_reveal(0, 0, 4), value_reveal(1, 100, 12), value_reveal(2, 1_000, 4)];
+        let trimmed = TrimmedMean { trim_percentage: 10 };
+        assert_eq!(trimmed.aggregate(&reveals, &rainfall()).unwrap().value, 200); // (100 * 12 + 1000 * 2) / 16
+    }
+
+    #[test]
+    fn test_scalar_degenerate_inputs() {
+        let scalar: Vec<Box<dyn ScalarAggregator>> = vec![Box::new(Median), Box::new(TrimmedMean { trim_percentage: 10 })];
+        for strategy in scalar {
+            assert_eq!(strategy.aggregate(&[], &rainfall()), None);
+            // Categorical votes, votes outside the range and votes without power are not counted
+            let reveals = [reveal(0, 1, 90, 10), value_reveal(1, -1, 10), value_reveal(2, 50_001, 10), value_reveal(3, 7, 0)];
+            assert_eq!(strategy.aggregate(&reveals, &rainfall()), None);
+            assert_eq!(strategy.aggregate(&[value_reveal(0, 7, 10)], &MarketKind::Categorical), None);
+
+            let extremes = MarketKind::Scalar { min: ScalarValue(i128::MIN), max: ScalarValue(i128::MAX), decimals: 0 };
+            let reveals = [value_reveal(0, i128::MIN, u64::MAX), value_reveal(1, i128::MAX, u64::MAX), value_reveal(2, i128::MAX, 1)];
+            assert_eq!(strategy.aggregate(&reveals, &extremes).unwrap().value, i128::MAX);
+        }
+    }
+
+    fn arb_reveals() -> impl Strategy<Value = (Vec<VoteReveal>, Vec<VoteReveal>)> {
+        prop::collection::vec((0usize..4, 0u8..=100, 0u64..10_000), 0..12)
+            .prop_map(|votes| {
+                votes
+                    .into_iter()
+                    .enumerate()
+                    .map(|(voter, (outcome, confidence, power))| reveal(voter as u8, outcome, confidence, power))
+                    .collect::<Vec<_>>()
+            })
+            .prop_flat_map(|reveals| (Just(reveals.clone()), Just(reveals).prop_shuffle()))
+    }
+
+    fn arb_value_reveals() -> impl Strategy<Value = (Vec<VoteReveal>, Vec<VoteReveal>)> {
+        prop::collection::vec((-100i128..60_000, 0u64..10_000), 0..12)
+            .prop_map(|votes| {
+                votes
+                    .into_iter()
+                    .enumerate()
+                    .map(|(voter, (value, power))| value_reveal(voter as u8, value, power))
+                    .collect::<Vec<_>>()
+            })
+            .prop_flat_map(|reveals| (Just(reveals.clone()), Just(reveals).prop_shuffle()))
+    }
+
+    fn arb_ballot_reveals() -> impl Strategy<Value = (Vec<VoteReveal>, Vec<VoteReveal>)> {
+        let ballot = prop_oneof![
+            (0u16..16).prop_map(Ballot::Approval),
+            Just(vec![0u8, 1, 2, 3]).prop_shuffle().prop_map(Ballot::Ranking),
+        ];
+        prop::collection::vec((ballot, 0u64..10_000), 0..12)
+            .prop_map(|votes| {
+                votes
+                    .into_iter()
+                    .enumerate()
+                    .map(|(voter, (ballot, power))| ballot_reveal(voter as u8, ballot, power))
+                    .collect::<Vec<_>>()
+            })
+            .prop_flat_map(|reveals| (Just(reveals.clone()), Just(reveals).prop_shuffle()))
+    }
+
+    proptest! {
+        #[test]
+        fn ballot_result_is_order_free_and_fits((reveals, shuffled) in arb_ballot_reveals()) {
+            let strategies: [(Box<dyn BallotAggregator>, MarketKind); 2] =
+                [(Box::new(Approval), MarketKind::MultiSelect), (Box::new(Borda), MarketKind::Ranked)];
+            for (strategy, kind) in strategies {
+                let expected = strategy.aggregate(&reveals, 4);
+                prop_assert_eq!(strategy.aggregate(&shuffled, 4), expected.clone());
+                prop_assert!(expected.ballot.fits(&kind, 4));
+                prop_assert!(expected.confidence <= 100);
+            }
+        }
+
+        #[test]
+        fn order_does_not_change_the_result((reveals, shuffled) in arb_reveals()) {
+            let mut reversed = reveals.clone();
+            reversed.reverse();
+
+            for strategy in strategies() {
+                let expected = strategy.aggregate(&reveals, 3);
+                prop_assert_eq!(strategy.aggregate(&shuffled, 3), expected);
+                prop_assert_eq!(strategy.aggregate(&reversed, 3), expected);
+            }
+        }
+
+        #[test]
+        fn scalar_result_is_order_free_and_in_range((reveals, shuffled) in arb_value_reveals(), trim in 0u8..=60) {
+            let kind = rainfall();
+            let scalar: Vec<Box<dyn ScalarAggregator>> = vec![Box::new(Median), Box::new(TrimmedMean { trim_percentage: trim })];
+            for strategy in scalar {
+                let expected = strategy.aggregate(&reveals, &kind);
+                prop_assert_eq!(strategy.aggregate(&shuffled, &kind), expected);
+                if let Some(aggregate) = expected {
+                    prop_assert!(kind.contains(aggregate.value));
+                    prop_assert!(aggregate.confidence <= 100);
+                }
+            }
+        }
+
+        #[test]
+        fn winner_beats_lower_outcomes_strictly((reveals, _) in arb_reveals()) {
+            for strategy in strategies() {
+                let Some(aggregate) = strategy.aggregate(&reveals, 4) else {
+                    continue;
+                };
+                let weight_of = |outcome: usize| -> u128 {
+                    reveals.iter().filter(|r| r.outcome_index == outcome).map(|r| strategy.weight(r)).sum()
+                };
+                let winner = weight_of(aggregate.winning_outcome);
+                for outcome in 0..4 {
+                    if outcome < aggregate.winning_outcome {
+                        prop_assert!(weight_of(outcome) < winner);
+                    } else {
+                        prop_assert!(weight_of(outcome) <= winner);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,124 @@
+// Copyright (c) Alethea Network
+// SPDX-License-Identifier: MIT
+
+//! Multi-select and ranked ballots
+//!
+//! Some questions have several right answers, like which teams make the
+//! playoffs. On a [`MarketKind::MultiSelect`] market a voter approves every
+//! outcome they think is true; on a [`MarketKind::Ranked`] market they order
+//! all of the outcomes, best first. The aggregated result is a ballot of the
+//! same shape.
+
+use serde::{Deserialize, Serialize};
+
+use crate::MarketKind;
+
+/// A voter's answer, or the result, on a multi-select or ranked market
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Ballot {
+    /// Approved outcomes, bit `i` for outcome `i`
+    Approval(u16),
+
+    /// Every outcome index exactly once, best first
+    Ranking(Vec<u8>),
+}
+
+async_graphql::scalar!(Ballot);
+
+impl Ballot {
+    /// Most outcomes a ballot can cover
+    pub const MAX_OUTCOMES: usize = 16;
+
+    /// Whether this is a well-formed answer on a market of `kind` with `num_outcomes` outcomes
+    pub fn fits(&self, kind: &MarketKind, num_outcomes: usize) -> bool {
+        if num_outcomes > Self::MAX_OUTCOMES {
+            return false;
+        }
+        match (self, kind) {
+            (Ballot::Approval(approved), MarketKind::MultiSelect) => u32::from(*approved) >> num_outcomes == 0,
+            (Ballot::Ranking(order), MarketKind::Ranked) => {
+                let mut seen = 0u32;
+                for index in order {
+                    if usize::from(*index) >= num_outcomes || seen & (1 << index) != 0 {
+                        return false;
+                    }
+                    seen |= 1 << index;
+                }
+                order.len() == num_outcomes
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether outcome `index` is approved; false for a ranking
+    pub fn approves(&self, index: usize) -> bool {
+        match self {
+            Ballot::Approval(approved) => index < Self::MAX_OUTCOMES && approved & (1 << index) != 0,
+            Ballot::Ranking(_) => false,
+        }
+    }
+
+    /// Whether a bet on outcome `index` wins against this result
+    ///
+    /// Every approved outcome wins; in a ranking only the top choice does.
+    pub fn picks(&self, index: usize) -> bool {
+        match self {
+            Ballot::Approval(_) => self.approves(index),
+            Ballot::Ranking(order) => order.first().is_some_and(|first| usize::from(*first) == index),
+        }
+    }
+
+    /// Whether this ballot counts as a right answer against `result`
+    ///
+    /// An approval ballot has to approve exactly the result's outcomes; a
+    /// ranking has to put the result's winner first.
+    pub fn agrees_with(&self, result: &Ballot) -> bool {
+        match (self, result) {
+            (Ballot::Approval(approved), Ballot::Approval(expected)) => approved == expected,
+            (Ballot::Ranking(order), Ballot::Ranking(expected)) => {
+                !order.is_empty() && order.first() == expected.first()
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ballot_fits_market() {
+        let playoffs = MarketKind::MultiSelect;
+        assert!(Ballot::Approval(0b0101).fits(&playoffs, 3));
+        assert!(Ballot::Approval(0).fits(&playoffs, 3));
+        assert!(!Ballot::Approval(0b1000).fits(&playoffs, 3));
+        assert!(Ballot::Approval(u16::MAX).fits(&playoffs, 16));
+        assert!(!Ballot::Approval(1).fits(&MarketKind::Ranked, 3));
+
+        let ranked = MarketKind::Ranked;
+        assert!(Ballot::Ranking(vec![2, 0, 1]).fits(&ranked, 3));
+        // Every outcome, each once
+        assert!(!Ballot::Ranking(vec![2, 0]).fits(&ranked, 3));
+        assert!(!Ballot::Ranking(vec![2, 2, 1]).fits(&ranked, 3));
+        assert!(!Ballot::Ranking(vec![3, 0, 1]).fits(&ranked, 3));
+        assert!(!Ballot::Ranking(vec![0, 1, 2]).fits(&MarketKind::Categorical, 3));
+        assert!(!Ballot::Ranking((0..17).collect()).fits(&ranked, 17));
+    }
+
+    #[test]
+    fn test_ballot_agreement() {
+        assert!(Ballot::Approval(0b011).agrees_with(&Ballot::Approval(0b011)));
+        assert!(!Ballot::Approval(0b111).agrees_with(&Ballot::Approval(0b011)));
+        assert!(Ballot::Approval(0b010).approves(1) && !Ballot::Approval(0b010).approves(0));
+        assert!(Ballot::Approval(0b011).picks(1) && !Ballot::Approval(0b011).picks(2));
+        assert!(Ballot::Ranking(vec![2, 0, 1]).picks(2) && !Ballot::Ranking(vec![2, 0, 1]).picks(0));
+        assert!(!Ballot::Ranking(vec![]).picks(0));
+
+        // Only the top choice has to match
+        assert!(Ballot::Ranking(vec![1, 2, 0]).agrees_with(&Ballot::Ranking(vec![1, 0, 2])));
+        assert!(!Ballot::Ranking(vec![0, 1, 2]).agrees_with(&Ballot::Ranking(vec![1, 0, 2])));
+        assert!(!Ballot::Ranking(vec![]).agrees_with(&Ballot::Ranking(vec![])));
+        assert!(!Ballot::Approval(0).agrees_with(&Ballot::Ranking(vec![0])));
+    }
+}
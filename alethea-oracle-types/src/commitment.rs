@@ -0,0 +1,229 @@
+// Copyright (c) Alethea Network
+// SPDX-License-Identifier: MIT
+
+//! Commit-reveal vote commitments
+//!
+//! A commitment is
+//! `SHA-256(domain || version || market_id || voter_chain || outcome_index || confidence || salt)`
+//! with every integer encoded as fixed-width little-endian, so the preimage has the same
+//! layout on native targets and on WASM. The domain tag keeps these hashes apart from any
+//! other SHA-256 use, and binding the market id and voter chain means a commitment cannot
+//! be replayed on another market or claimed by another voter.
+//!
+//! Scalar market votes commit to an `i128` value in place of the outcome index, under
+//! their own domain tag so the two kinds of commitment can never open each other.
+//! Multi-select and ranked votes commit to the whole ballot the same way: a kind byte
+//! (0 approval, 1 ranking), then the approval bitset as `u16` or the ranking's length as
+//! `u64` followed by its outcome indices.
+//!
+//! All chains must agree on [`COMMITMENT_VERSION`]; bump it whenever the preimage changes.
+
+use linera_sdk::linera_base_types::ChainId;
+use sha2::{Digest, Sha256};
+
+use crate::Ballot;
+
+/// Version of the commitment preimage layout
+pub const COMMITMENT_VERSION: u8 = 2;
+
+/// Domain separation tag untuk vote commitments
+const COMMITMENT_DOMAIN: &[u8] = b"alethea-network/vote-commitment";
+
+/// Domain separation tag untuk scalar vote commitments
+const SCALAR_COMMITMENT_DOMAIN: &[u8] = b"alethea-network/scalar-vote-commitment";
+
+/// Domain separation tag untuk multi-select and ranked vote commitments
+const BALLOT_COMMITMENT_DOMAIN: &[u8] = b"alethea-network/ballot-vote-commitment";
+
+/// Compute the commitment hash for a vote
+pub fn compute_commitment(
+    market_id: u64,
+    voter_chain: ChainId,
+    outcome_index: usize,
+    confidence: u8,
+    salt: &[u8; 32],
+) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(COMMITMENT_DOMAIN);
+    hasher.update([COMMITMENT_VERSION]);
+    hasher.update(market_id.to_le_bytes());
+    hasher.update(voter_chain.0.as_bytes().as_slice());
+    hasher.update((outcome_index as u64).to_le_bytes());
+    hasher.update([confidence]);
+    hasher.update(salt);
+    hasher.finalize().into()
+}
+
+/// Check that a revealed vote opens the given commitment
+pub fn verify_commitment(
+    commitment_hash: &[u8; 32],
+    market_id: u64,
+    voter_chain: ChainId,
+    outcome_index: usize,
+    confidence: u8,
+    salt: &[u8; 32],
+) -> bool {
+    compute_commitment(market_id, voter_chain, outcome_index, confidence, salt) == *commitment_hash
+}
+
+/// Compute the commitment hash for a vote on a scalar market
+pub fn compute_scalar_commitment(
+    market_id: u64,
+    voter_chain: ChainId,
+    value: i128,
+    confidence: u8,
+    salt: &[u8; 32],
+) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(SCALAR_COMMITMENT_DOMAIN);
+    hasher.update([COMMITMENT_VERSION]);
+    hasher.update(market_id.to_le_bytes());
+    hasher.update(voter_chain.0.as_bytes().as_slice());
+    hasher.update(value.to_le_bytes());
+    hasher.update([confidence]);
+    hasher.update(salt);
+    hasher.finalize().into()
+}
+
+/// Check that a revealed scalar vote opens the given commitment
+pub fn verify_scalar_commitment(
+    commitment_hash: &[u8; 32],
+    market_id: u64,
+    voter_chain: ChainId,
+    value: i128,
+    confidence: u8,
+    salt: &[u8; 32],
+) -> bool {
+    compute_scalar_commitment(market_id, voter_chain, value, confidence, salt) == *commitment_hash
+}
+
+/// Compute the commitment hash for a ballot on a multi-select or ranked market
+pub fn compute_ballot_commitment(
+    market_id: u64,
+    voter_chain: ChainId,
+    ballot: &Ballot,
+    confidence: u8,
+    salt: &[u8; 32],
+) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(BALLOT_COMMITMENT_DOMAIN);
+    hasher.update([COMMITMENT_VERSION]);
+    hasher.update(market_id.to_le_bytes());
+    hasher.update(voter_chain.0.as_bytes().as_slice());
+    match ballot {
+        Ballot::Approval(approved) => {
+            hasher.update([0u8]);
+            hasher.update(approved.to_le_bytes());
+        }
+        Ballot::Ranking(order) => {
+            hasher.update([1u8]);
+            hasher.update((order.len() as u64).to_le_bytes());
+            hasher.update(order);
+        }
+    }
+    hasher.update([confidence]);
+    hasher.update(salt);
+    hasher.finalize().into()
+}
+
+/// Check that a revealed ballot opens the given commitment
+pub fn verify_ballot_commitment(
+    commitment_hash: &[u8; 32],
+    market_id: u64,
+    voter_chain: ChainId,
+    ballot: &Ballot,
+    confidence: u8,
+    salt: &[u8; 32],
+) -> bool {
+    compute_ballot_commitment(market_id, voter_chain, ballot, confidence, salt) == *commitment_hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linera_sdk::linera_base_types::CryptoHash;
+
+    fn chain(byte: u8) -> ChainId {
+        ChainId(CryptoHash::from([byte; 32]))
+    }
+
+    #[test]
+    fn test_commitment_deterministic() {
+        let salt = [7u8; 32];
+        assert_eq!(
+            compute_commitment(1, chain(1), 0, 80, &salt),
+            compute_commitment(1, chain(1), 0, 80, &salt),
+        );
+    }
+
+    #[test]
+    fn test_commitment_roundtrip() {
+        let salt = [42u8; 32];
+        let hash = compute_commitment(5, chain(1), 2, 80, &salt);
+
+        assert!(verify_commitment(&hash, 5, chain(1), 2, 80, &salt));
+        assert!(!verify_commitment(&hash, 5, chain(1), 1, 80, &salt));
+        assert!(!verify_commitment(&hash, 5, chain(1), 2, 80, &[43u8; 32]));
+        // Confidence is fixed at commit time
+        assert!(!verify_commitment(&hash, 5, chain(1), 2, 100, &salt));
+    }
+
+    #[test]
+    fn test_commitment_domain_separated() {
+        let salt = [9u8; 32];
+        let base = compute_commitment(1, chain(1), 0, 80, &salt);
+
+        // Same vote on another market or from another voter must not collide
+        assert_ne!(base, compute_commitment(2, chain(1), 0, 80, &salt));
+        assert_ne!(base, compute_commitment(1, chain(2), 0, 80, &salt));
+        assert_ne!(base, compute_commitment(1, chain(1), 1, 80, &salt));
+        assert_ne!(base, compute_commitment(1, chain(1), 0, 81, &salt));
+    }
+
+    #[test]
+    fn test_scalar_commitment_roundtrip() {
+        let salt = [11u8; 32];
+        let hash = compute_scalar_commitment(5, chain(1), -1_234, 80, &salt);
+
+        assert!(verify_scalar_commitment(&hash, 5, chain(1), -1_234, 80, &salt));
+        assert!(!verify_scalar_commitment(&hash, 5, chain(1), 1_234, 80, &salt));
+        assert!(!verify_scalar_commitment(&hash, 5, chain(2), -1_234, 80, &salt));
+        // Never opens as a categorical vote, even where the bytes line up
+        assert_ne!(compute_scalar_commitment(5, chain(1), 1, 80, &salt), compute_commitment(5, chain(1), 1, 80, &salt));
+    }
+
+    #[test]
+    fn test_ballot_commitment_covers_whole_ballot() {
+        let salt = [13u8; 32];
+        let ranking = Ballot::Ranking(vec![2, 0, 1]);
+        let hash = compute_ballot_commitment(5, chain(1), &ranking, 80, &salt);
+
+        assert!(verify_ballot_commitment(&hash, 5, chain(1), &ranking, 80, &salt));
+        // Same top choice, different order below it
+        assert!(!verify_ballot_commitment(&hash, 5, chain(1), &Ballot::Ranking(vec![2, 1, 0]), 80, &salt));
+        assert!(!verify_ballot_commitment(&hash, 5, chain(1), &Ballot::Ranking(vec![2, 0]), 80, &salt));
+        assert!(!verify_ballot_commitment(&hash, 6, chain(1), &ranking, 80, &salt));
+
+        let approval = Ballot::Approval(0b101);
+        let hash = compute_ballot_commitment(5, chain(1), &approval, 80, &salt);
+        assert!(verify_ballot_commitment(&hash, 5, chain(1), &approval, 80, &salt));
+        assert!(!verify_ballot_commitment(&hash, 5, chain(1), &Ballot::Approval(0b100), 80, &salt));
+        assert!(!verify_ballot_commitment(&hash, 5, chain(1), &approval, 81, &salt));
+    }
+
+    #[test]
+    fn test_commitment_known_vector() {
+        // Pins the preimage layout: changing it must come with a version bump
+        let mut hasher = Sha256::new();
+        hasher.update(b"alethea-network/vote-commitment");
+        hasher.update([2u8]);
+        hasher.update(3u64.to_le_bytes());
+        hasher.update([4u8; 32]);
+        hasher.update(1u64.to_le_bytes());
+        hasher.update([70u8]);
+        hasher.update([5u8; 32]);
+        let expected: [u8; 32] = hasher.finalize().into();
+
+        assert_eq!(compute_commitment(3, chain(4), 1, 70, &[5u8; 32]), expected);
+    }
+}
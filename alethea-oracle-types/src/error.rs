@@ -0,0 +1,151 @@
+// Copyright (c) Alethea Network
+// SPDX-License-Identifier: MIT
+
+//! Typed errors returned in operation responses
+//!
+//! Responses carry these instead of bare numbers or sentinel ids so clients
+//! can match on the variant. [`CoordinatorError::code`] keeps the old numeric codes for
+//! clients that still compare against them.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Why an Oracle Coordinator operation was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Error, async_graphql::Enum)]
+pub enum CoordinatorError {
+    #[error("market must have between 2 and 10 outcomes")]
+    InvalidOutcomes,
+    #[error("deadlines must be ordered trading < commit < reveal")]
+    InvalidDeadlines,
+    #[error("minimum voters is below the protocol minimum")]
+    MinVotersTooLow,
+    #[error("stake is below the minimum stake")]
+    InsufficientStake,
+    #[error("voter is already registered")]
+    VoterAlreadyRegistered,
+    #[error("market not found")]
+    MarketNotFound,
+    #[error("market is not active")]
+    MarketNotActive,
+    #[error("trading period has not ended")]
+    TradingPeriodNotEnded,
+    #[error("market is not in reveal phase")]
+    NotInRevealPhase,
+    #[error("not enough reveals to resolve market")]
+    NotEnoughReveals,
+    #[error("voting deadline has not been reached")]
+    VotingDeadlineNotReached,
+    #[error("voter not found")]
+    VoterNotFound,
+    #[error("operation must be signed")]
+    Unauthenticated,
+    #[error("signer balance is too low")]
+    InsufficientBalance,
+    #[error("market cannot move to that status")]
+    InvalidTransition,
+    #[error("market is not resolved")]
+    MarketNotResolved,
+    #[error("challenge window has closed")]
+    ChallengeWindowClosed,
+    #[error("challenge window is still open")]
+    ChallengeWindowOpen,
+    #[error("market has already been disputed")]
+    AlreadyDisputed,
+    #[error("dispute bond is below the minimum")]
+    DisputeBondTooLow,
+    #[error("market resolution is already final")]
+    AlreadyFinalized,
+    #[error("scalar market needs min below max and no outcomes")]
+    InvalidScalarRange,
+    #[error("aggregation method does not fit the market kind")]
+    AggregationMismatch,
+}
+
+impl CoordinatorError {
+    /// Numeric code; 1-12 match the values that used to be returned as `error_code`
+    pub fn code(&self) -> u32 {
+        match self {
+            CoordinatorError::InvalidOutcomes => 1,
+            CoordinatorError::InvalidDeadlines => 2,
+            CoordinatorError::MinVotersTooLow => 3,
+            CoordinatorError::InsufficientStake => 4,
+            CoordinatorError::VoterAlreadyRegistered => 5,
+            CoordinatorError::MarketNotFound => 6,
+            CoordinatorError::MarketNotActive => 7,
+            CoordinatorError::TradingPeriodNotEnded => 8,
+            CoordinatorError::NotInRevealPhase => 9,
+            CoordinatorError::NotEnoughReveals => 10,
+            CoordinatorError::VotingDeadlineNotReached => 11,
+            CoordinatorError::VoterNotFound => 12,
+            CoordinatorError::Unauthenticated => 13,
+            CoordinatorError::InsufficientBalance => 14,
+            CoordinatorError::InvalidTransition => 15,
+            CoordinatorError::MarketNotResolved => 16,
+            CoordinatorError::ChallengeWindowClosed => 17,
+            CoordinatorError::ChallengeWindowOpen => 18,
+            CoordinatorError::AlreadyDisputed => 19,
+            CoordinatorError::DisputeBondTooLow => 20,
+            CoordinatorError::AlreadyFinalized => 21,
+            CoordinatorError::InvalidScalarRange => 22,
+            CoordinatorError::AggregationMismatch => 23,
+        }
+    }
+}
+
+/// Why a Voter Chain operation was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Error, async_graphql::Enum)]
+pub enum VoterError {
+    #[error("voter chain has not been initialized")]
+    NotInitialized,
+    #[error("outcome index is out of range for this market")]
+    InvalidOutcome,
+    #[error("confidence must be between 0 and 100")]
+    InvalidConfidence,
+    #[error("no voting request received for this market")]
+    NoPendingRequest,
+    #[error("voting deadline has passed")]
+    DeadlinePassed,
+    #[error("already committed or voted on this market")]
+    AlreadyVoted,
+    #[error("no pending commitment for this market")]
+    NoCommitment,
+    #[error("stored vote does not match its commitment")]
+    CommitmentMismatch,
+    #[error("failed to write voter state")]
+    StorageFailure,
+    #[error("operation must be signed")]
+    Unauthenticated,
+    #[error("signer balance is too low")]
+    InsufficientBalance,
+    #[error("coordinator escrow is not configured")]
+    EscrowNotConfigured,
+    #[error("not enough stake to unstake")]
+    InsufficientStake,
+    #[error("value is outside the market's range")]
+    ValueOutOfRange,
+    #[error("ballot does not fit the market's outcomes")]
+    InvalidBallot,
+}
+
+/// Why a Market Chain operation was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Error, async_graphql::Enum)]
+pub enum MarketError {
+    #[error("market must have between 2 and 10 outcomes")]
+    InvalidOutcomes,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_display_and_codes() {
+        assert_eq!(CoordinatorError::MarketNotFound.to_string(), "market not found");
+        assert_eq!(CoordinatorError::InvalidOutcomes.code(), 1);
+        assert_eq!(CoordinatorError::VoterNotFound.code(), 12);
+
+        let bytes = bcs::to_bytes(&CoordinatorError::NotEnoughReveals).unwrap();
+        let decoded: CoordinatorError = bcs::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, CoordinatorError::NotEnoughReveals);
+    }
+}
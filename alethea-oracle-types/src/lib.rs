@@ -11,12 +11,28 @@
 //! 
 //! By centralizing these types, we ensure consistency and reduce duplication.
 
+// GraphQLMutationRoot generates one argument per operation field
+#![allow(clippy::too_many_arguments)]
+
 use linera_sdk::{
-    linera_base_types::{AccountOwner, Amount, ChainId, Timestamp},
+    linera_base_types::{AccountOwner, Amount, ChainId, TimeDelta, Timestamp},
     abi::{ContractAbi, ServiceAbi},
 };
 use serde::{Deserialize, Serialize};
 
+pub mod aggregation;
+pub mod ballot;
+pub mod commitment;
+pub mod error;
+pub mod rewards;
+pub mod scalar;
+pub mod voting_power;
+
+pub use ballot::Ballot;
+pub use error::{CoordinatorError, MarketError, VoterError};
+pub use scalar::{MarketKind, ScalarValue};
+pub use voting_power::{PowerFactors, StakeCurve};
+
 /// ABI untuk Oracle Coordinator
 pub struct OracleCoordinatorAbi;
 
@@ -48,21 +64,36 @@ impl ServiceAbi for VoterChainAbi {
 #[derive(Debug, Serialize, Deserialize, linera_sdk::graphql::GraphQLMutationRoot)]
 pub enum CoordinatorOperation {
     /// Create new prediction market
+    ///
+    /// Commitments are accepted until `commit_deadline`, reveals from then
+    /// until `reveal_deadline`, after which the market can be aggregated.
     CreateMarket {
         question: String,
         outcomes: Vec<String>,
         trading_deadline: Timestamp,
-        voting_deadline: Timestamp,
+        commit_deadline: Timestamp,
+        reveal_deadline: Timestamp,
         min_voters: u32,
         category: String,
+        /// How reveals are tallied; `Parameters::aggregation_method` if not given,
+        /// or the kind's own default for scalar, multi-select and ranked markets
+        aggregation_method: Option<AggregationMethod>,
+        /// Categorical if not given; a scalar market takes no `outcomes`
+        kind: Option<MarketKind>,
     },
     
-    /// Register voter chain
+    /// Register voter chain, escrowing `initial_stake` from the signer
     RegisterVoter {
         voter_chain: ChainId,
         initial_stake: Amount,
     },
     
+    /// Top up a registered voter's stake from the signer's tokens
+    DepositStake {
+        voter_chain: ChainId,
+        amount: Amount,
+    },
+    
     /// Start voting period untuk market
     StartVoting {
         market_id: u64,
@@ -90,6 +121,28 @@ pub enum CoordinatorOperation {
     GetMarketStats {
         market_id: u64,
     },
+    
+    /// Get the undistributed reward pool of a market
+    GetRewardPool {
+        market_id: u64,
+    },
+    
+    /// Challenge a resolved market's outcome while its challenge window is open
+    ///
+    /// `bond` is escrowed from the signer. The market goes to an appeal round
+    /// with a larger quorum and higher minimum stake; the bond is refunded if
+    /// the appeal overturns the outcome and forfeited to its voters otherwise.
+    DisputeResolution {
+        market_id: u64,
+        bond: Amount,
+    },
+    
+    /// Settle a resolved market once its challenge window has passed
+    ///
+    /// Pays rewards, applies slashes and sends the outcome to requesters.
+    FinalizeResolution {
+        market_id: u64,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -104,6 +157,11 @@ pub enum CoordinatorResponse {
         initial_reputation: u64,
     },
     
+    StakeDeposited {
+        voter_chain: ChainId,
+        total_stake: Amount,
+    },
+    
     VotingStarted {
         market_id: u64,
         total_voters_notified: u32,
@@ -116,14 +174,53 @@ pub enum CoordinatorResponse {
         total_voters: u32,
     },
     
-    Market(Market),
+    /// No outcome reached the consensus threshold
+    MarketInvalid {
+        market_id: u64,
+        confidence: u8,
+        total_voters: u32,
+    },
+    
+    /// A scalar market resolved to `value`
+    ScalarMarketResolved {
+        market_id: u64,
+        value: ScalarValue,
+        confidence: u8,
+        total_voters: u32,
+    },
+    
+    /// A multi-select or ranked market resolved to `ballot`
+    BallotMarketResolved {
+        market_id: u64,
+        ballot: Ballot,
+        confidence: u8,
+        total_voters: u32,
+    },
+    
+    Market(Box<Market>),
     ActiveMarkets(Vec<Market>),
     VoterInfo(VoterInfo),
     MarketStats(MarketStats),
+    RewardPool {
+        market_id: u64,
+        balance: Amount,
+    },
+    
+    DisputeOpened {
+        market_id: u64,
+        min_voters: u32,
+        commit_deadline: Timestamp,
+        reveal_deadline: Timestamp,
+    },
     
-    Error {
-        error_code: u32, // WASM safe - 0=Unknown, 1=NotFound, 2=InvalidParam, etc.
+    ResolutionFinalized {
+        market_id: u64,
+        winning_outcome: Option<usize>,
+        winning_value: Option<ScalarValue>,
+        winning_ballot: Option<Ballot>,
     },
+    
+    Error(CoordinatorError),
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -144,10 +241,12 @@ pub enum VoterOperation {
         stake: Amount,
     },
     
-    /// Commit vote (hidden)
+    /// Commit vote (hidden), salted with client-supplied `seed`
     CommitVote {
         market_id: u64,
         outcome_index: usize,
+        confidence: u8,
+        seed: [u8; 32],
     },
     
     /// Reveal vote
@@ -159,6 +258,7 @@ pub enum VoterOperation {
     SubmitVote {
         market_id: u64,
         outcome_index: usize,
+        confidence: u8,
     },
     
     /// Add more stake
@@ -166,6 +266,14 @@ pub enum VoterOperation {
         amount: Amount,
     },
     
+    /// Start unbonding free stake held by the coordinator
+    RequestUnstake {
+        amount: Amount,
+    },
+    
+    /// Withdraw stake whose unbonding period has passed
+    CompleteUnstake,
+    
     /// Get voter info
     GetVoterInfo,
     
@@ -200,9 +308,17 @@ pub enum VoterResponse {
         new_total: Amount,
     },
     
+    UnstakeRequested {
+        amount: Amount,
+    },
+    
+    UnstakeCompletionRequested,
+    
     VoterInfo(VoterInfo),
     VoteHistory(Vec<VoteRecord>),
     PendingCommitments(Vec<CommitmentInfo>),
+    
+    Error(VoterError),
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -214,13 +330,19 @@ pub enum VoterQuery {
 
 // ==================== CROSS-CHAIN MESSAGES ====================
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Messages exchanged between market, voter and coordinator chains
+///
+/// BCS-encoded, so each variant is identified on the wire by its position.
+/// New variants go at the end; reordering, removing or changing the fields of
+/// an existing variant breaks every chain still running the old code.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Message {
     /// Coordinator -> Voter: Request untuk vote
     VotingRequest {
         market_id: u64,
         question: String,
         outcomes: Vec<String>,
+        /// Commit deadline; votes must be committed (or cast directly) before it
         deadline: Timestamp,
     },
     
@@ -230,6 +352,7 @@ pub enum Message {
         market_id: u64,
         commitment_hash: [u8; 32],
         voting_power: u64,
+        /// Informational; the coordinator locks the stake the round asks for
         stake_amount: Amount,
     },
     
@@ -283,53 +406,248 @@ pub enum Message {
         question: String,
         outcomes: Vec<String>,
     },
+    
+    /// Voter -> Coordinator: `amount` was transferred to the coordinator's escrow account
+    StakeDeposit {
+        voter_chain: ChainId,
+        amount: Amount,
+    },
+    
+    /// Voter -> Coordinator: Move `amount` of free stake into the unbonding queue
+    UnstakeRequest {
+        voter_chain: ChainId,
+        amount: Amount,
+    },
+    
+    /// Voter -> Coordinator: Pay out unbonded stake to the message signer
+    UnstakeWithdrawal {
+        voter_chain: ChainId,
+    },
+    
+    /// Coordinator -> Voter: `amount` of unbonded stake was transferred back
+    StakeWithdrawn {
+        amount: Amount,
+    },
+    
+    /// Market -> Coordinator: `amount` was transferred to the coordinator's escrow
+    /// to fund the reward pool of the market behind an earlier `ResolutionRequest`
+    ResolutionFee {
+        market_id: u64,
+        amount: Amount,
+    },
+    
+    /// Coordinator -> Market Chain: voters reached no consensus; `market_id` is
+    /// the requester's id and positions should be refunded
+    MarketInvalid {
+        market_id: u64,
+        confidence: u8,
+        timestamp: Timestamp,
+    },
+    
+    /// Coordinator -> Voter: Request untuk vote on a scalar market, answered
+    /// with a value between `min` and `max`
+    ScalarVotingRequest {
+        market_id: u64,
+        question: String,
+        min: i128,
+        max: i128,
+        decimals: u8,
+        /// Commit deadline; votes must be committed before it
+        deadline: Timestamp,
+    },
+    
+    /// Voter -> Coordinator: Reveal a scalar vote
+    ScalarVoteReveal {
+        voter_chain: ChainId,
+        market_id: u64,
+        value: i128,
+        salt: [u8; 32],
+        confidence: u8,
+    },
+    
+    /// Market -> Coordinator: Resolution request for a scalar market
+    ScalarResolutionRequest {
+        market_id: u64,
+        question: String,
+        min: i128,
+        max: i128,
+        decimals: u8,
+    },
+    
+    /// Coordinator -> Market Chain: scalar market resolved to `value`
+    ScalarMarketResolved {
+        market_id: u64,
+        value: i128,
+        confidence: u8,
+        timestamp: Timestamp,
+    },
+    
+    /// Coordinator -> Voter: Request untuk vote on a multi-select or ranked
+    /// market, answered with a [`Ballot`] over `outcomes`
+    BallotVotingRequest {
+        market_id: u64,
+        question: String,
+        outcomes: Vec<String>,
+        kind: MarketKind,
+        /// Commit deadline; votes must be committed before it
+        deadline: Timestamp,
+    },
+    
+    /// Voter -> Coordinator: Reveal a ballot
+    BallotVoteReveal {
+        voter_chain: ChainId,
+        market_id: u64,
+        ballot: Ballot,
+        salt: [u8; 32],
+        confidence: u8,
+    },
+    
+    /// Coordinator -> Voter: `amount` of the voter's stake was slashed to the treasury
+    StakeSlashed {
+        amount: Amount,
+    },
+    
+    /// Coordinator -> Voter: an unstake request for `amount` was refused; the stake stays bonded
+    UnstakeRejected {
+        amount: Amount,
+    },
+    
+    /// Market -> Coordinator: Resolution request for a multi-select or ranked market
+    BallotResolutionRequest {
+        market_id: u64,
+        question: String,
+        outcomes: Vec<String>,
+        kind: MarketKind,
+    },
+    
+    /// Coordinator -> Market Chain: multi-select or ranked market resolved to `ballot`
+    BallotResolved {
+        market_id: u64,
+        ballot: Ballot,
+        confidence: u8,
+        timestamp: Timestamp,
+    },
 }
 
 // ==================== DATA STRUCTURES ====================
 
 /// Market information
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, async_graphql::SimpleObject)]
 pub struct Market {
     pub id: u64,
     pub question: String,
+    /// Empty for a scalar market
     pub outcomes: Vec<String>,
+    /// Whether the answer is one of `outcomes`, a number, or a ballot over `outcomes`
+    pub kind: MarketKind,
     pub creator: Option<AccountOwner>,
     
     // Timing
     pub created_at: Timestamp,
     pub trading_deadline: Timestamp,
-    pub voting_deadline: Timestamp,
+    /// Commitments close here and reveals open
+    pub commit_deadline: Timestamp,
+    /// Reveals close here; the market can then be aggregated
+    pub reveal_deadline: Timestamp,
     
     // Requirements
     pub min_voters: u32,
     
     // Metadata
     pub category: String,
+    /// How reveals are tallied, fixed when the market is created
+    pub aggregation_method: AggregationMethod,
     
     // Status
     pub status: MarketStatus,
     pub winning_outcome: Option<usize>,
+    /// Result of a scalar market, in place of `winning_outcome`
+    pub winning_value: Option<ScalarValue>,
+    /// Result of a multi-select or ranked market, in place of `winning_outcome`
+    pub winning_ballot: Option<Ballot>,
     pub confidence_score: Option<u8>,
     
     // Statistics
     pub total_commitments: u32,
     pub total_reveals: u32,
+    
+    // Disputes
+    /// Disputes are accepted until here; None until the market resolves
+    pub challenge_deadline: Option<Timestamp>,
+    /// Set once rewards, slashes and the resolution messages have gone out
+    pub finalized: bool,
+    /// Challenge that sent the market to its appeal round, if any
+    pub dispute: Option<Dispute>,
 }
 
-/// Market status
+/// A challenge to a market's first resolution
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, async_graphql::SimpleObject)]
+pub struct Dispute {
+    /// Signer that posted the bond, and gets it back if the outcome is overturned
+    pub disputer: AccountOwner,
+    pub bond: Amount,
+    /// Outcome the first round resolved to
+    pub disputed_outcome: usize,
+    /// Value the first round resolved to, on a scalar market
+    pub disputed_value: Option<ScalarValue>,
+    /// Ballot the first round resolved to, on a multi-select or ranked market
+    pub disputed_ballot: Option<Ballot>,
+    pub disputed_at: Timestamp,
+}
+
+/// A chain waiting on a market's resolution
+///
+/// `market_id` is the requester's own id for the market, which is what the
+/// resolution is reported under; it need not match the coordinator's id.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ResolutionRequester {
+    pub chain_id: ChainId,
+    pub market_id: u64,
+}
+
+/// Market status
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, async_graphql::Enum)]
 pub enum MarketStatus {
     Active,           // Trading period
     VotingStarted,    // Voting announced
-    CommitPhase,      // Commitments being collected
-    RevealPhase,      // Reveals being collected
-    Aggregating,      // Computing result
+    CommitPhase,      // Commitments being collected, until commit_deadline
+    RevealPhase,      // Reveals being collected, until reveal_deadline
+    Aggregating,      // Reveals closed, waiting for aggregation
     Resolved,         // Final outcome determined
     Disputed,         // Under dispute
+    Invalid,          // No consensus; nothing resolved, positions refunded
+}
+
+impl MarketStatus {
+    /// Every allowed status change; [`Market::transition`] rejects anything else
+    ///
+    /// Voting phases may be skipped when a deadline has already passed, but
+    /// never revisited. A disputed resolution goes through the voting phases
+    /// once more as an appeal round.
+    pub const TRANSITIONS: &'static [(MarketStatus, MarketStatus)] = &[
+        (MarketStatus::Active, MarketStatus::VotingStarted),
+        (MarketStatus::VotingStarted, MarketStatus::CommitPhase),
+        (MarketStatus::VotingStarted, MarketStatus::RevealPhase),
+        (MarketStatus::VotingStarted, MarketStatus::Aggregating),
+        (MarketStatus::CommitPhase, MarketStatus::RevealPhase),
+        (MarketStatus::CommitPhase, MarketStatus::Aggregating),
+        (MarketStatus::RevealPhase, MarketStatus::Aggregating),
+        (MarketStatus::Aggregating, MarketStatus::Resolved),
+        (MarketStatus::Aggregating, MarketStatus::Invalid),
+        (MarketStatus::Resolved, MarketStatus::Disputed),
+        (MarketStatus::Disputed, MarketStatus::CommitPhase),
+        (MarketStatus::Disputed, MarketStatus::RevealPhase),
+        (MarketStatus::Disputed, MarketStatus::Aggregating),
+    ];
+
+    /// Whether the table allows moving from `self` to `next`
+    pub fn can_transition_to(self, next: MarketStatus) -> bool {
+        MarketStatus::TRANSITIONS.contains(&(self, next))
+    }
 }
 
 /// Voter information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
 pub struct VoterInfo {
     pub chain_id: ChainId,
     
@@ -343,13 +661,35 @@ pub struct VoterInfo {
     // Stake
     pub total_stake: Amount,
     pub locked_stake: Amount,
+    /// Waiting out the unbonding period; not counted as voting power, still slashable
+    pub unbonding_stake: Amount,
     
     // Timestamps
     pub registered_at: Timestamp,
     pub last_active: Timestamp,
 }
 
+/// Reputation score every newly registered voter starts with
+pub const INITIAL_REPUTATION: u64 = 100;
+
 impl VoterInfo {
+    /// Fresh voter with the starting reputation and nothing locked
+    pub fn new(chain_id: ChainId, total_stake: Amount, registered_at: Timestamp) -> Self {
+        VoterInfo {
+            chain_id,
+            reputation_score: INITIAL_REPUTATION,
+            total_votes: 0,
+            total_correct: 0,
+            total_incorrect: 0,
+            correct_streak: 0,
+            total_stake,
+            locked_stake: Amount::ZERO,
+            unbonding_stake: Amount::ZERO,
+            registered_at,
+            last_active: registered_at,
+        }
+    }
+
     /// Calculate accuracy rate
     pub fn accuracy_rate(&self) -> f64 {
         if self.total_votes == 0 {
@@ -358,23 +698,29 @@ impl VoterInfo {
         (self.total_correct as f64 / self.total_votes as f64) * 100.0
     }
 
-    /// Calculate voting power (reputation-based)
-    pub fn voting_power(&self) -> u64 {
-        let base_power = self.reputation_score;
-        
-        // Bonus untuk high accuracy
-        let accuracy_multiplier = if self.accuracy_rate() > 90.0 {
-            1.5
-        } else if self.accuracy_rate() > 80.0 {
-            1.2
-        } else {
-            1.0
-        };
-        
-        (base_power as f64 * accuracy_multiplier) as u64
+    /// Accuracy in basis points, the integer form voting power is computed from
+    pub fn accuracy_bps(&self) -> u64 {
+        voting_power::accuracy_bps(u64::from(self.total_correct), u64::from(self.total_votes))
+    }
+
+    /// Calculate voting power from reputation, accuracy and stake
+    pub fn voting_power(&self, curve: StakeCurve) -> u64 {
+        self.power_factors(curve).voting_power()
+    }
+
+    /// Reputation and stake factors behind [`Self::voting_power`]
+    pub fn power_factors(&self, curve: StakeCurve) -> PowerFactors {
+        PowerFactors::new(self.reputation_score, self.accuracy_bps(), self.total_stake, curve)
     }
 }
 
+/// Stake queued for withdrawal, released once `release_at` has passed
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct UnbondingEntry {
+    pub amount: Amount,
+    pub release_at: Timestamp,
+}
+
 /// Vote commitment (hidden vote)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VoteCommitment {
@@ -382,6 +728,8 @@ pub struct VoteCommitment {
     pub market_id: u64,
     pub commitment_hash: [u8; 32],
     pub voting_power: u64,
+    /// What `voting_power` is made of, for aggregation that weights by only one
+    pub power_factors: PowerFactors,
     pub stake_amount: Amount,
     pub committed_at: Timestamp,
 }
@@ -392,9 +740,15 @@ pub struct VoteReveal {
     pub voter_chain: ChainId,
     pub market_id: u64,
     pub outcome_index: usize,
+    /// Answer on a scalar market; `outcome_index` is unused then
+    pub value: Option<i128>,
+    /// Answer on a multi-select or ranked market; `outcome_index` is unused then
+    pub ballot: Option<Ballot>,
     pub salt: [u8; 32],
     pub confidence: u8,
     pub voting_power: u64,
+    /// What `voting_power` is made of, for aggregation that weights by only one
+    pub power_factors: PowerFactors,
     pub revealed_at: Timestamp,
 }
 
@@ -419,7 +773,7 @@ pub struct CommitmentInfo {
 }
 
 /// Market statistics
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
 pub struct MarketStats {
     pub market_id: u64,
     pub total_commitments: u32,
@@ -450,11 +804,71 @@ pub struct Parameters {
     /// Percentage stake yang di-slash untuk incorrect vote (0-100)
     pub slash_percentage: u8,
     
-    /// Minimum consensus percentage untuk resolusi (0-100)
+    /// Percentage of committed stake slashed when a commitment is never revealed (0-100)
+    #[serde(default = "default_non_reveal_slash_percentage")]
+    pub non_reveal_slash_percentage: u8,
+    
+    /// Minimum consensus percentage untuk resolusi (0-100), unless the market's
+    /// aggregation method sets its own; below it the market ends Invalid
     pub min_consensus: u8,
     
-    /// Reward pool percentage dari fees (0-100)
+    /// Share of each market's fee pool paid to correct voters (0-100); the rest goes to the treasury
     pub reward_pool_percentage: u8,
+    
+    /// How stake is weighted in voting power
+    #[serde(default)]
+    pub voting_power_curve: StakeCurve,
+    
+    /// Aggregation for markets created without one
+    #[serde(default)]
+    pub aggregation_method: AggregationMethod,
+    
+    /// Commit window for markets opened by a market chain's resolution request, and for appeal rounds
+    #[serde(default = "default_phase_period")]
+    pub commit_period: TimeDelta,
+    
+    /// Reveal window that follows the commit window for those markets
+    #[serde(default = "default_phase_period")]
+    pub reveal_period: TimeDelta,
+    
+    /// Time after resolution during which the outcome can be disputed
+    #[serde(default = "default_phase_period")]
+    pub challenge_period: TimeDelta,
+    
+    /// Smallest bond accepted with a dispute
+    #[serde(default = "default_min_dispute_bond")]
+    pub min_dispute_bond: Amount,
+    
+    /// Appeal rounds need this many times the market's quorum and the minimum stake
+    #[serde(default = "default_appeal_multiplier")]
+    pub appeal_multiplier: u32,
+    
+    /// Delay between requesting an unstake and being able to withdraw it
+    #[serde(default = "default_unbonding_period")]
+    pub unbonding_period: TimeDelta,
+}
+
+/// Withholding a reveal costs more than voting wrong
+fn default_non_reveal_slash_percentage() -> u8 {
+    20
+}
+
+/// One day
+fn default_phase_period() -> TimeDelta {
+    TimeDelta::from_secs(24 * 60 * 60)
+}
+
+/// Seven days
+fn default_unbonding_period() -> TimeDelta {
+    TimeDelta::from_secs(7 * 24 * 60 * 60)
+}
+
+fn default_min_dispute_bond() -> Amount {
+    Amount::from_tokens(500)
+}
+
+fn default_appeal_multiplier() -> u32 {
+    2
 }
 
 impl Default for Parameters {
@@ -462,8 +876,17 @@ impl Default for Parameters {
         Parameters {
             min_stake: Amount::from_tokens(100),
             slash_percentage: 10, // 10% slash
+            non_reveal_slash_percentage: default_non_reveal_slash_percentage(),
             min_consensus: 66,    // 66% majority
             reward_pool_percentage: 80, // 80% to voters
+            voting_power_curve: StakeCurve::default(),
+            aggregation_method: AggregationMethod::default(),
+            commit_period: default_phase_period(),
+            reveal_period: default_phase_period(),
+            unbonding_period: default_unbonding_period(),
+            challenge_period: default_phase_period(),
+            min_dispute_bond: default_min_dispute_bond(),
+            appeal_multiplier: default_appeal_multiplier(),
         }
     }
 }
@@ -477,8 +900,8 @@ pub struct InitialState {
 
 // ==================== AGGREGATION ====================
 
-/// Aggregation method
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Aggregation method, see [`aggregation`] for how each one tallies
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AggregationMethod {
     /// Simple majority (most votes wins)
     SimpleMajority,
@@ -495,6 +918,21 @@ pub enum AggregationMethod {
     
     /// Quadratic voting
     QuadraticVoting,
+    
+    /// Scalar markets: the value with half the voting power on either side
+    Median,
+    
+    /// Scalar markets: mean by voting power, after dropping `trim_percentage`
+    /// of it from each end
+    TrimmedMean {
+        trim_percentage: u8,
+    },
+    
+    /// Multi-select markets: every outcome approved by more than half the voting power
+    Approval,
+    
+    /// Ranked markets: outcomes ordered by Borda count, weighted by voting power
+    Borda,
 }
 
 impl Default for AggregationMethod {
@@ -507,14 +945,230 @@ impl Default for AggregationMethod {
     }
 }
 
+async_graphql::scalar!(AggregationMethod);
+
 // ==================== HELPER FUNCTIONS ====================
 
 impl Market {
+    /// New market in the trading period, with no votes yet
+    pub fn new(
+        id: u64,
+        question: String,
+        outcomes: Vec<String>,
+        creator: Option<AccountOwner>,
+        created_at: Timestamp,
+        trading_deadline: Timestamp,
+        commit_deadline: Timestamp,
+        reveal_deadline: Timestamp,
+        min_voters: u32,
+        category: String,
+        aggregation_method: AggregationMethod,
+        kind: MarketKind,
+    ) -> Self {
+        Market {
+            id,
+            question,
+            outcomes,
+            kind,
+            creator,
+            created_at,
+            trading_deadline,
+            commit_deadline,
+            reveal_deadline,
+            min_voters,
+            category,
+            aggregation_method,
+            status: MarketStatus::Active,
+            winning_outcome: None,
+            winning_value: None,
+            winning_ballot: None,
+            confidence_score: None,
+            total_commitments: 0,
+            total_reveals: 0,
+            challenge_deadline: None,
+            finalized: false,
+            dispute: None,
+        }
+    }
+
+    /// Change status, only along [`MarketStatus::TRANSITIONS`]
+    ///
+    /// All status changes go through here; on error the status is unchanged.
+    pub fn transition(&mut self, next: MarketStatus) -> Result<(), CoordinatorError> {
+        if !self.status.can_transition_to(next) {
+            return Err(CoordinatorError::InvalidTransition);
+        }
+        self.status = next;
+        Ok(())
+    }
+
+    /// Move a market that is being voted on into the phase its deadlines give at `now`
+    ///
+    /// Phases follow the clock only, never the order messages arrive in.
+    /// Returns whether the status changed.
+    pub fn advance_phase(&mut self, now: Timestamp) -> bool {
+        if !self.is_voting_phase() {
+            return false;
+        }
+        let phase = if now < self.commit_deadline {
+            MarketStatus::CommitPhase
+        } else if now < self.reveal_deadline {
+            MarketStatus::RevealPhase
+        } else {
+            MarketStatus::Aggregating
+        };
+        phase != self.status && self.transition(phase).is_ok()
+    }
+
+    /// Count a commitment
+    pub fn record_commitment(&mut self) {
+        self.total_commitments += 1;
+    }
+
+    /// Count a verified reveal
+    pub fn record_reveal(&mut self) {
+        self.total_reveals += 1;
+    }
+
+    /// Count a direct vote (no commit-reveal)
+    pub fn record_direct_vote(&mut self) {
+        self.total_reveals += 1;
+    }
+
+    /// Mark the market resolved with the aggregated outcome
+    pub fn resolve(&mut self, winning_outcome: usize, confidence: u8) -> Result<(), CoordinatorError> {
+        self.transition(MarketStatus::Resolved)?;
+        self.winning_outcome = Some(winning_outcome);
+        self.confidence_score = Some(confidence);
+        Ok(())
+    }
+
+    /// Mark a scalar market resolved with the aggregated value
+    pub fn resolve_value(&mut self, value: i128, confidence: u8) -> Result<(), CoordinatorError> {
+        self.transition(MarketStatus::Resolved)?;
+        self.winning_value = Some(ScalarValue(value));
+        self.confidence_score = Some(confidence);
+        Ok(())
+    }
+
+    /// Mark a multi-select or ranked market resolved with the aggregated ballot
+    pub fn resolve_ballot(&mut self, ballot: Ballot, confidence: u8) -> Result<(), CoordinatorError> {
+        self.transition(MarketStatus::Resolved)?;
+        self.winning_ballot = Some(ballot);
+        self.confidence_score = Some(confidence);
+        Ok(())
+    }
+
+    /// End the market without an outcome; `confidence` is the best share any outcome got
+    pub fn invalidate(&mut self, confidence: u8) -> Result<(), CoordinatorError> {
+        self.transition(MarketStatus::Invalid)?;
+        self.winning_outcome = None;
+        self.winning_value = None;
+        self.winning_ballot = None;
+        self.confidence_score = Some(confidence);
+        Ok(())
+    }
+
+    /// Whether `reveal` matches the market's result; None while there is none
+    ///
+    /// A scalar vote is right if it agrees with the result within the
+    /// market's tolerance, see [`MarketKind::agrees`], and a ballot if it
+    /// agrees with the resulting ballot, see [`Ballot::agrees_with`].
+    pub fn reveal_is_correct(&self, reveal: &VoteReveal) -> Option<bool> {
+        match self.kind {
+            MarketKind::Categorical => {
+                let result = self.winning_outcome?;
+                Some(reveal.value.is_none() && reveal.ballot.is_none() && reveal.outcome_index == result)
+            }
+            MarketKind::Scalar { .. } => {
+                let result = self.winning_value?;
+                Some(reveal.value.is_some_and(|value| self.kind.agrees(value, result.0)))
+            }
+            MarketKind::MultiSelect | MarketKind::Ranked => {
+                let result = self.winning_ballot.as_ref()?;
+                Some(reveal.ballot.as_ref().is_some_and(|ballot| ballot.agrees_with(result)))
+            }
+        }
+    }
+
+    /// Whether the market's result confirms what `dispute` challenged
+    pub fn upholds(&self, dispute: &Dispute) -> bool {
+        match self.kind {
+            MarketKind::Categorical => self.winning_outcome == Some(dispute.disputed_outcome),
+            MarketKind::Scalar { .. } => match (self.winning_value, dispute.disputed_value) {
+                (Some(result), Some(disputed)) => self.kind.agrees(result.0, disputed.0),
+                _ => false,
+            },
+            MarketKind::MultiSelect | MarketKind::Ranked => {
+                match (&self.winning_ballot, &dispute.disputed_ballot) {
+                    (Some(result), Some(disputed)) => disputed.agrees_with(result),
+                    _ => false,
+                }
+            }
+        }
+    }
+
+    /// Whether a dispute can still be filed at `now`
+    pub fn can_be_disputed(&self, now: Timestamp) -> bool {
+        self.is_resolved()
+            && !self.finalized
+            && self.dispute.is_none()
+            && self.challenge_deadline.is_some_and(|deadline| now < deadline)
+    }
+
+    /// Send a resolved market to an appeal round
+    ///
+    /// The first round's outcome is cleared and votes are collected again
+    /// between the new deadlines, from a quorum of `min_voters`.
+    pub fn open_appeal(
+        &mut self,
+        dispute: Dispute,
+        commit_deadline: Timestamp,
+        reveal_deadline: Timestamp,
+        min_voters: u32,
+    ) -> Result<(), CoordinatorError> {
+        if self.dispute.is_some() {
+            return Err(CoordinatorError::AlreadyDisputed);
+        }
+        self.transition(MarketStatus::Disputed)?;
+        self.dispute = Some(dispute);
+        self.commit_deadline = commit_deadline;
+        self.reveal_deadline = reveal_deadline;
+        self.min_voters = min_voters;
+        self.winning_outcome = None;
+        self.winning_value = None;
+        self.winning_ballot = None;
+        self.confidence_score = None;
+        self.challenge_deadline = None;
+        self.total_commitments = 0;
+        self.total_reveals = 0;
+        Ok(())
+    }
+
+    /// Participation and confidence summary
+    pub fn stats(&self) -> MarketStats {
+        MarketStats {
+            market_id: self.id,
+            total_commitments: self.total_commitments,
+            total_reveals: self.total_reveals,
+            participation_rate: if self.total_commitments > 0 {
+                (self.total_reveals as f64 / self.total_commitments as f64 * 100.0) as u8
+            } else {
+                0
+            },
+            confidence_score: self.confidence_score.unwrap_or(0),
+            status: self.status,
+        }
+    }
+
     /// Check if market is in voting phase
     pub fn is_voting_phase(&self) -> bool {
         matches!(
             self.status,
-            MarketStatus::VotingStarted | MarketStatus::CommitPhase | MarketStatus::RevealPhase
+            MarketStatus::VotingStarted
+                | MarketStatus::Disputed
+                | MarketStatus::CommitPhase
+                | MarketStatus::RevealPhase
         )
     }
     
@@ -523,11 +1177,16 @@ impl Market {
         self.status == MarketStatus::Resolved
     }
     
+    /// Check if voting ended without consensus
+    pub fn is_invalid(&self) -> bool {
+        self.status == MarketStatus::Invalid
+    }
+    
     /// Check if commit phase is active
     pub fn is_commit_phase(&self) -> bool {
         matches!(
             self.status,
-            MarketStatus::VotingStarted | MarketStatus::CommitPhase
+            MarketStatus::VotingStarted | MarketStatus::Disputed | MarketStatus::CommitPhase
         )
     }
     
@@ -556,80 +1215,64 @@ impl VoteCommitment {
             return false;
         }
         
-        // Compute hash dari reveal - WASM SAFE (fixed array)
-        let mut data = [0u8; 40]; // 8 bytes outcome + 32 bytes salt
-        data[..8].copy_from_slice(&reveal.outcome_index.to_le_bytes());
-        data[8..40].copy_from_slice(&reveal.salt);
+        if let Some(ballot) = &reveal.ballot {
+            return commitment::verify_ballot_commitment(
+                &self.commitment_hash,
+                reveal.market_id,
+                reveal.voter_chain,
+                ballot,
+                reveal.confidence,
+                &reveal.salt,
+            );
+        }
         
-        let computed_hash = simple_hash(&data);
-        computed_hash == self.commitment_hash
-    }
-}
-
-/// Simple hash function (sama dengan contract implementation) - WASM SAFE
-pub fn simple_hash(data: &[u8]) -> [u8; 32] {
-    if data.is_empty() {
-        return [0u8; 32];
-    }
-
-    let mut result = [0u8; 32];
-    
-    // Store length in first 4 bytes
-    let len = data.len() as u64;
-    result[0] = (len & 0xFF) as u8;
-    result[1] = ((len >> 8) & 0xFF) as u8;
-    result[2] = ((len >> 16) & 0xFF) as u8;
-    result[3] = ((len >> 24) & 0xFF) as u8;
-    
-    // Safety limit to prevent infinite loops
-    const MAX_HASH_INPUT: usize = 10_000;
-    let max_len = data.len().min(MAX_HASH_INPUT);
-    
-    // XOR all data into result
-    for i in 0..max_len {
-        let pos = i % 32;
-        result[pos] ^= data[i];
-    }
-    
-    // Simple diffusion
-    for i in 0..32 {
-        let a = result[i];
-        let b = result[(i + 7) % 32];
-        result[i] = a.wrapping_add(b);
+        if let Some(value) = reveal.value {
+            return commitment::verify_scalar_commitment(
+                &self.commitment_hash,
+                reveal.market_id,
+                reveal.voter_chain,
+                value,
+                reveal.confidence,
+                &reveal.salt,
+            );
+        }
+        
+        commitment::verify_commitment(
+            &self.commitment_hash,
+            reveal.market_id,
+            reveal.voter_chain,
+            reveal.outcome_index,
+            reveal.confidence,
+            &reveal.salt,
+        )
     }
-    
-    result
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use linera_sdk::linera_base_types::CryptoHash;
+    use proptest::prelude::*;
 
-    #[test]
-    fn test_simple_hash_deterministic() {
-        let data = b"test data";
-        let hash1 = simple_hash(data);
-        let hash2 = simple_hash(data);
-        assert_eq!(hash1, hash2);
+    fn test_chain(byte: u8) -> ChainId {
+        ChainId(CryptoHash::from([byte; 32]))
     }
 
     #[test]
     fn test_commitment_verify() {
-        let voter_chain = ChainId::root(0);
+        let voter_chain = test_chain(0);
         let market_id = 1;
         let outcome = 0;
         let salt = [42u8; 32];
 
-        let mut data = [0u8; 40];
-        data[..8].copy_from_slice(&outcome.to_le_bytes());
-        data[8..40].copy_from_slice(&salt);
-        let hash = simple_hash(&data);
+        let hash = commitment::compute_commitment(market_id, voter_chain, outcome, 95, &salt);
 
         let commitment = VoteCommitment {
             voter_chain,
             market_id,
             commitment_hash: hash,
             voting_power: 100,
+            power_factors: PowerFactors { reputation: 100, stake: 1 },
             stake_amount: Amount::ZERO,
             committed_at: Timestamp::from(0),
         };
@@ -638,22 +1281,212 @@ mod tests {
             voter_chain,
             market_id,
             outcome_index: outcome,
+            value: None,
+            ballot: None,
             salt,
             confidence: 95,
             voting_power: 100,
+            power_factors: PowerFactors { reputation: 100, stake: 1 },
             revealed_at: Timestamp::from(1000),
         };
 
         assert!(commitment.verify_reveal(&reveal));
+
+        // A different outcome with the same salt must not open the commitment
+        let forged = VoteReveal { outcome_index: 1, ..reveal.clone() };
+        assert!(!commitment.verify_reveal(&forged));
+
+        // Confidence cannot be raised after commit
+        let boosted = VoteReveal { confidence: 100, ..reveal.clone() };
+        assert!(!commitment.verify_reveal(&boosted));
+
+        // Nor can another voter claim it
+        let stolen = VoteReveal { voter_chain: test_chain(1), ..reveal.clone() };
+        assert!(!commitment.verify_reveal(&stolen));
+
+        // A scalar vote opens only a scalar commitment
+        let scalar = VoteReveal { value: Some(outcome as i128), ..reveal.clone() };
+        assert!(!commitment.verify_reveal(&scalar));
+        let hash = commitment::compute_scalar_commitment(market_id, voter_chain, 1_250, 95, &salt);
+        let commitment = VoteCommitment { commitment_hash: hash, ..commitment };
+        assert!(commitment.verify_reveal(&VoteReveal { value: Some(1_250), ..reveal.clone() }));
+        assert!(!commitment.verify_reveal(&VoteReveal { value: Some(1_251), ..reveal.clone() }));
+        assert!(!commitment.verify_reveal(&reveal));
+
+        // And a ballot only a ballot commitment
+        let ballot = Ballot::Ranking(vec![1, 0]);
+        assert!(!commitment.verify_reveal(&VoteReveal { ballot: Some(ballot.clone()), ..reveal.clone() }));
+        let hash = commitment::compute_ballot_commitment(market_id, voter_chain, &ballot, 95, &salt);
+        let commitment = VoteCommitment { commitment_hash: hash, ..commitment };
+        assert!(commitment.verify_reveal(&VoteReveal { ballot: Some(ballot), ..reveal.clone() }));
+        assert!(!commitment.verify_reveal(&VoteReveal { ballot: Some(Ballot::Ranking(vec![0, 1])), ..reveal.clone() }));
+    }
+
+    /// Wire tag of each variant; the exhaustive match makes new variants pin theirs
+    fn wire_tag(message: &Message) -> u8 {
+        match message {
+            Message::VotingRequest { .. } => 0,
+            Message::VoteCommitment { .. } => 1,
+            Message::VoteReveal { .. } => 2,
+            Message::DirectVote { .. } => 3,
+            Message::ReputationUpdate { .. } => 4,
+            Message::RewardPayment { .. } => 5,
+            Message::MarketResolved { .. } => 6,
+            Message::MarketResolutionRequest { .. } => 7,
+            Message::ResolutionRequest { .. } => 8,
+            Message::StakeDeposit { .. } => 9,
+            Message::UnstakeRequest { .. } => 10,
+            Message::UnstakeWithdrawal { .. } => 11,
+            Message::StakeWithdrawn { .. } => 12,
+            Message::ResolutionFee { .. } => 13,
+            Message::MarketInvalid { .. } => 14,
+            Message::ScalarVotingRequest { .. } => 15,
+            Message::ScalarVoteReveal { .. } => 16,
+            Message::ScalarResolutionRequest { .. } => 17,
+            Message::ScalarMarketResolved { .. } => 18,
+            Message::BallotVotingRequest { .. } => 19,
+            Message::BallotVoteReveal { .. } => 20,
+            Message::StakeSlashed { .. } => 21,
+            Message::UnstakeRejected { .. } => 22,
+            Message::BallotResolutionRequest { .. } => 23,
+            Message::BallotResolved { .. } => 24,
+        }
+    }
+
+    fn sample_messages() -> Vec<Message> {
+        vec![
+            Message::VotingRequest {
+                market_id: 1,
+                question: "Q?".to_string(),
+                outcomes: vec!["Yes".to_string(), "No".to_string()],
+                deadline: Timestamp::from(10),
+            },
+            Message::VoteCommitment {
+                voter_chain: test_chain(1),
+                market_id: 1,
+                commitment_hash: [2u8; 32],
+                voting_power: 100,
+                stake_amount: Amount::from_tokens(5),
+            },
+            Message::VoteReveal {
+                voter_chain: test_chain(1),
+                market_id: 1,
+                outcome_index: 1,
+                salt: [3u8; 32],
+                confidence: 80,
+            },
+            Message::DirectVote {
+                voter_chain: test_chain(1),
+                market_id: 1,
+                outcome_index: 0,
+                confidence: 70,
+                voting_power: 100,
+            },
+            Message::ReputationUpdate { increase: true, amount: 10 },
+            Message::RewardPayment { amount: Amount::from_tokens(1), market_id: 1 },
+            Message::MarketResolved {
+                market_id: 1,
+                outcome: 0,
+                confidence: 90,
+                timestamp: Timestamp::from(20),
+            },
+            Message::MarketResolutionRequest { market_id: 1, requester: test_chain(2) },
+            Message::ResolutionRequest {
+                market_id: 1,
+                question: "Q?".to_string(),
+                outcomes: vec!["Yes".to_string(), "No".to_string()],
+            },
+            Message::StakeDeposit { voter_chain: test_chain(1), amount: Amount::from_tokens(3) },
+            Message::UnstakeRequest { voter_chain: test_chain(1), amount: Amount::from_tokens(2) },
+            Message::UnstakeWithdrawal { voter_chain: test_chain(1) },
+            Message::StakeWithdrawn { amount: Amount::from_tokens(2) },
+            Message::ResolutionFee { market_id: 1, amount: Amount::from_tokens(4) },
+            Message::MarketInvalid { market_id: 1, confidence: 40, timestamp: Timestamp::from(30) },
+            Message::ScalarVotingRequest {
+                market_id: 1,
+                question: "Rainfall?".to_string(),
+                min: 0,
+                max: 50_000,
+                decimals: 2,
+                deadline: Timestamp::from(10),
+            },
+            Message::ScalarVoteReveal {
+                voter_chain: test_chain(1),
+                market_id: 1,
+                value: -1_234,
+                salt: [3u8; 32],
+                confidence: 80,
+            },
+            Message::ScalarResolutionRequest {
+                market_id: 1,
+                question: "Rainfall?".to_string(),
+                min: 0,
+                max: 50_000,
+                decimals: 2,
+            },
+            Message::ScalarMarketResolved {
+                market_id: 1,
+                value: 1_250,
+                confidence: 90,
+                timestamp: Timestamp::from(20),
+            },
+            Message::BallotVotingRequest {
+                market_id: 1,
+                question: "Playoffs?".to_string(),
+                outcomes: vec!["A".to_string(), "B".to_string(), "C".to_string()],
+                kind: MarketKind::MultiSelect,
+                deadline: Timestamp::from(10),
+            },
+            Message::BallotVoteReveal {
+                voter_chain: test_chain(1),
+                market_id: 1,
+                ballot: Ballot::Ranking(vec![2, 0, 1]),
+                salt: [3u8; 32],
+                confidence: 80,
+            },
+            Message::StakeSlashed { amount: Amount::from_tokens(10) },
+            Message::UnstakeRejected { amount: Amount::from_tokens(5) },
+            Message::BallotResolutionRequest {
+                market_id: 1,
+                question: "Playoff teams?".to_string(),
+                outcomes: vec!["A".to_string(), "B".to_string(), "C".to_string()],
+                kind: MarketKind::MultiSelect,
+            },
+            Message::BallotResolved {
+                market_id: 1,
+                ballot: Ballot::Approval(0b101),
+                confidence: 70,
+                timestamp: Timestamp::from(2000),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_message_wire_roundtrip() {
+        for message in sample_messages() {
+            let bytes = bcs::to_bytes(&message).expect("Message should serialize");
+            assert_eq!(bytes[0], wire_tag(&message), "Wire tag changed for {:?}", message);
+
+            let decoded: Message = bcs::from_bytes(&bytes).expect("Message should deserialize");
+            assert_eq!(decoded, message);
+        }
+    }
+
+    #[test]
+    fn test_sample_messages_cover_every_variant() {
+        let tags: Vec<u8> = sample_messages().iter().map(wire_tag).collect();
+        let expected: Vec<u8> = (0..tags.len() as u8).collect();
+        assert_eq!(tags, expected);
     }
 
     #[test]
     fn test_voter_accuracy_rate() {
         let voter = VoterInfo {
-            chain_id: ChainId::root(0),
+            chain_id: test_chain(0),
             reputation_score: 100,
-            total_stake: Amount::ZERO,
+            total_stake: Amount::from_tokens(100),
             locked_stake: Amount::ZERO,
+            unbonding_stake: Amount::ZERO,
             total_votes: 10,
             total_correct: 8,
             total_incorrect: 2,
@@ -663,7 +1496,9 @@ mod tests {
         };
 
         assert_eq!(voter.accuracy_rate(), 80.0);
-        assert_eq!(voter.voting_power(), 120); // 100 * 1.2
+        assert_eq!(voter.accuracy_bps(), 8_000);
+        assert_eq!(voter.voting_power(StakeCurve::Linear), 12_000); // 100 * 1.2 * 100
+        assert_eq!(voter.voting_power(StakeCurve::Sqrt), 1_200); // 100 * 1.2 * 10
     }
     
     #[test]
@@ -672,17 +1507,25 @@ mod tests {
             id: 1,
             question: "Test?".to_string(),
             outcomes: vec!["Yes".to_string(), "No".to_string()],
-            creator: AccountOwner::from(ChainId::root(0)),
+            kind: MarketKind::Categorical,
+            creator: Some(AccountOwner::CHAIN),
             created_at: Timestamp::from(0),
             trading_deadline: Timestamp::from(1000),
-            voting_deadline: Timestamp::from(2000),
+            commit_deadline: Timestamp::from(2000),
+            reveal_deadline: Timestamp::from(3000),
             min_voters: 3,
             category: "test".to_string(),
+            aggregation_method: AggregationMethod::default(),
             status: MarketStatus::CommitPhase,
             winning_outcome: None,
+            winning_value: None,
+            winning_ballot: None,
             confidence_score: None,
             total_commitments: 0,
             total_reveals: 0,
+            challenge_deadline: None,
+            finalized: false,
+            dispute: None,
         };
         
         assert!(market.is_voting_phase());
@@ -694,5 +1537,358 @@ mod tests {
         assert!(market.is_resolved());
         assert!(!market.is_voting_phase());
     }
-}
 
+    #[test]
+    fn test_market_vote_bookkeeping() {
+        let mut market = Market::new(
+            1,
+            "Test?".to_string(),
+            vec!["Yes".to_string(), "No".to_string()],
+            None,
+            Timestamp::from(0),
+            Timestamp::from(1000),
+            Timestamp::from(2000),
+            Timestamp::from(3000),
+            3,
+            "test".to_string(),
+            AggregationMethod::default(),
+            MarketKind::Categorical,
+        );
+        assert_eq!(market.status, MarketStatus::Active);
+        // Not being voted on yet: the clock does not move it
+        assert!(!market.advance_phase(Timestamp::from(2500)));
+
+        market.transition(MarketStatus::VotingStarted).unwrap();
+        market.record_commitment();
+        market.record_commitment();
+        market.record_reveal();
+        // Votes alone do not change the phase
+        assert_eq!(market.status, MarketStatus::VotingStarted);
+
+        let stats = market.stats();
+        assert_eq!((stats.total_commitments, stats.total_reveals), (2, 1));
+        assert_eq!(stats.participation_rate, 50);
+
+        // Reveals have to close first
+        assert_eq!(market.resolve(1, 90), Err(CoordinatorError::InvalidTransition));
+        assert_eq!(market.winning_outcome, None);
+
+        market.advance_phase(Timestamp::from(3000));
+        market.resolve(1, 90).unwrap();
+        assert!(market.is_resolved());
+        assert_eq!(market.winning_outcome, Some(1));
+        assert_eq!(market.stats().confidence_score, 90);
+    }
+
+    #[test]
+    fn test_market_phase_follows_deadlines() {
+        let mut market = Market::new(
+            1,
+            "Test?".to_string(),
+            vec!["Yes".to_string(), "No".to_string()],
+            None,
+            Timestamp::from(0),
+            Timestamp::from(1000),
+            Timestamp::from(2000),
+            Timestamp::from(3000),
+            3,
+            "test".to_string(),
+            AggregationMethod::default(),
+            MarketKind::Categorical,
+        );
+        market.transition(MarketStatus::VotingStarted).unwrap();
+
+        assert!(market.advance_phase(Timestamp::from(1500)));
+        assert_eq!(market.status, MarketStatus::CommitPhase);
+        assert!(!market.advance_phase(Timestamp::from(1999)));
+
+        assert!(market.advance_phase(Timestamp::from(2000)));
+        assert_eq!(market.status, MarketStatus::RevealPhase);
+
+        assert!(market.advance_phase(Timestamp::from(3000)));
+        assert_eq!(market.status, MarketStatus::Aggregating);
+        // Once reveals close there is no way back
+        assert!(!market.advance_phase(Timestamp::from(1500)));
+    }
+
+    #[test]
+    fn test_scalar_market_result() {
+        let kind = MarketKind::Scalar { min: ScalarValue(0), max: ScalarValue(50_000), decimals: 2 };
+        let mut market = Market::new(
+            1,
+            "Rainfall?".to_string(),
+            vec![],
+            None,
+            Timestamp::from(0),
+            Timestamp::from(1000),
+            Timestamp::from(2000),
+            Timestamp::from(3000),
+            3,
+            "weather".to_string(),
+            AggregationMethod::Median,
+            kind,
+        );
+        let vote = |value: Option<i128>| VoteReveal {
+            voter_chain: test_chain(1),
+            market_id: 1,
+            outcome_index: 0,
+            value,
+            ballot: None,
+            salt: [0u8; 32],
+            confidence: 90,
+            voting_power: 100,
+            power_factors: PowerFactors { reputation: 100, stake: 1 },
+            revealed_at: Timestamp::from(2500),
+        };
+        assert_eq!(market.reveal_is_correct(&vote(Some(1_250))), None);
+
+        market.transition(MarketStatus::VotingStarted).unwrap();
+        market.advance_phase(Timestamp::from(3000));
+        market.resolve_value(1_250, 80).unwrap();
+        assert_eq!((market.winning_outcome, market.winning_value), (None, Some(ScalarValue(1_250))));
+
+        // Within 1% of the 0-500.00 range counts as right
+        assert_eq!(market.reveal_is_correct(&vote(Some(1_750))), Some(true));
+        assert_eq!(market.reveal_is_correct(&vote(Some(749))), Some(false));
+        assert_eq!(market.reveal_is_correct(&vote(None)), Some(false));
+
+        let dispute = |value: i128| Dispute {
+            disputer: AccountOwner::CHAIN,
+            bond: Amount::ONE,
+            disputed_outcome: 0,
+            disputed_value: Some(ScalarValue(value)),
+            disputed_ballot: None,
+            disputed_at: Timestamp::from(3000),
+        };
+        assert!(market.upholds(&dispute(1_300)));
+        assert!(!market.upholds(&dispute(5_000)));
+    }
+
+    #[test]
+    fn test_ballot_market_result() {
+        let mut market = Market::new(
+            1,
+            "Who makes the playoffs?".to_string(),
+            vec!["A".to_string(), "B".to_string(), "C".to_string()],
+            None,
+            Timestamp::from(0),
+            Timestamp::from(1000),
+            Timestamp::from(2000),
+            Timestamp::from(3000),
+            3,
+            "sports".to_string(),
+            AggregationMethod::Approval,
+            MarketKind::MultiSelect,
+        );
+        let vote = |ballot: Option<Ballot>| VoteReveal {
+            voter_chain: test_chain(1),
+            market_id: 1,
+            outcome_index: 0,
+            value: None,
+            ballot,
+            salt: [0u8; 32],
+            confidence: 90,
+            voting_power: 100,
+            power_factors: PowerFactors { reputation: 100, stake: 1 },
+            revealed_at: Timestamp::from(2500),
+        };
+        assert_eq!(market.reveal_is_correct(&vote(Some(Ballot::Approval(0b011)))), None);
+
+        market.transition(MarketStatus::VotingStarted).unwrap();
+        market.advance_phase(Timestamp::from(3000));
+        market.resolve_ballot(Ballot::Approval(0b011), 80).unwrap();
+        assert_eq!(market.winning_outcome, None);
+
+        assert_eq!(market.reveal_is_correct(&vote(Some(Ballot::Approval(0b011)))), Some(true));
+        assert_eq!(market.reveal_is_correct(&vote(Some(Ballot::Approval(0b001)))), Some(false));
+        // A bare outcome index is no answer here, even the first approved one
+        assert_eq!(market.reveal_is_correct(&vote(None)), Some(false));
+
+        let dispute = |ballot: Ballot| Dispute {
+            disputer: AccountOwner::CHAIN,
+            bond: Amount::ONE,
+            disputed_outcome: 0,
+            disputed_value: None,
+            disputed_ballot: Some(ballot),
+            disputed_at: Timestamp::from(3000),
+        };
+        assert!(market.upholds(&dispute(Ballot::Approval(0b011))));
+        assert!(!market.upholds(&dispute(Ballot::Approval(0b111))));
+
+        market.invalidate(0).unwrap_err();
+        assert!(market.winning_ballot.is_some());
+    }
+
+    #[test]
+    fn test_market_invalidate() {
+        let mut market = Market::new(
+            1,
+            "Test?".to_string(),
+            vec!["Yes".to_string(), "No".to_string(), "Maybe".to_string()],
+            None,
+            Timestamp::from(0),
+            Timestamp::from(1000),
+            Timestamp::from(2000),
+            Timestamp::from(3000),
+            3,
+            "test".to_string(),
+            AggregationMethod::default(),
+            MarketKind::Categorical,
+        );
+        assert_eq!(market.invalidate(34), Err(CoordinatorError::InvalidTransition));
+
+        market.transition(MarketStatus::VotingStarted).unwrap();
+        market.advance_phase(Timestamp::from(3000));
+        market.invalidate(34).unwrap();
+        assert!(market.is_invalid() && !market.is_resolved());
+        assert_eq!((market.winning_outcome, market.confidence_score), (None, Some(34)));
+        // Terminal: neither resolvable nor disputable afterwards
+        assert_eq!(market.resolve(0, 34), Err(CoordinatorError::InvalidTransition));
+        assert!(!market.can_be_disputed(Timestamp::from(3000)));
+    }
+
+    #[test]
+    fn test_market_appeal_round() {
+        let mut market = Market::new(
+            1,
+            "Test?".to_string(),
+            vec!["Yes".to_string(), "No".to_string()],
+            None,
+            Timestamp::from(0),
+            Timestamp::from(1000),
+            Timestamp::from(2000),
+            Timestamp::from(3000),
+            3,
+            "test".to_string(),
+            AggregationMethod::default(),
+            MarketKind::Categorical,
+        );
+        let dispute = Dispute {
+            disputer: AccountOwner::CHAIN,
+            bond: Amount::from_tokens(500),
+            disputed_outcome: 1,
+            disputed_value: None,
+            disputed_ballot: None,
+            disputed_at: Timestamp::from(3500),
+        };
+        let appeal = |market: &mut Market| {
+            market.open_appeal(dispute.clone(), Timestamp::from(5000), Timestamp::from(6000), 6)
+        };
+        assert_eq!(appeal(&mut market), Err(CoordinatorError::InvalidTransition));
+
+        market.transition(MarketStatus::VotingStarted).unwrap();
+        market.advance_phase(Timestamp::from(3000));
+        market.record_reveal();
+        market.resolve(1, 90).unwrap();
+        assert!(!market.can_be_disputed(Timestamp::from(3500))); // No challenge window set
+        market.challenge_deadline = Some(Timestamp::from(4000));
+        assert!(market.can_be_disputed(Timestamp::from(3500)));
+        assert!(!market.can_be_disputed(Timestamp::from(4000)));
+
+        appeal(&mut market).unwrap();
+        assert_eq!(market.status, MarketStatus::Disputed);
+        assert_eq!((market.winning_outcome, market.total_reveals, market.min_voters), (None, 0, 6));
+        assert!(market.is_commit_phase());
+        assert!(market.advance_phase(Timestamp::from(5500)));
+        assert_eq!(market.status, MarketStatus::RevealPhase);
+
+        // One appeal per market
+        market.advance_phase(Timestamp::from(6000));
+        market.resolve(0, 80).unwrap();
+        assert_eq!(appeal(&mut market), Err(CoordinatorError::AlreadyDisputed));
+        assert_eq!(market.status, MarketStatus::Resolved);
+    }
+
+    const STATUSES: [MarketStatus; 8] = [
+        MarketStatus::Active,
+        MarketStatus::VotingStarted,
+        MarketStatus::CommitPhase,
+        MarketStatus::RevealPhase,
+        MarketStatus::Aggregating,
+        MarketStatus::Resolved,
+        MarketStatus::Disputed,
+        MarketStatus::Invalid,
+    ];
+
+    /// Something the coordinator can do to a market
+    #[derive(Debug, Clone)]
+    enum MarketAction {
+        Transition(MarketStatus),
+        Advance(u64),
+        Resolve(usize),
+        Invalidate,
+        Appeal(u64),
+    }
+
+    fn market_action() -> impl Strategy<Value = MarketAction> {
+        prop_oneof![
+            prop::sample::select(STATUSES.to_vec()).prop_map(MarketAction::Transition),
+            (0u64..4000).prop_map(MarketAction::Advance),
+            (0usize..2).prop_map(MarketAction::Resolve),
+            Just(MarketAction::Invalidate),
+            (0u64..4000).prop_map(MarketAction::Appeal),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn transition_follows_table(from in prop::sample::select(STATUSES.to_vec()), to in prop::sample::select(STATUSES.to_vec())) {
+            let mut market = Market::new(
+                1, "Q?".to_string(), vec!["Yes".to_string(), "No".to_string()], None,
+                Timestamp::from(0), Timestamp::from(1000), Timestamp::from(2000), Timestamp::from(3000),
+                3, "test".to_string(), AggregationMethod::default(), MarketKind::Categorical,
+            );
+            market.status = from;
+
+            let result = market.transition(to);
+            prop_assert_eq!(result.is_ok(), MarketStatus::TRANSITIONS.contains(&(from, to)));
+            prop_assert_eq!(market.status, if result.is_ok() { to } else { from });
+        }
+
+        #[test]
+        fn no_invalid_transition_is_reachable(actions in prop::collection::vec(market_action(), 0..40)) {
+            let mut market = Market::new(
+                1, "Q?".to_string(), vec!["Yes".to_string(), "No".to_string()], None,
+                Timestamp::from(0), Timestamp::from(1000), Timestamp::from(2000), Timestamp::from(3000),
+                3, "test".to_string(), AggregationMethod::default(), MarketKind::Categorical,
+            );
+
+            let mut resolved_once = false;
+            for action in actions {
+                let before = market.status;
+                match action {
+                    MarketAction::Transition(next) => { let _ = market.transition(next); }
+                    MarketAction::Advance(now) => { market.advance_phase(Timestamp::from(now)); }
+                    MarketAction::Resolve(outcome) => { let _ = market.resolve(outcome, 90); }
+                    MarketAction::Invalidate => { let _ = market.invalidate(40); }
+                    MarketAction::Appeal(now) => {
+                        let dispute = Dispute {
+                            disputer: AccountOwner::CHAIN,
+                            bond: Amount::ONE,
+                            disputed_outcome: market.winning_outcome.unwrap_or(0),
+                            disputed_value: None,
+                            disputed_ballot: None,
+                            disputed_at: Timestamp::from(now),
+                        };
+                        let _ = market.open_appeal(dispute, Timestamp::from(now + 1000), Timestamp::from(now + 2000), 6);
+                    }
+                }
+                let after = market.status;
+                prop_assert!(before == after || before.can_transition_to(after), "{:?} -> {:?}", before, after);
+                // An outcome is only recorded by getting to Resolved
+                resolved_once |= market.status == MarketStatus::Resolved;
+                if market.winning_outcome.is_some() {
+                    prop_assert!(resolved_once);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_new_voter_starts_unlocked() {
+        let voter = VoterInfo::new(test_chain(1), Amount::from_tokens(5), Timestamp::from(7));
+        assert_eq!(voter.reputation_score, INITIAL_REPUTATION);
+        assert_eq!(voter.locked_stake, Amount::ZERO);
+        assert_eq!(voter.last_active, voter.registered_at);
+    }
+}
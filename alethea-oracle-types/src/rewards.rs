@@ -0,0 +1,127 @@
+// Copyright (c) Alethea Network
+// SPDX-License-Identifier: MIT
+
+//! Integer reward and slash arithmetic
+//!
+//! Everything is computed in attos with `u128`, never through `f64`, so the
+//! coordinator pays out exactly what a pool holds. Rounding leftovers ("dust")
+//! are handed out by a fixed rule so every validator gets the same split.
+
+use linera_sdk::linera_base_types::Amount;
+
+/// `percentage` (clamped to 100) of `amount`, rounded down
+pub fn percentage_of(amount: Amount, percentage: u8) -> Amount {
+    let attos = u128::from(amount);
+    let percentage = u128::from(percentage.min(100));
+    // Split before multiplying so large amounts cannot overflow
+    Amount::from_attos(attos / 100 * percentage + attos % 100 * percentage / 100)
+}
+
+/// Split `pool` between `weights` in proportion, down to the atto
+///
+/// Each share is rounded down first; the leftover attos then go one each to
+/// the largest rounding remainders, ties to the lower index. Shares sum to
+/// exactly `pool` unless every weight is zero, in which case all are zero.
+pub fn split_pro_rata(pool: Amount, weights: &[u64]) -> Vec<Amount> {
+    let mut weights: Vec<u128> = weights.iter().map(|weight| u128::from(*weight)).collect();
+    let mut total: u128 = weights.iter().sum();
+    if total == 0 {
+        return vec![Amount::ZERO; weights.len()];
+    }
+
+    // Keep `remainder * weight` within u128; the shift is the same for everyone
+    while total > u128::from(u64::MAX) {
+        weights.iter_mut().for_each(|weight| *weight >>= 1);
+        total = weights.iter().sum();
+    }
+    if total == 0 {
+        return vec![Amount::ZERO; weights.len()];
+    }
+
+    let pool = u128::from(pool);
+    let (quotient, remainder) = (pool / total, pool % total);
+    let mut shares = Vec::with_capacity(weights.len());
+    let mut fractions = Vec::with_capacity(weights.len());
+    for (index, weight) in weights.iter().enumerate() {
+        let scaled = remainder * weight;
+        shares.push(quotient * weight + scaled / total);
+        fractions.push((scaled % total, index));
+    }
+
+    let dust = pool - shares.iter().sum::<u128>();
+    fractions.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    for (_, index) in fractions.into_iter().take(dust as usize) {
+        shares[index] += 1;
+    }
+
+    shares.into_iter().map(Amount::from_attos).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_percentage_of() {
+        assert_eq!(percentage_of(Amount::from_tokens(100), 10), Amount::from_tokens(10));
+        assert_eq!(percentage_of(Amount::from_attos(199), 50), Amount::from_attos(99));
+        assert_eq!(percentage_of(Amount::from_tokens(7), 150), Amount::from_tokens(7));
+        assert_eq!(percentage_of(Amount::MAX, 100), Amount::MAX);
+    }
+
+    #[test]
+    fn test_split_assigns_dust_deterministically() {
+        // 10 attos over three equal weights: 3 each, the spare atto to the first
+        let shares = split_pro_rata(Amount::from_attos(10), &[1, 1, 1]);
+        assert_eq!(shares, vec![Amount::from_attos(4), Amount::from_attos(3), Amount::from_attos(3)]);
+
+        // Largest remainder wins over index: 7 * 2/3 = 4.67, 7 * 1/3 = 2.33
+        let shares = split_pro_rata(Amount::from_attos(7), &[1, 2]);
+        assert_eq!(shares, vec![Amount::from_attos(2), Amount::from_attos(5)]);
+
+        assert_eq!(split_pro_rata(Amount::from_tokens(5), &[0, 0]), vec![Amount::ZERO; 2]);
+        assert!(split_pro_rata(Amount::from_tokens(5), &[]).is_empty());
+    }
+
+    #[test]
+    fn test_split_whole_tokens() {
+        let shares = split_pro_rata(Amount::from_tokens(80), &[1_000, 1_000]);
+        assert_eq!(shares, vec![Amount::from_tokens(40); 2]);
+    }
+
+    proptest! {
+        #[test]
+        fn split_sums_to_pool(pool in any::<u128>(), weights in prop::collection::vec(any::<u64>(), 1..20)) {
+            let shares = split_pro_rata(Amount::from_attos(pool), &weights);
+            prop_assert_eq!(shares.len(), weights.len());
+
+            let paid: u128 = shares.iter().map(|share| u128::from(*share)).sum();
+            if weights.iter().any(|weight| *weight > 0) {
+                prop_assert_eq!(paid, pool);
+            } else {
+                prop_assert_eq!(paid, 0);
+            }
+        }
+
+        #[test]
+        fn split_is_proportional(pool in 0u128..10u128.pow(30), weights in prop::collection::vec(0u64..1_000_000, 1..20)) {
+            let total: u128 = weights.iter().map(|weight| u128::from(*weight)).sum();
+            prop_assume!(total > 0);
+
+            let shares = split_pro_rata(Amount::from_attos(pool), &weights);
+            for (share, weight) in shares.iter().zip(&weights) {
+                // Within one atto of the exact share
+                let exact_floor = pool * u128::from(*weight) / total;
+                let share = u128::from(*share);
+                prop_assert!(share == exact_floor || share == exact_floor + 1);
+            }
+        }
+
+        #[test]
+        fn percentage_never_exceeds_amount(amount in any::<u128>(), percentage in any::<u8>()) {
+            let part = u128::from(percentage_of(Amount::from_attos(amount), percentage));
+            prop_assert!(part <= amount);
+        }
+    }
+}
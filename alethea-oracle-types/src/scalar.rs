@@ -0,0 +1,157 @@
+// Copyright (c) Alethea Network
+// SPDX-License-Identifier: MIT
+
+//! Scalar (numeric) markets
+//!
+//! A scalar market asks for a number instead of one of a list of outcomes.
+//! Answers are integers in the market's unit with `decimals` fixed-point
+//! digits, so 12.34 with two decimals is `1234`.
+
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A number answered on a scalar market
+///
+/// Plain `i128` in BCS. JSON and GraphQL have no 128-bit integers, so there it
+/// is a decimal string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct ScalarValue(pub i128);
+
+impl fmt::Display for ScalarValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Serialize for ScalarValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&self.0.to_string())
+        } else {
+            serializer.serialize_i128(self.0)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ScalarValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let text = String::deserialize(deserializer)?;
+            text.parse().map(ScalarValue).map_err(serde::de::Error::custom)
+        } else {
+            i128::deserialize(deserializer).map(ScalarValue)
+        }
+    }
+}
+
+async_graphql::scalar!(ScalarValue);
+
+/// What kind of answer a market asks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MarketKind {
+    /// One of the market's `outcomes`, by index
+    #[default]
+    Categorical,
+
+    /// A number between `min` and `max`, inclusive
+    Scalar {
+        min: ScalarValue,
+        max: ScalarValue,
+        decimals: u8,
+    },
+
+    /// Any set of the market's `outcomes`, see [`crate::Ballot::Approval`]
+    MultiSelect,
+
+    /// All of the market's `outcomes` in order, see [`crate::Ballot::Ranking`]
+    Ranked,
+}
+
+async_graphql::scalar!(MarketKind);
+
+impl MarketKind {
+    /// Share of the range within which two answers count as agreeing (0-100)
+    pub const TOLERANCE_PERCENTAGE: u128 = 1;
+
+    pub fn is_scalar(&self) -> bool {
+        matches!(self, MarketKind::Scalar { .. })
+    }
+
+    /// Whether votes on this market are [`crate::Ballot`]s
+    pub fn is_ballot(&self) -> bool {
+        matches!(self, MarketKind::MultiSelect | MarketKind::Ranked)
+    }
+
+    /// Whether `value` is an answer this market accepts
+    pub fn contains(&self, value: i128) -> bool {
+        match self {
+            MarketKind::Scalar { min, max, .. } => (min.0..=max.0).contains(&value),
+            _ => false,
+        }
+    }
+
+    /// Whether `min` is below `max`; other kinds are always valid
+    pub fn is_valid(&self) -> bool {
+        match self {
+            MarketKind::Scalar { min, max, .. } => min < max,
+            _ => true,
+        }
+    }
+
+    /// Whether two answers are close enough to count as the same
+    ///
+    /// Within [`Self::TOLERANCE_PERCENTAGE`] of the range, rounded down, so a
+    /// narrow range asks for an exact match.
+    pub fn agrees(&self, a: i128, b: i128) -> bool {
+        match self {
+            MarketKind::Scalar { min, max, .. } => {
+                let tolerance = max.0.abs_diff(min.0) / 100 * Self::TOLERANCE_PERCENTAGE;
+                a.abs_diff(b) <= tolerance
+            }
+            _ => a == b,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind(min: i128, max: i128) -> MarketKind {
+        MarketKind::Scalar { min: ScalarValue(min), max: ScalarValue(max), decimals: 2 }
+    }
+
+    #[test]
+    fn test_range_and_tolerance() {
+        let rainfall = kind(0, 50_000);
+        assert!(rainfall.contains(0) && rainfall.contains(50_000));
+        assert!(!rainfall.contains(-1) && !rainfall.contains(50_001));
+        assert!(!MarketKind::Categorical.contains(0));
+        assert!(!MarketKind::MultiSelect.contains(0) && MarketKind::MultiSelect.is_ballot());
+        assert!(!rainfall.is_ballot() && !MarketKind::Ranked.is_scalar());
+
+        assert!(rainfall.agrees(1_000, 1_500) && rainfall.agrees(1_500, 1_000));
+        assert!(!rainfall.agrees(1_000, 1_501));
+        // Under 100 units wide: only the same answer agrees
+        assert!(!kind(0, 99).agrees(1, 2));
+
+        assert!(rainfall.is_valid() && MarketKind::Categorical.is_valid());
+        assert!(!kind(5, 5).is_valid());
+        assert!(kind(i128::MIN, i128::MAX).agrees(i128::MIN, i128::MIN + 1));
+    }
+
+    #[test]
+    fn test_value_encoding() {
+        let value = ScalarValue(-170_141_183_460_469_231_731_687_303_715_884_105_728);
+        // GraphQL gets a string
+        let graphql = async_graphql::to_value(value).unwrap();
+        assert_eq!(graphql, async_graphql::Value::from("-170141183460469231731687303715884105728"));
+        assert_eq!(async_graphql::from_value::<ScalarValue>(graphql).unwrap(), value);
+
+        // Full width in BCS
+        let bytes = bcs::to_bytes(&value).unwrap();
+        assert_eq!(bytes.len(), 16);
+        assert_eq!(bcs::from_bytes::<ScalarValue>(&bytes).unwrap(), value);
+    }
+}
@@ -0,0 +1,143 @@
+// Copyright (c) Alethea Network
+// SPDX-License-Identifier: MIT
+
+//! Voting power shared by voter chains and the coordinator
+//!
+//! Power is `reputation * accuracy bonus * stake weight`, where the stake
+//! weight comes from whole staked tokens run through a [`StakeCurve`]. The
+//! voter chain uses it to report its power, and the coordinator recomputes it
+//! from its own `VoterInfo` records instead of trusting that report.
+//!
+//! Accuracy and the bonus are integer basis points, so both sides get the
+//! same power without going through `f64`.
+
+use linera_sdk::linera_base_types::Amount;
+use serde::{Deserialize, Serialize};
+
+/// How staked tokens translate into vote weight
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum StakeCurve {
+    /// One weight per staked token
+    Linear,
+    /// Square root of staked tokens, dampens large stakers
+    #[default]
+    Sqrt,
+    /// Linear up to `max_tokens`, flat afterwards
+    Capped { max_tokens: u64 },
+}
+
+impl StakeCurve {
+    /// Weight for `stake`, counted in whole tokens
+    pub fn weight(&self, stake: Amount) -> u64 {
+        let tokens = u128::from(stake) / u128::from(Amount::ONE);
+        let weight = match self {
+            StakeCurve::Linear => tokens,
+            StakeCurve::Sqrt => tokens.isqrt(),
+            StakeCurve::Capped { max_tokens } => tokens.min(u128::from(*max_tokens)),
+        };
+        u64::try_from(weight).unwrap_or(u64::MAX)
+    }
+}
+
+/// 100% in basis points
+pub const BASIS_POINTS: u64 = 10_000;
+
+/// Share of `correct` in `total` votes in basis points, rounded down; 0 with no votes
+pub fn accuracy_bps(correct: u64, total: u64) -> u64 {
+    if total == 0 {
+        return 0;
+    }
+    let accuracy = u128::from(correct.min(total)) * u128::from(BASIS_POINTS) / u128::from(total);
+    accuracy as u64 // At most BASIS_POINTS
+}
+
+/// Accuracy bonus in basis points: 15_000 at 90% accuracy or better, 12_000 at 80%, else 10_000
+pub fn accuracy_multiplier(accuracy_bps: u64) -> u64 {
+    if accuracy_bps >= 9_000 {
+        15_000
+    } else if accuracy_bps >= 8_000 {
+        12_000
+    } else {
+        BASIS_POINTS
+    }
+}
+
+/// The two factors of voting power, kept apart so aggregation can weight by either
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct PowerFactors {
+    /// Reputation with the accuracy bonus applied
+    pub reputation: u64,
+    /// Stake run through the [`StakeCurve`]
+    pub stake: u64,
+}
+
+impl PowerFactors {
+    /// Factors from reputation, accuracy in basis points, stake and stake curve
+    pub fn new(reputation: u64, accuracy_bps: u64, stake: Amount, curve: StakeCurve) -> Self {
+        let multiplier = accuracy_multiplier(accuracy_bps);
+        let reputation = u128::from(reputation) * u128::from(multiplier) / u128::from(BASIS_POINTS);
+        PowerFactors {
+            reputation: u64::try_from(reputation).unwrap_or(u64::MAX),
+            stake: curve.weight(stake),
+        }
+    }
+
+    /// Both factors multiplied, the voter's voting power
+    pub fn voting_power(&self) -> u64 {
+        let power = u128::from(self.reputation) * u128::from(self.stake);
+        u64::try_from(power).unwrap_or(u64::MAX)
+    }
+}
+
+/// Voting power from reputation, accuracy in basis points, stake and stake curve
+pub fn voting_power(reputation: u64, accuracy_bps: u64, stake: Amount, curve: StakeCurve) -> u64 {
+    PowerFactors::new(reputation, accuracy_bps, stake, curve).voting_power()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_curves() {
+        let stake = Amount::from_tokens(400);
+        assert_eq!(StakeCurve::Linear.weight(stake), 400);
+        assert_eq!(StakeCurve::Sqrt.weight(stake), 20);
+        assert_eq!(StakeCurve::Sqrt.weight(Amount::from_tokens(399)), 19); // Rounded down
+        assert_eq!(StakeCurve::Capped { max_tokens: 100 }.weight(stake), 100);
+        assert_eq!(StakeCurve::Capped { max_tokens: 1000 }.weight(stake), 400);
+    }
+
+    #[test]
+    fn test_stake_counted_in_tokens_not_attos() {
+        // Fractions of a token carry no weight; raw attos must never leak through
+        assert_eq!(StakeCurve::Linear.weight(Amount::from_attos(10u128.pow(18) - 1)), 0);
+        assert_eq!(StakeCurve::Linear.weight(Amount::from_tokens(1)), 1);
+    }
+
+    #[test]
+    fn test_accuracy_in_basis_points() {
+        assert_eq!(accuracy_bps(0, 0), 0);
+        assert_eq!(accuracy_bps(4, 5), 8_000);
+        assert_eq!(accuracy_bps(2, 3), 6_666);
+        assert_eq!(accuracy_bps(u64::MAX, u64::MAX), BASIS_POINTS);
+        // 89.99% is still below the top bonus
+        assert_eq!(accuracy_multiplier(accuracy_bps(8_999, 10_000)), 12_000);
+        assert_eq!(accuracy_multiplier(accuracy_bps(9, 10)), 15_000);
+        assert_eq!(accuracy_multiplier(accuracy_bps(79, 100)), BASIS_POINTS);
+    }
+
+    #[test]
+    fn test_voting_power() {
+        let stake = Amount::from_tokens(100);
+        assert_eq!(voting_power(100, 5_000, stake, StakeCurve::Sqrt), 1000);
+        assert_eq!(voting_power(100, 8_000, stake, StakeCurve::Sqrt), 1200);
+        assert_eq!(voting_power(100, 9_500, stake, StakeCurve::Sqrt), 1500);
+        assert_eq!(voting_power(100, 9_500, Amount::ZERO, StakeCurve::Linear), 0);
+        assert_eq!(voting_power(u64::MAX, 9_500, Amount::MAX, StakeCurve::Linear), u64::MAX);
+
+        let factors = PowerFactors::new(100, 8_000, stake, StakeCurve::Sqrt);
+        assert_eq!(factors, PowerFactors { reputation: 120, stake: 10 });
+        assert_eq!(factors.voting_power(), 1200);
+    }
+}
@@ -6,13 +6,13 @@
 mod state;
 
 use linera_sdk::{
-    linera_base_types::{WithContractAbi, AccountOwner, Amount, Timestamp},
+    linera_base_types::{WithContractAbi, Account, AccountOwner, Amount, ChainId, Timestamp},
     views::{RootView, View},
     Contract, ContractRuntime,
 };
 use alethea_market_chain::{
     MarketChainAbi, MarketOperation, MarketResponse, InitialState, Message,
-    Parameters, MarketDetails, PositionDetails, MarketStatus,
+    Parameters, MarketDetails, PositionDetails, MarketStatus, MarketKind, MarketError, ScalarValue, Ballot, LONG,
 };
 
 use self::state::{MarketState, Market, Position};
@@ -56,7 +56,11 @@ impl Contract for MarketChainContract {
                 outcomes,
                 resolution_deadline,
                 initial_liquidity,
-            } => self.create_market(question, outcomes, resolution_deadline, initial_liquidity).await,
+                kind,
+            } => {
+                self.create_market(question, outcomes, resolution_deadline, initial_liquidity, kind.unwrap_or_default())
+                    .await
+            }
             
             MarketOperation::BuyShares {
                 market_id,
@@ -86,13 +90,22 @@ impl Contract for MarketChainContract {
         }
     }
 
-    async fn execute_message(&mut self, message: Message) -> () {
+    async fn execute_message(&mut self, message: Message) {
         match message {
-            Message::ResolutionResult { market_id, outcome_index } => {
-                self.handle_resolution(market_id, outcome_index).await;
+            Message::MarketResolved { market_id, outcome, .. } => {
+                self.handle_resolution(market_id, outcome).await;
+            }
+            Message::MarketInvalid { market_id, .. } => {
+                self.handle_invalid(market_id).await;
+            }
+            Message::ScalarMarketResolved { market_id, value, .. } => {
+                self.handle_scalar_resolution(market_id, value).await;
+            }
+            Message::BallotResolved { market_id, ballot, .. } => {
+                self.handle_ballot_resolution(market_id, ballot).await;
             }
-            Message::ResolutionRequest { .. } => {
-                // This message is sent FROM Market to Oracle, not received
+            _ => {
+                // Requests and votes are sent FROM this chain or between other chains
                 // No action needed here
             }
         }
@@ -110,12 +123,25 @@ impl MarketChainContract {
         outcomes: Vec<String>,
         resolution_deadline: Timestamp,
         initial_liquidity: Amount,
+        kind: MarketKind,
     ) -> MarketResponse {
         let market_id = *self.state.next_market_id.get();
         let creator = self.runtime
             .authenticated_signer()
             .expect("Market creation requires authentication");
         
+        // Scalar markets trade a long and a short side of the range
+        assert!(kind.is_valid(), "Scalar market needs min below max");
+        // Same bounds the coordinator takes, or the market could never be resolved
+        if !kind.is_scalar() && !(2..=10).contains(&outcomes.len()) {
+            return MarketResponse::Error(MarketError::InvalidOutcomes);
+        }
+        let outcomes = if kind.is_scalar() {
+            vec!["Long".to_string(), "Short".to_string()]
+        } else {
+            outcomes
+        };
+        
         let num_outcomes = outcomes.len();
         let liquidity_u128: u128 = initial_liquidity.into();
         let liquidity_per_outcome = Amount::from_tokens(liquidity_u128 / num_outcomes as u128);
@@ -127,9 +153,13 @@ impl MarketChainContract {
             creator: Some(creator),
             total_liquidity: initial_liquidity,
             outcome_pools: vec![liquidity_per_outcome; num_outcomes],
+            outcome_shares: vec![0; num_outcomes],
             resolution_deadline,
             status: MarketStatus::Open,
             final_outcome: None,
+            kind,
+            final_value: None,
+            final_ballot: None,
         };
         
         self.state.markets.insert(&market_id, market)
@@ -151,20 +181,11 @@ impl MarketChainContract {
         assert!(matches!(market.status, MarketStatus::Open), "Market closed");
         assert!(outcome_index < market.outcomes.len(), "Invalid outcome");
         
-        // Simple linear pricing for demo
-        let shares = self.calculate_shares(&market, outcome_index, amount);
-        
-        market.outcome_pools[outcome_index].saturating_add_assign(amount);
-        market.total_liquidity.saturating_add_assign(amount);
-        
-        self.state.markets.insert(&market_id, market)
-            .expect("Failed to update market");
-        
-        // Update position
         let owner = self.runtime
             .authenticated_signer()
             .expect("Buy shares requires authentication");
         
+        // One position per owner, so it can only grow on the outcome it started on
         let position_key = (market_id, owner);
         let mut position = self.state.positions.get(&position_key).await
             .expect("Failed to read position")
@@ -174,9 +195,32 @@ impl MarketChainContract {
                 outcome_index,
                 shares: 0,
                 average_price: Amount::ZERO,
+                cost: Amount::ZERO,
             });
+        assert_eq!(position.outcome_index, outcome_index, "Position holds another outcome");
+        
+        // Simple linear pricing for demo
+        let shares = self.calculate_shares(&market, outcome_index, amount);
+        // Payouts split by share counts, so the market's total has to stay a u64
+        market.outcome_shares.iter().sum::<u64>()
+            .checked_add(shares)
+            .expect("Too many shares");
+        
+        // Collect the stake first: refunds and payouts come out of this account
+        self.collect(owner, amount);
+        
+        market.outcome_pools[outcome_index].saturating_add_assign(amount);
+        market.total_liquidity.saturating_add_assign(amount);
+        // Markets stored before shares were counted start from zero
+        market.outcome_shares.resize(market.outcomes.len(), 0);
+        market.outcome_shares[outcome_index] += shares;
+        
+        self.state.markets.insert(&market_id, market)
+            .expect("Failed to update market");
         
+        // Update position
         position.shares += shares;
+        position.cost.saturating_add_assign(amount);
         // Simple average price calculation
         position.average_price = amount;
         
@@ -195,29 +239,75 @@ impl MarketChainContract {
             "Market deadline not reached"
         );
         
-        // Clone data needed for message before moving market
-        let question = market.question.clone();
-        let outcomes = market.outcomes.clone();
+        // Build the request before moving market
+        let request = match market.kind {
+            MarketKind::Categorical => Message::ResolutionRequest {
+                market_id,
+                question: market.question.clone(),
+                outcomes: market.outcomes.clone(),
+            },
+            MarketKind::Scalar { min, max, decimals } => Message::ScalarResolutionRequest {
+                market_id,
+                question: market.question.clone(),
+                min: min.0,
+                max: max.0,
+                decimals,
+            },
+            MarketKind::MultiSelect | MarketKind::Ranked => Message::BallotResolutionRequest {
+                market_id,
+                question: market.question.clone(),
+                outcomes: market.outcomes.clone(),
+                kind: market.kind,
+            },
+        };
         
         market.status = MarketStatus::WaitingResolution;
         self.state.markets.insert(&market_id, market)
             .expect("Failed to update market");
         
         // Send message to Oracle Coordinator if configured
-        if let Some(oracle_chain) = self.state.oracle_chain.get() {
-            self.runtime.send_message(
-                *oracle_chain,
-                Message::ResolutionRequest {
-                    market_id,
-                    question,
-                    outcomes,
-                },
-            );
+        if let Some(oracle_chain) = *self.state.oracle_chain.get() {
+            self.runtime.send_message(oracle_chain, request);
+            self.pay_resolution_fee(oracle_chain, market_id);
         }
         
         MarketResponse::ResolutionRequested
     }
 
+    /// Transfer the resolution fee from the requester into the coordinator's escrow
+    ///
+    /// Sent after the `ResolutionRequest`, so the coordinator already knows the
+    /// market when the fee arrives.
+    fn pay_resolution_fee(&mut self, oracle_chain: ChainId, market_id: u64) {
+        let params = self.runtime.application_parameters();
+        if params.resolution_fee == Amount::ZERO {
+            return;
+        }
+        
+        let payer = self.runtime
+            .authenticated_signer()
+            .expect("Resolution fee requires authentication");
+        assert!(
+            self.runtime.owner_balance(payer) >= params.resolution_fee,
+            "Insufficient balance for resolution fee"
+        );
+        
+        // The payer's own account on the oracle chain; the coordinator pulls
+        // the fee from there into escrow once it knows the market
+        let payer_account = Account {
+            chain_id: oracle_chain,
+            owner: payer,
+        };
+        self.runtime.transfer(payer, payer_account, params.resolution_fee);
+        self.runtime
+            .prepare_message(Message::ResolutionFee {
+                market_id,
+                amount: params.resolution_fee,
+            })
+            .with_authentication()
+            .send_to(oracle_chain);
+    }
+
     async fn handle_resolution(&mut self, market_id: u64, outcome_index: usize) {
         let mut market = self.state.get_market(market_id).await
             .expect("Market not found");
@@ -229,11 +319,52 @@ impl MarketChainContract {
             .expect("Failed to update market");
     }
 
+    /// Oracle answered a scalar market; long and short split each share by the value
+    async fn handle_scalar_resolution(&mut self, market_id: u64, value: i128) {
+        let mut market = self.state.get_market(market_id).await
+            .expect("Market not found");
+        
+        market.status = MarketStatus::Resolved;
+        market.final_value = Some(ScalarValue(value));
+        
+        self.state.markets.insert(&market_id, market)
+            .expect("Failed to update market");
+    }
+
+    /// Oracle answered a multi-select or ranked market with a ballot
+    async fn handle_ballot_resolution(&mut self, market_id: u64, ballot: Ballot) {
+        let mut market = self.state.get_market(market_id).await
+            .expect("Market not found");
+        
+        market.status = MarketStatus::Resolved;
+        market.final_ballot = Some(ballot);
+        
+        self.state.markets.insert(&market_id, market)
+            .expect("Failed to update market");
+    }
+
+    /// Oracle found no consensus: the market has no outcome and positions are refunded
+    async fn handle_invalid(&mut self, market_id: u64) {
+        let mut market = self.state.get_market(market_id).await
+            .expect("Market not found");
+        
+        market.status = MarketStatus::Invalid;
+        market.final_outcome = None;
+        market.final_value = None;
+        market.final_ballot = None;
+        
+        self.state.markets.insert(&market_id, market)
+            .expect("Failed to update market");
+    }
+
     async fn claim_winnings(&mut self, market_id: u64) -> MarketResponse {
         let market = self.state.get_market(market_id).await
             .expect("Market not found");
         
-        assert!(matches!(market.status, MarketStatus::Resolved), "Not resolved");
+        assert!(
+            matches!(market.status, MarketStatus::Resolved | MarketStatus::Invalid),
+            "Not resolved"
+        );
         
         let owner = self.runtime
             .authenticated_signer()
@@ -242,16 +373,140 @@ impl MarketChainContract {
         let position = self.state.get_position(market_id, &owner).await
             .expect("No position found");
         
-        let final_outcome = market.final_outcome.expect("No outcome");
+        if market.status == MarketStatus::Invalid {
+            // Refund once, then the position is gone
+            self.pay_out(owner, position.cost);
+            self.state.positions.remove(&(market_id, owner))
+                .expect("Failed to remove position");
+            return MarketResponse::PositionRefunded { amount: position.cost };
+        }
         
-        if position.outcome_index == final_outcome {
-            // Winner! Calculate payout (1:1 for demo)
-            let winnings = Amount::from_tokens(position.shares as u128);
-            // TODO: Actual token transfer logic
-            MarketResponse::WinningsClaimed { amount: winnings }
+        let winnings = if let Some(value) = market.final_value {
+            Self::scalar_payout(
+                &market.kind,
+                value,
+                &market.outcome_shares,
+                position.outcome_index,
+                position.shares,
+            )
+        } else if let Some(ballot) = &market.final_ballot {
+            // Every outcome the ballot picks wins
+            Self::winner_payout(
+                &market.outcome_shares,
+                |index| ballot.picks(index),
+                position.outcome_index,
+                position.shares,
+            )
         } else {
-            MarketResponse::WinningsClaimed { amount: Amount::ZERO }
+            let final_outcome = market.final_outcome.expect("No outcome");
+            Self::winner_payout(
+                &market.outcome_shares,
+                |index| index == final_outcome,
+                position.outcome_index,
+                position.shares,
+            )
+        };
+        
+        // Winner or not, every position is settled once and removed
+        self.pay_out(owner, winnings);
+        self.state.positions.remove(&(market_id, owner))
+            .expect("Failed to remove position");
+        MarketResponse::WinningsClaimed { amount: winnings }
+    }
+
+    /// Transfer `amount` from `owner` into this application's account
+    fn collect(&mut self, owner: AccountOwner, amount: Amount) {
+        assert!(
+            self.runtime.owner_balance(owner) >= amount,
+            "Insufficient balance for shares"
+        );
+        let market_account = Account {
+            chain_id: self.runtime.chain_id(),
+            owner: AccountOwner::from(self.runtime.application_id().forget_abi()),
+        };
+        self.runtime.transfer(owner, market_account, amount);
+    }
+
+    /// Pay `amount` from this application's account to `owner` on this chain
+    fn pay_out(&mut self, owner: AccountOwner, amount: Amount) {
+        if amount == Amount::ZERO {
+            return;
+        }
+        let market_account = AccountOwner::from(self.runtime.application_id().forget_abi());
+        assert!(
+            self.runtime.owner_balance(market_account) >= amount,
+            "Insufficient market funds for payout"
+        );
+        let destination = Account {
+            chain_id: self.runtime.chain_id(),
+            owner,
+        };
+        self.runtime.transfer(market_account, destination, amount);
+    }
+
+    /// Payout of `shares` on one side of a scalar market resolved at `value`
+    ///
+    /// Everything paid in is split by where `value` falls in the range: long
+    /// holders share (value - min) / (max - min) of it and short holders the
+    /// rest, pro rata by shares. With nobody on one side there was no bet,
+    /// so each share just returns the token it cost.
+    fn scalar_payout(
+        kind: &MarketKind,
+        value: ScalarValue,
+        outcome_shares: &[u64],
+        outcome_index: usize,
+        shares: u64,
+    ) -> Amount {
+        let MarketKind::Scalar { min, max, .. } = *kind else {
+            return Amount::ZERO;
+        };
+        if outcome_shares.len() != 2 || outcome_shares.contains(&0) {
+            return Amount::from_tokens(u128::from(shares));
+        }
+        let side_shares = outcome_shares[outcome_index];
+        let pot: u128 = Amount::from_tokens(outcome_shares.iter().map(|shares| u128::from(*shares)).sum()).into();
+        let mut numerator = value.0.clamp(min.0, max.0).abs_diff(min.0);
+        let mut denominator = max.0.abs_diff(min.0);
+        // Drop low bits of the fraction until the product fits
+        while numerator.checked_mul(pot).is_none() {
+            numerator >>= 1;
+            denominator >>= 1;
+        }
+        let long_pot = pot * numerator / denominator;
+        let side_pot = if outcome_index == LONG { long_pot } else { pot - long_pot };
+        Amount::from_attos(Self::share_of(side_pot, shares, side_shares))
+    }
+
+    /// Payout of `shares` on `outcome_index` once the winning outcomes are known
+    ///
+    /// Everything paid in goes to the holders of winning outcomes, pro rata by
+    /// shares. With no shares on any winner nobody won the bet, so each share
+    /// just returns the token it cost.
+    fn winner_payout(
+        outcome_shares: &[u64],
+        wins: impl Fn(usize) -> bool,
+        outcome_index: usize,
+        shares: u64,
+    ) -> Amount {
+        let winning_shares: u64 = outcome_shares.iter()
+            .enumerate()
+            .filter(|(index, _)| wins(*index))
+            .map(|(_, shares)| *shares)
+            .sum();
+        if winning_shares == 0 {
+            return Amount::from_tokens(u128::from(shares));
+        }
+        if !wins(outcome_index) {
+            return Amount::ZERO;
         }
+        let pot: u128 = Amount::from_tokens(outcome_shares.iter().map(|shares| u128::from(*shares)).sum()).into();
+        Amount::from_attos(Self::share_of(pot, shares, winning_shares))
+    }
+
+    /// `total * part / whole` rounded down, without overflowing for `part <= whole`
+    fn share_of(total: u128, part: u64, whole: u64) -> u128 {
+        let (part, whole) = (u128::from(part), u128::from(whole));
+        total / whole * part + total % whole * part / whole
     }
 
     async fn get_market(&mut self, market_id: u64) -> MarketResponse {
@@ -268,6 +523,9 @@ impl MarketChainContract {
             resolution_deadline: market.resolution_deadline,
             status: market.status,
             final_outcome: market.final_outcome,
+            kind: market.kind,
+            final_value: market.final_value,
+            final_ballot: market.final_ballot,
         })
     }
 
@@ -279,6 +537,7 @@ impl MarketChainContract {
                 outcome_index: 0,
                 shares: 0,
                 average_price: Amount::ZERO,
+                cost: Amount::ZERO,
             });
         
         MarketResponse::Position(PositionDetails {
@@ -290,18 +549,12 @@ impl MarketChainContract {
         })
     }
 
-    fn calculate_shares(&self, market: &Market, _outcome_index: usize, amount: Amount) -> u64 {
-        let total_pool = market.total_liquidity;
-        
-        if total_pool == Amount::ZERO {
-            // Convert Amount to u64 for shares
-            let amount_u128: u128 = amount.into();
-            return amount_u128 as u64;
-        }
-        
-        // Simple linear pricing: shares proportional to amount
-        let shares_u128: u128 = amount.into();
-        shares_u128 as u64
+    fn calculate_shares(&self, _market: &Market, _outcome_index: usize, amount: Amount) -> u64 {
+        // Simple linear pricing: one share per whole token, the same unit payouts use
+        let attos: u128 = amount.into();
+        let one: u128 = Amount::ONE.into();
+        assert!(attos > 0 && attos % one == 0, "Shares are bought in whole tokens");
+        u64::try_from(attos / one).expect("Too many shares")
     }
     
     async fn set_oracle_chain(&mut self, oracle_chain_id: Option<linera_sdk::linera_base_types::ChainId>) -> MarketResponse {
@@ -313,8 +566,7 @@ impl MarketChainContract {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use linera_sdk::{util::BlockingWait, views::View, Contract, ContractRuntime};
-    use market_chain::{MarketOperation, InitialState};
+    use linera_sdk::{linera_base_types::{ApplicationId, CryptoHash}, util::BlockingWait};
     use futures::FutureExt;
 
     #[test]
@@ -326,6 +578,7 @@ mod tests {
             outcomes: vec!["Yes".to_string(), "No".to_string()],
             resolution_deadline: Timestamp::from(1000000),
             initial_liquidity: Amount::from_tokens(1000),
+            kind: None,
         };
         
         let response = contract
@@ -341,8 +594,447 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_request_resolution_sends_shared_request() {
+        let mut contract = create_test_contract();
+        let oracle_chain = ChainId(CryptoHash::from([8u8; 32]));
+        contract
+            .execute_operation(MarketOperation::SetOracleChain { oracle_chain_id: Some(oracle_chain) })
+            .blocking_wait();
+        let market_id = create_market(&mut contract);
+
+        contract.runtime.set_system_time(Timestamp::from(2000));
+        contract
+            .execute_operation(MarketOperation::RequestResolution { market_id })
+            .blocking_wait();
+
+        let sent = contract.runtime.created_send_message_requests();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].destination, oracle_chain);
+        let bytes = bcs::to_bytes(&sent[0].message).expect("Message should serialize");
+        let decoded: alethea_oracle_types::Message =
+            bcs::from_bytes(&bytes).expect("Coordinator should decode market requests");
+        assert_eq!(
+            decoded,
+            alethea_oracle_types::Message::ResolutionRequest {
+                market_id,
+                question: "Will BTC hit 100k?".to_string(),
+                outcomes: vec!["Yes".to_string(), "No".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_messages_decode_across_crates() {
+        // Voter chain -> coordinator
+        let commitment = alethea_voter_chain::Message::VoteCommitment {
+            voter_chain: ChainId(CryptoHash::from([1u8; 32])),
+            market_id: 3,
+            commitment_hash: [4u8; 32],
+            voting_power: 100,
+            stake_amount: Amount::from_tokens(1),
+        };
+        let bytes = bcs::to_bytes(&commitment).expect("Message should serialize");
+        let decoded: alethea_oracle_coordinator::Message =
+            bcs::from_bytes(&bytes).expect("Coordinator should decode voter messages");
+        assert_eq!(decoded, commitment);
+
+        // Coordinator -> market chain, then handled here
+        let mut contract = create_test_contract();
+        let market_id = create_market(&mut contract);
+        let resolved = alethea_oracle_coordinator::Message::MarketResolved {
+            market_id,
+            outcome: 1,
+            confidence: 90,
+            timestamp: Timestamp::from(2000),
+        };
+        let bytes = bcs::to_bytes(&resolved).expect("Message should serialize");
+        let decoded: Message = bcs::from_bytes(&bytes).expect("Market chain should decode coordinator messages");
+        contract.execute_message(decoded).blocking_wait();
+        let market = contract.state.get_market(market_id).blocking_wait().unwrap();
+        assert_eq!((market.status, market.final_outcome), (MarketStatus::Resolved, Some(1)));
+    }
+
+    #[test]
+    fn test_market_resolved_message_resolves_market() {
+        let mut contract = create_test_contract();
+        let market_id = create_market(&mut contract);
+
+        contract
+            .execute_message(Message::MarketResolved {
+                market_id,
+                outcome: 1,
+                confidence: 90,
+                timestamp: Timestamp::from(2000),
+            })
+            .blocking_wait();
+
+        let market = contract.state.get_market(market_id).blocking_wait().unwrap();
+        assert_eq!(market.status, MarketStatus::Resolved);
+        assert_eq!(market.final_outcome, Some(1));
+    }
+
+    #[test]
+    fn test_market_invalid_message_refunds_positions() {
+        let mut contract = create_test_contract();
+        let market_id = create_market(&mut contract);
+        let owner = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        contract.runtime.set_owner_balance(owner, Amount::from_tokens(80));
+        for amount in [30, 20] {
+            contract
+                .execute_operation(MarketOperation::BuyShares {
+                    market_id,
+                    outcome_index: 0,
+                    amount: Amount::from_tokens(amount),
+                })
+                .blocking_wait();
+        }
+        assert_eq!(contract.runtime.owner_balance(owner), Amount::from_tokens(30));
+        assert_eq!(contract.runtime.owner_balance(market_account()), Amount::from_tokens(10_050));
+
+        // Buying more than the balance fails and moves nothing
+        let too_much = std::panic::AssertUnwindSafe(contract.execute_operation(MarketOperation::BuyShares {
+            market_id,
+            outcome_index: 0,
+            amount: Amount::from_tokens(31),
+        }))
+        .catch_unwind()
+        .blocking_wait();
+        assert!(too_much.is_err());
+
+        contract
+            .execute_message(Message::MarketInvalid {
+                market_id,
+                confidence: 50,
+                timestamp: Timestamp::from(2000),
+            })
+            .blocking_wait();
+        let market = contract.state.get_market(market_id).blocking_wait().unwrap();
+        assert_eq!((market.status, market.final_outcome), (MarketStatus::Invalid, None));
+
+        let response = contract
+            .execute_operation(MarketOperation::ClaimWinnings { market_id })
+            .blocking_wait();
+        assert!(matches!(
+            response,
+            MarketResponse::PositionRefunded { amount } if amount == Amount::from_tokens(50)
+        ));
+        assert!(contract.state.get_position(market_id, &owner).blocking_wait().is_none());
+        // Only what was collected comes back
+        assert_eq!(contract.runtime.owner_balance(owner), Amount::from_tokens(80));
+        assert_eq!(contract.runtime.owner_balance(market_account()), Amount::from_tokens(10_000));
+    }
+
+    #[test]
+    fn test_scalar_market_pays_long_and_short() {
+        let mut contract = create_test_contract();
+        let oracle_chain = ChainId(CryptoHash::from([8u8; 32]));
+        contract
+            .execute_operation(MarketOperation::SetOracleChain { oracle_chain_id: Some(oracle_chain) })
+            .blocking_wait();
+        let kind = MarketKind::Scalar { min: ScalarValue(-5_000), max: ScalarValue(15_000), decimals: 2 };
+        let response = contract
+            .execute_operation(MarketOperation::CreateMarket {
+                question: "Temperature at noon?".to_string(),
+                outcomes: vec![],
+                resolution_deadline: Timestamp::from(1000),
+                initial_liquidity: Amount::from_tokens(1000),
+                kind: Some(kind),
+            })
+            .blocking_wait();
+        let MarketResponse::MarketCreated(market_id) = response else {
+            panic!("Expected MarketCreated");
+        };
+        let market = contract.state.get_market(market_id).blocking_wait().unwrap();
+        assert_eq!(market.outcomes, vec!["Long".to_string(), "Short".to_string()]);
+
+        let long = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        let short = AccountOwner::from(CryptoHash::from([2u8; 32]));
+        for (owner, outcome_index, amount) in [(long, LONG, 300), (short, alethea_market_chain::SHORT, 500)] {
+            contract.runtime.set_owner_balance(owner, Amount::from_tokens(1_000));
+            contract.runtime.set_authenticated_signer(owner);
+            let response = contract
+                .execute_operation(MarketOperation::BuyShares { market_id, outcome_index, amount: Amount::from_tokens(amount) })
+                .blocking_wait();
+            assert!(matches!(response, MarketResponse::SharesPurchased { shares } if shares == amount as u64));
+        }
+        contract.runtime.set_authenticated_signer(long);
+        contract
+            .execute_operation(MarketOperation::BuyShares { market_id, outcome_index: LONG, amount: Amount::from_tokens(100) })
+            .blocking_wait();
+        assert_eq!(contract.runtime.owner_balance(market_account()), Amount::from_tokens(10_900));
+
+        // A long holder cannot buy short, and shares come in whole tokens
+        for (outcome_index, amount) in [(alethea_market_chain::SHORT, Amount::from_tokens(1)), (LONG, Amount::from_attos(400))] {
+            let rejected = std::panic::AssertUnwindSafe(
+                contract.execute_operation(MarketOperation::BuyShares { market_id, outcome_index, amount }),
+            )
+            .catch_unwind()
+            .blocking_wait();
+            assert!(rejected.is_err());
+        }
+
+        contract.runtime.set_system_time(Timestamp::from(2000));
+        contract.execute_operation(MarketOperation::RequestResolution { market_id }).blocking_wait();
+        assert_eq!(
+            contract.runtime.created_send_message_requests()[0].message,
+            Message::ScalarResolutionRequest {
+                market_id,
+                question: "Temperature at noon?".to_string(),
+                min: -5_000,
+                max: 15_000,
+                decimals: 2,
+            }
+        );
+
+        // A quarter of the way up the range
+        contract
+            .execute_message(Message::ScalarMarketResolved {
+                market_id,
+                value: 0,
+                confidence: 90,
+                timestamp: Timestamp::from(2000),
+            })
+            .blocking_wait();
+        let claim = |contract: &mut MarketChainContract, owner| {
+            contract.runtime.set_authenticated_signer(owner);
+            match contract.execute_operation(MarketOperation::ClaimWinnings { market_id }).blocking_wait() {
+                MarketResponse::WinningsClaimed { amount } => amount,
+                other => panic!("Expected WinningsClaimed, got {:?}", other),
+            }
+        };
+        // Long holds 400 of the 900 paid in and gets a quarter of it; short the rest
+        assert_eq!(claim(&mut contract, long), Amount::from_tokens(225));
+        assert_eq!(claim(&mut contract, short), Amount::from_tokens(675));
+        assert_eq!(contract.runtime.owner_balance(long), Amount::from_tokens(825));
+        assert_eq!(contract.runtime.owner_balance(short), Amount::from_tokens(1_175));
+        assert_eq!(contract.runtime.owner_balance(market_account()), Amount::from_tokens(10_000));
+
+        // Paid positions are gone: claiming again fails
+        contract.runtime.set_authenticated_signer(long);
+        let again = std::panic::AssertUnwindSafe(
+            contract.execute_operation(MarketOperation::ClaimWinnings { market_id }),
+        )
+        .catch_unwind()
+        .blocking_wait();
+        assert!(again.is_err());
+        assert_eq!(contract.runtime.owner_balance(long), Amount::from_tokens(825));
+
+        // The whole i128 range and the largest pot still split exactly
+        let widest = MarketKind::Scalar { min: ScalarValue(i128::MIN), max: ScalarValue(i128::MAX), decimals: 0 };
+        let payout = |value, outcome_index| {
+            MarketChainContract::scalar_payout(&widest, ScalarValue(value), &[u64::MAX, u64::MAX], outcome_index, u64::MAX)
+        };
+        let pot = Amount::from_tokens(2 * u128::from(u64::MAX));
+        assert_eq!(payout(i128::MAX, LONG), pot);
+        assert_eq!(payout(i128::MIN, LONG), Amount::ZERO);
+        assert_eq!(payout(0, LONG).saturating_add(payout(0, alethea_market_chain::SHORT)), pot);
+
+        // Nobody took the other side: shares are returned at cost
+        let one_sided = MarketChainContract::scalar_payout(&widest, ScalarValue(0), &[7, 0], LONG, 7);
+        assert_eq!(one_sided, Amount::from_tokens(7));
+    }
+
+    #[test]
+    fn test_multi_select_market_pays_picked_outcomes() {
+        let mut contract = create_test_contract();
+        let oracle_chain = ChainId(CryptoHash::from([8u8; 32]));
+        contract
+            .execute_operation(MarketOperation::SetOracleChain { oracle_chain_id: Some(oracle_chain) })
+            .blocking_wait();
+        let create = |contract: &mut MarketChainContract, outcomes: &[&str]| {
+            contract
+                .execute_operation(MarketOperation::CreateMarket {
+                    question: "Which teams make the playoffs?".to_string(),
+                    outcomes: outcomes.iter().map(|outcome| outcome.to_string()).collect(),
+                    resolution_deadline: Timestamp::from(1000),
+                    initial_liquidity: Amount::from_tokens(1000),
+                    kind: Some(MarketKind::MultiSelect),
+                })
+                .blocking_wait()
+        };
+        assert!(matches!(
+            create(&mut contract, &["Lions"]),
+            MarketResponse::Error(MarketError::InvalidOutcomes)
+        ));
+        let MarketResponse::MarketCreated(market_id) = create(&mut contract, &["Lions", "Bears", "Wolves"]) else {
+            panic!("Expected MarketCreated");
+        };
+
+        let picked = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        let missed = AccountOwner::from(CryptoHash::from([2u8; 32]));
+        for (owner, outcome_index) in [(picked, 1), (missed, 2)] {
+            contract.runtime.set_owner_balance(owner, Amount::from_tokens(400));
+            contract.runtime.set_authenticated_signer(owner);
+            contract
+                .execute_operation(MarketOperation::BuyShares { market_id, outcome_index, amount: Amount::from_tokens(400) })
+                .blocking_wait();
+        }
+        assert_eq!(contract.runtime.owner_balance(market_account()), Amount::from_tokens(10_800));
+
+        contract.runtime.set_system_time(Timestamp::from(2000));
+        contract.execute_operation(MarketOperation::RequestResolution { market_id }).blocking_wait();
+        assert!(matches!(
+            contract.runtime.created_send_message_requests()[0].message,
+            Message::BallotResolutionRequest { kind: MarketKind::MultiSelect, .. }
+        ));
+
+        contract
+            .execute_message(Message::BallotResolved {
+                market_id,
+                ballot: Ballot::Approval(0b011),
+                confidence: 70,
+                timestamp: Timestamp::from(2000),
+            })
+            .blocking_wait();
+        let claim = |contract: &mut MarketChainContract, owner| {
+            contract.runtime.set_authenticated_signer(owner);
+            match contract.execute_operation(MarketOperation::ClaimWinnings { market_id }).blocking_wait() {
+                MarketResponse::WinningsClaimed { amount } => amount,
+                other => panic!("Expected WinningsClaimed, got {:?}", other),
+            }
+        };
+        // Only Bears holders picked a winner, so they take everything paid in
+        assert_eq!(claim(&mut contract, picked), Amount::from_tokens(800));
+        assert_eq!(claim(&mut contract, missed), Amount::ZERO);
+        assert_eq!(contract.runtime.owner_balance(picked), Amount::from_tokens(800));
+        assert_eq!(contract.runtime.owner_balance(missed), Amount::ZERO);
+        assert_eq!(contract.runtime.owner_balance(market_account()), Amount::from_tokens(10_000));
+        for owner in [picked, missed] {
+            assert!(contract.state.get_position(market_id, &owner).blocking_wait().is_none());
+        }
+    }
+
+    #[test]
+    fn test_categorical_market_splits_pot_among_winners() {
+        let mut contract = create_test_contract();
+        let market_id = create_market(&mut contract);
+        let buyers = [
+            (AccountOwner::from(CryptoHash::from([1u8; 32])), 0, 30),
+            (AccountOwner::from(CryptoHash::from([2u8; 32])), 0, 10),
+            (AccountOwner::from(CryptoHash::from([3u8; 32])), 1, 60),
+        ];
+        for (owner, outcome_index, amount) in buyers {
+            contract.runtime.set_owner_balance(owner, Amount::from_tokens(100));
+            contract.runtime.set_authenticated_signer(owner);
+            contract
+                .execute_operation(MarketOperation::BuyShares { market_id, outcome_index, amount: Amount::from_tokens(amount) })
+                .blocking_wait();
+        }
+
+        contract
+            .execute_message(Message::MarketResolved {
+                market_id,
+                outcome: 0,
+                confidence: 90,
+                timestamp: Timestamp::from(2000),
+            })
+            .blocking_wait();
+        for ((owner, _, _), expected) in buyers.into_iter().zip([75, 25, 0]) {
+            contract.runtime.set_authenticated_signer(owner);
+            let response = contract
+                .execute_operation(MarketOperation::ClaimWinnings { market_id })
+                .blocking_wait();
+            assert!(matches!(
+                response,
+                MarketResponse::WinningsClaimed { amount } if amount == Amount::from_tokens(expected)
+            ));
+            assert!(contract.state.get_position(market_id, &owner).blocking_wait().is_none());
+        }
+        assert_eq!(contract.runtime.owner_balance(buyers[0].0), Amount::from_tokens(145));
+        assert_eq!(contract.runtime.owner_balance(buyers[2].0), Amount::from_tokens(40));
+        assert_eq!(contract.runtime.owner_balance(market_account()), Amount::from_tokens(10_000));
+
+        // Paid once: the loser cannot claim again either
+        let again = std::panic::AssertUnwindSafe(
+            contract.execute_operation(MarketOperation::ClaimWinnings { market_id }),
+        )
+        .catch_unwind()
+        .blocking_wait();
+        assert!(again.is_err());
+
+        // Nobody backed the winner: every share returns what it cost
+        let unbacked = MarketChainContract::winner_payout(&[0, 5], |index| index == 0, 1, 5);
+        assert_eq!(unbacked, Amount::from_tokens(5));
+    }
+
+    #[test]
+    fn test_request_resolution_pays_fee_to_coordinator() {
+        let oracle_chain = ChainId(CryptoHash::from([8u8; 32]));
+        let requester = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        let mut contract = create_test_contract();
+        contract.runtime.set_application_parameters(Parameters {
+            oracle_chain_id: None,
+            resolution_fee: Amount::from_tokens(5),
+        });
+        contract.runtime.set_owner_balance(requester, Amount::from_tokens(20));
+        contract
+            .execute_operation(MarketOperation::SetOracleChain { oracle_chain_id: Some(oracle_chain) })
+            .blocking_wait();
+        let market_id = create_market(&mut contract);
+
+        contract.runtime.set_system_time(Timestamp::from(2000));
+        contract
+            .execute_operation(MarketOperation::RequestResolution { market_id })
+            .blocking_wait();
+
+        assert_eq!(contract.runtime.owner_balance(requester), Amount::from_tokens(15));
+        let payer_account = Account { chain_id: oracle_chain, owner: requester };
+        assert_eq!(contract.runtime.outgoing_transfers().get(&payer_account), Some(&Amount::from_tokens(5)));
+
+        let sent = contract.runtime.created_send_message_requests();
+        assert_eq!(sent.len(), 2);
+        assert!(matches!(sent[0].message, Message::ResolutionRequest { .. }));
+        assert!(sent[1].authenticated);
+        assert_eq!(
+            sent[1].message,
+            Message::ResolutionFee { market_id, amount: Amount::from_tokens(5) }
+        );
+    }
+
+    fn create_market(contract: &mut MarketChainContract) -> u64 {
+        let response = contract
+            .execute_operation(MarketOperation::CreateMarket {
+                question: "Will BTC hit 100k?".to_string(),
+                outcomes: vec!["Yes".to_string(), "No".to_string()],
+                resolution_deadline: Timestamp::from(1000),
+                initial_liquidity: Amount::from_tokens(1000),
+                kind: None,
+            })
+            .blocking_wait();
+        match response {
+            MarketResponse::MarketCreated(id) => id,
+            other => panic!("Expected MarketCreated, got {:?}", other),
+        }
+    }
+
+    fn test_chain() -> ChainId {
+        ChainId(CryptoHash::from([0u8; 32]))
+    }
+
+    fn application_id() -> ApplicationId {
+        ApplicationId::new(CryptoHash::from([9u8; 32]))
+    }
+
+    /// Account of the market application, funded with 10_000 tokens for payouts
+    fn market_account() -> AccountOwner {
+        AccountOwner::from(application_id())
+    }
+
     fn create_test_contract() -> MarketChainContract {
-        let runtime = ContractRuntime::new();
+        let runtime = ContractRuntime::new()
+            .with_chain_id(test_chain())
+            .with_application_id(application_id().with_abi())
+            .with_owner_balances([
+                (market_account(), Amount::from_tokens(10_000)),
+                (AccountOwner::from(CryptoHash::from([1u8; 32])), Amount::ZERO),
+            ])
+            .with_authenticated_signer(AccountOwner::from(CryptoHash::from([1u8; 32])))
+            .with_system_time(Timestamp::from(0))
+            .with_application_parameters(Parameters {
+                oracle_chain_id: None,
+                resolution_fee: Amount::ZERO,
+            });
         let state = MarketState::load(runtime.root_view_storage_context())
             .blocking_wait()
             .expect("Failed to load state");
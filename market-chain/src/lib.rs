@@ -14,12 +14,24 @@ use linera_sdk::{
 };
 use serde::{Deserialize, Serialize};
 
+pub use alethea_oracle_types::{Ballot, MarketError, MarketKind, ScalarValue};
+
 pub struct MarketChainAbi;
 
+/// Outcome of a scalar market's long side; pays more the higher the result
+pub const LONG: usize = 0;
+
+/// Outcome of a scalar market's short side; pays what long does not
+pub const SHORT: usize = 1;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Parameters {
     /// Oracle chain ID for resolving markets
     pub oracle_chain_id: Option<linera_sdk::linera_base_types::ChainId>,
+    
+    /// Fee the resolution requester pays into the coordinator's reward pool
+    #[serde(default)]
+    pub resolution_fee: Amount,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -38,11 +50,16 @@ pub struct MarketConfig {
 #[derive(Debug, Serialize, Deserialize, GraphQLMutationRoot)]
 pub enum MarketOperation {
     /// Create a new prediction market
+    ///
+    /// A scalar `kind` makes a long/short market; its outcomes are
+    /// replaced by "Long" and "Short". Multi-select and ranked kinds
+    /// cannot be traded and are rejected.
     CreateMarket {
         question: String,
         outcomes: Vec<String>,
         resolution_deadline: Timestamp,
         initial_liquidity: Amount,
+        kind: Option<MarketKind>,
     },
     
     /// Buy shares for a specific outcome
@@ -52,7 +69,7 @@ pub enum MarketOperation {
         amount: Amount,
     },
     
-    /// Request oracle resolution (after deadline)
+    /// Request oracle resolution (after deadline), paying the resolution fee
     RequestResolution {
         market_id: u64,
     },
@@ -93,6 +110,9 @@ pub enum MarketResponse {
     /// Winnings claimed
     WinningsClaimed { amount: Amount },
     
+    /// Position closed on an invalid market, what was paid for it is returned
+    PositionRefunded { amount: Amount },
+    
     /// Market details
     Market(MarketDetails),
     
@@ -101,6 +121,9 @@ pub enum MarketResponse {
     
     /// Generic OK response
     Ok,
+    
+    /// Operation rejected
+    Error(MarketError),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -114,6 +137,11 @@ pub struct MarketDetails {
     pub resolution_deadline: Timestamp,
     pub status: MarketStatus,
     pub final_outcome: Option<usize>,
+    pub kind: MarketKind,
+    /// Result of a resolved scalar market
+    pub final_value: Option<ScalarValue>,
+    /// Result of a resolved multi-select or ranked market
+    pub final_ballot: Option<Ballot>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, async_graphql::Enum, Copy, PartialEq, Eq)]
@@ -122,6 +150,8 @@ pub enum MarketStatus {
     Closed,
     WaitingResolution,
     Resolved,
+    /// Oracle could not reach consensus; positions are refunded
+    Invalid,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -133,20 +163,8 @@ pub struct PositionDetails {
     pub average_price: Amount,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub enum Message {
-    /// Resolution result from oracle
-    ResolutionResult {
-        market_id: u64,
-        outcome_index: usize,
-    },
-    /// Request resolution from oracle
-    ResolutionRequest {
-        market_id: u64,
-        question: String,
-        outcomes: Vec<String>,
-    },
-}
+/// Cross-chain messages are shared with the coordinator and voter chains
+pub use alethea_oracle_types::Message;
 
 impl ContractAbi for MarketChainAbi {
     type Operation = MarketOperation;
@@ -6,7 +6,7 @@ use linera_sdk::{
     views::{linera_views, MapView, RegisterView, RootView, ViewStorageContext},
 };
 use serde::{Deserialize, Serialize};
-use alethea_market_chain::{MarketStatus, MarketConfig};
+use alethea_market_chain::{Ballot, MarketStatus, MarketConfig, MarketKind, ScalarValue};
 
 /// The application state for Market Chain
 #[derive(RootView)]
@@ -33,9 +33,20 @@ pub struct Market {
     pub creator: Option<AccountOwner>,
     pub total_liquidity: Amount,
     pub outcome_pools: Vec<Amount>,
+    /// Shares sold on each outcome; payouts split what was paid in by these
+    #[serde(default)]
+    pub outcome_shares: Vec<u64>,
     pub resolution_deadline: Timestamp,
     pub status: MarketStatus,
     pub final_outcome: Option<usize>,
+    #[serde(default)]
+    pub kind: MarketKind,
+    /// Result of a resolved scalar market
+    #[serde(default)]
+    pub final_value: Option<ScalarValue>,
+    /// Result of a resolved multi-select or ranked market
+    #[serde(default)]
+    pub final_ballot: Option<Ballot>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
@@ -45,6 +56,9 @@ pub struct Position {
     pub outcome_index: usize,
     pub shares: u64,
     pub average_price: Amount,
+    /// Total paid for the shares, refunded if the market ends invalid
+    #[serde(default)]
+    pub cost: Amount,
 }
 
 impl MarketState {
@@ -52,20 +66,24 @@ impl MarketState {
     #[allow(dead_code)]
     pub async fn initialize_markets(&mut self, markets: Vec<MarketConfig>) {
         for config in markets {
-            let market_id = self.next_market_id.get();
+            let market_id = *self.next_market_id.get();
             
             // Create market with default values
             // This would need actual creator owner from runtime
             let market = Market {
-                id: *market_id,
+                id: market_id,
                 question: config.question,
                 outcomes: config.outcomes.clone(),
                 creator: None,
                 total_liquidity: Amount::ZERO,
                 outcome_pools: vec![Amount::ZERO; config.outcomes.len()],
+                outcome_shares: vec![0; config.outcomes.len()],
                 resolution_deadline: config.resolution_deadline,
                 status: MarketStatus::Open,
                 final_outcome: None,
+                kind: MarketKind::Categorical,
+                final_value: None,
+                final_ballot: None,
             };
             
             self.markets.insert(&market_id, market).expect("Failed to insert market");
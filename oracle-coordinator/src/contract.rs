@@ -4,19 +4,18 @@
 #![cfg_attr(target_arch = "wasm32", no_main)]
 
 mod state;
-mod types;
 
 use linera_sdk::{
-    linera_base_types::{AccountOwner, Amount, ChainId, Timestamp, WithContractAbi},
-    views::RootView,
+    linera_base_types::{Account, AccountOwner, Amount, ChainId, Timestamp, WithContractAbi},
+    views::{RootView, View},
     Contract, ContractRuntime,
 };
 use alethea_oracle_types::{
-    OracleCoordinatorAbi, CoordinatorOperation, CoordinatorResponse,
-    Message, Parameters,
+    aggregation::{Aggregate, BallotAggregate, ScalarAggregate}, rewards, AggregationMethod, Ballot, OracleCoordinatorAbi, CoordinatorError, CoordinatorOperation, CoordinatorResponse,
+    Dispute, Message, Parameters, Market, MarketKind, MarketStatus, PowerFactors, ResolutionRequester, ScalarValue, VoteCommitment,
+    VoteReveal, VoterInfo, INITIAL_REPUTATION,
 };
 
-use crate::types::{Market, MarketStatus, VoterInfo, VoteCommitment, VoteReveal};
 use self::state::OracleCoordinatorState;
 
 pub struct OracleCoordinatorContract {
@@ -54,17 +53,23 @@ impl Contract for OracleCoordinatorContract {
                 question, 
                 outcomes, 
                 trading_deadline,
-                voting_deadline,
+                commit_deadline,
+                reveal_deadline,
                 min_voters,
                 category,
+                aggregation_method,
+                kind,
             } => {
                 self.create_market(
                     question, 
                     outcomes, 
                     trading_deadline, 
-                    voting_deadline,
+                    commit_deadline,
+                    reveal_deadline,
                     min_voters,
                     category,
+                    aggregation_method,
+                    kind,
                 ).await
             }
             
@@ -75,6 +80,10 @@ impl Contract for OracleCoordinatorContract {
                 self.register_voter(voter_chain, initial_stake).await
             }
             
+            CoordinatorOperation::DepositStake { voter_chain, amount } => {
+                self.deposit_stake(voter_chain, amount).await
+            }
+            
             CoordinatorOperation::StartVoting { market_id } => {
                 self.start_voting(market_id).await
             }
@@ -98,24 +107,42 @@ impl Contract for OracleCoordinatorContract {
             CoordinatorOperation::GetMarketStats { market_id } => {
                 self.get_market_stats(market_id).await
             }
+            
+            CoordinatorOperation::GetRewardPool { market_id } => {
+                let balance = self.state.get_reward_pool(market_id).await;
+                CoordinatorResponse::RewardPool { market_id, balance }
+            }
+            
+            CoordinatorOperation::DisputeResolution { market_id, bond } => {
+                self.dispute_resolution(market_id, bond).await
+            }
+            
+            CoordinatorOperation::FinalizeResolution { market_id } => {
+                self.finalize_resolution(market_id).await
+            }
         }
     }
 
-    async fn execute_message(&mut self, message: Message) -> () {
+    async fn execute_message(&mut self, message: Message) {
+        // Votes and stake changes are only accepted from the voter chain itself
+        if let Some(voter_chain) = Self::claimed_voter(&message) {
+            if !self.authenticate_voter(voter_chain) {
+                return;
+            }
+        }
+
         match message {
             Message::VoteCommitment { 
                 voter_chain, 
                 market_id,
                 commitment_hash,
-                voting_power,
-                stake_amount,
+                voting_power: _, // Recomputed from our own VoterInfo
+                stake_amount: _, // Each round decides what it locks
             } => {
                 self.handle_commitment(
                     voter_chain,
                     market_id,
                     commitment_hash,
-                    voting_power,
-                    stake_amount,
                 ).await;
             }
             
@@ -135,6 +162,26 @@ impl Contract for OracleCoordinatorContract {
                 ).await;
             }
             
+            Message::ScalarVoteReveal {
+                voter_chain,
+                market_id,
+                value,
+                salt,
+                confidence,
+            } => {
+                self.handle_scalar_reveal(voter_chain, market_id, value, salt, confidence).await;
+            }
+            
+            Message::BallotVoteReveal {
+                voter_chain,
+                market_id,
+                ballot,
+                salt,
+                confidence,
+            } => {
+                self.handle_ballot_reveal(voter_chain, market_id, ballot, salt, confidence).await;
+            }
+            
             Message::MarketResolutionRequest { 
                 market_id, 
                 requester 
@@ -147,7 +194,29 @@ impl Contract for OracleCoordinatorContract {
                 question,
                 outcomes,
             } => {
-                self.handle_market_resolution_request(market_id, question, outcomes).await;
+                self.handle_market_resolution_request(market_id, question, outcomes, MarketKind::Categorical).await;
+            }
+            
+            Message::ScalarResolutionRequest {
+                market_id,
+                question,
+                min,
+                max,
+                decimals,
+            } => {
+                let kind = MarketKind::Scalar { min: ScalarValue(min), max: ScalarValue(max), decimals };
+                self.handle_market_resolution_request(market_id, question, Vec::new(), kind).await;
+            }
+            
+            Message::BallotResolutionRequest {
+                market_id,
+                question,
+                outcomes,
+                kind,
+            } => {
+                if kind.is_ballot() {
+                    self.handle_market_resolution_request(market_id, question, outcomes, kind).await;
+                }
             }
             
             Message::DirectVote {
@@ -155,9 +224,25 @@ impl Contract for OracleCoordinatorContract {
                 market_id,
                 outcome_index,
                 confidence,
-                voting_power,
+                voting_power: _, // Recomputed from our own VoterInfo
             } => {
-                self.handle_direct_vote(voter_chain, market_id, outcome_index, confidence, voting_power).await;
+                self.handle_direct_vote(voter_chain, market_id, outcome_index, confidence).await;
+            }
+            
+            Message::StakeDeposit { voter_chain, amount } => {
+                self.handle_stake_deposit(voter_chain, amount).await;
+            }
+            
+            Message::UnstakeRequest { voter_chain, amount } => {
+                self.handle_unstake_request(voter_chain, amount).await;
+            }
+            
+            Message::UnstakeWithdrawal { voter_chain } => {
+                self.handle_unstake_withdrawal(voter_chain).await;
+            }
+            
+            Message::ResolutionFee { market_id, amount } => {
+                self.handle_resolution_fee(market_id, amount).await;
             }
             
             _ => {
@@ -167,57 +252,71 @@ impl Contract for OracleCoordinatorContract {
     }
 
     async fn store(mut self) {
-        // RootView automatically persists state
-        // No manual save needed
+        self.state.save().await.expect("Failed to save state");
     }
 }
 
 impl OracleCoordinatorContract {
-    /// Batas maksimal untuk data hashing
-    const MAX_HASH_INPUT: usize = 10_000;
+    /// Minimum number of voters a market may require
+    const MIN_VOTERS: u32 = 3;
 
     /// Create new market untuk resolusi - WASM SAFE
+    #[allow(clippy::too_many_arguments)]
     async fn create_market(
         &mut self,
         question: String,
         outcomes: Vec<String>,
         trading_deadline: Timestamp,
-        voting_deadline: Timestamp,
+        commit_deadline: Timestamp,
+        reveal_deadline: Timestamp,
         min_voters: u32,
         category: String,
+        aggregation_method: Option<AggregationMethod>,
+        kind: Option<MarketKind>,
     ) -> CoordinatorResponse {
         // Simple validation without panic
-        if outcomes.len() < 2 || outcomes.len() > 10 {
-            return CoordinatorResponse::Error { error_code: 1 }; // Invalid outcomes
+        let kind = kind.unwrap_or_default();
+        if kind.is_scalar() {
+            if !outcomes.is_empty() || !kind.is_valid() {
+                return CoordinatorResponse::Error(CoordinatorError::InvalidScalarRange);
+            }
+        } else if outcomes.len() < 2 || outcomes.len() > 10 {
+            return CoordinatorResponse::Error(CoordinatorError::InvalidOutcomes);
         }
         
-        if trading_deadline >= voting_deadline {
-            return CoordinatorResponse::Error { error_code: 2 }; // Invalid deadlines
+        if trading_deadline >= commit_deadline || commit_deadline >= reveal_deadline {
+            return CoordinatorResponse::Error(CoordinatorError::InvalidDeadlines);
         }
         
-        if min_voters < 3 {
-            return CoordinatorResponse::Error { error_code: 3 }; // Min voters too low
+        if min_voters < Self::MIN_VOTERS {
+            return CoordinatorResponse::Error(CoordinatorError::MinVotersTooLow);
+        }
+
+        let aggregation_method = match aggregation_method.or_else(|| AggregationMethod::default_for(&kind)) {
+            Some(method) => method,
+            None => self.runtime.application_parameters().aggregation_method,
+        };
+        if !aggregation_method.fits(&kind) {
+            return CoordinatorResponse::Error(CoordinatorError::AggregationMismatch);
         }
 
         let market_id = self.state.next_market_id().await;
         let creator = self.runtime.authenticated_signer();
 
-        let market = Market {
-            id: market_id,
-            question: question.clone(),
+        let market = Market::new(
+            market_id,
+            question.clone(),
             outcomes,
             creator,
-            created_at: self.runtime.system_time(),
+            self.runtime.system_time(),
             trading_deadline,
-            voting_deadline,
+            commit_deadline,
+            reveal_deadline,
             min_voters,
             category,
-            status: MarketStatus::Active,
-            winning_outcome: None,
-            confidence_score: None,
-            total_commitments: 0,
-            total_reveals: 0,
-        };
+            aggregation_method,
+            kind,
+        );
 
         self.state.add_market(market_id, market).await;
 
@@ -236,77 +335,258 @@ impl OracleCoordinatorContract {
         let params = self.runtime.application_parameters();
         
         if initial_stake < params.min_stake {
-            return CoordinatorResponse::Error { error_code: 4 }; // Insufficient stake
+            return CoordinatorResponse::Error(CoordinatorError::InsufficientStake);
         }
 
         // Check jika sudah terdaftar
         if self.state.is_voter_registered(&voter_chain).await {
-            return CoordinatorResponse::Error { error_code: 5 }; // Already registered
+            return CoordinatorResponse::Error(CoordinatorError::VoterAlreadyRegistered);
         }
 
-        let voter_info = VoterInfo {
-            chain_id: voter_chain,
-            reputation_score: 100, // Starting reputation
-            total_stake: initial_stake,
-            locked_stake: Amount::ZERO,
-            total_votes: 0,
-            total_correct: 0,
-            total_incorrect: 0,
-            correct_streak: 0,
-            registered_at: self.runtime.system_time(),
-            last_active: self.runtime.system_time(),
-        };
+        if let Err(error) = self.escrow_from_signer(initial_stake) {
+            return CoordinatorResponse::Error(error);
+        }
+
+        let voter_info = VoterInfo::new(voter_chain, initial_stake, self.runtime.system_time());
 
         self.state.register_voter(voter_chain, voter_info).await;
+        self.state.add_escrowed(initial_stake);
 
         CoordinatorResponse::VoterRegistered { 
             voter_chain,
-            initial_reputation: 100,
+            initial_reputation: INITIAL_REPUTATION,
+        }
+    }
+
+    /// Top up a registered voter's stake from the signer's tokens - WASM SAFE
+    async fn deposit_stake(&mut self, voter_chain: ChainId, amount: Amount) -> CoordinatorResponse {
+        if !self.state.is_voter_registered(&voter_chain).await {
+            return CoordinatorResponse::Error(CoordinatorError::VoterNotFound);
+        }
+
+        if let Err(error) = self.escrow_from_signer(amount) {
+            return CoordinatorResponse::Error(error);
+        }
+
+        match self.state.credit_voter_stake(voter_chain, amount).await {
+            Some(total_stake) => CoordinatorResponse::StakeDeposited { voter_chain, total_stake },
+            None => CoordinatorResponse::Error(CoordinatorError::VoterNotFound),
+        }
+    }
+
+    /// Escrow and credit stake a voter chain sent to its signer's account here
+    ///
+    /// The tokens are moved out of the message signer's own account, so a voter
+    /// is only ever credited with what it deposited itself. If they have not
+    /// arrived the deposit is ignored and they stay with the signer.
+    async fn handle_stake_deposit(&mut self, voter_chain: ChainId, amount: Amount) {
+        if !self.state.is_voter_registered(&voter_chain).await {
+            return;
+        }
+
+        if self.escrow_from_signer(amount).is_err() {
+            return; // Transfer belum sampai
+        }
+        self.state.credit_voter_stake(voter_chain, amount).await;
+    }
+
+    /// Start the unbonding period for `amount` of a voter's free stake
+    ///
+    /// Locked stake backs open commitments and stays put; a request for more
+    /// than is free is refused and the voter chain told so.
+    async fn handle_unstake_request(&mut self, voter_chain: ChainId, amount: Amount) {
+        let unbonding_period = self.runtime.application_parameters().unbonding_period;
+        let release_at = self.runtime.system_time().saturating_add(unbonding_period);
+        if !self.state.begin_unbonding(voter_chain, amount, release_at).await {
+            self.runtime.send_message(voter_chain, Message::UnstakeRejected { amount });
+        }
+    }
+
+    /// Pay out stake whose unbonding period has passed to the signer on the voter chain
+    async fn handle_unstake_withdrawal(&mut self, voter_chain: ChainId) {
+        let Some(signer) = self.runtime.authenticated_signer() else {
+            return;
+        };
+        let now = self.runtime.system_time();
+        let amount = self.state.release_unbonded(voter_chain, now).await;
+        if amount == Amount::ZERO {
+            return; // Belum ada yang bisa ditarik
+        }
+
+        let escrow = self.escrow_account();
+        let destination = Account { chain_id: voter_chain, owner: signer };
+        self.runtime.transfer(escrow.owner, destination, amount);
+        self.runtime.send_message(voter_chain, Message::StakeWithdrawn { amount });
+    }
+
+    /// Add a market chain's resolution fee to the reward pool of its market
+    ///
+    /// `market_id` is the id on the sending chain. Like stake deposits, the fee
+    /// is pulled from the payer's own account on this chain, so it is only
+    /// counted once that transfer has arrived. For an unknown market nothing is
+    /// pulled and the tokens stay with the payer.
+    async fn handle_resolution_fee(&mut self, origin_market_id: u64, amount: Amount) {
+        let Some(origin_chain) = self.runtime.message_origin_chain_id() else {
+            return;
+        };
+        if self.runtime.message_is_bouncing() == Some(true) {
+            return;
+        }
+        let Some(market_id) = self.state.get_market_for_origin(origin_chain, origin_market_id).await else {
+            return;
+        };
+
+        if self.escrow_from_signer(amount).is_err() {
+            return; // Transfer belum sampai
+        }
+
+        let settled = self
+            .state
+            .get_market(market_id)
+            .await
+            .is_none_or(|market| market.finalized);
+        if settled {
+            // Too late for this market's voters
+            self.state.add_escrowed(amount);
+            self.state.add_to_treasury(amount);
+            return;
+        }
+        self.state.add_to_reward_pool(market_id, amount).await;
+    }
+
+    /// Application account on this chain that holds all voter stake
+    fn escrow_account(&mut self) -> Account {
+        Account {
+            chain_id: self.runtime.chain_id(),
+            owner: AccountOwner::from(self.runtime.application_id().forget_abi()),
         }
     }
 
+    /// Move `amount` from the operation signer into escrow
+    fn escrow_from_signer(&mut self, amount: Amount) -> Result<(), CoordinatorError> {
+        let signer = self
+            .runtime
+            .authenticated_signer()
+            .ok_or(CoordinatorError::Unauthenticated)?;
+        if self.runtime.owner_balance(signer) < amount {
+            return Err(CoordinatorError::InsufficientBalance);
+        }
+
+        let escrow = self.escrow_account();
+        self.runtime.transfer(signer, escrow, amount);
+        Ok(())
+    }
+
+    /// Voter chain a voter message claims to come from
+    fn claimed_voter(message: &Message) -> Option<ChainId> {
+        match message {
+            Message::VoteCommitment { voter_chain, .. }
+            | Message::VoteReveal { voter_chain, .. }
+            | Message::ScalarVoteReveal { voter_chain, .. }
+            | Message::BallotVoteReveal { voter_chain, .. }
+            | Message::DirectVote { voter_chain, .. }
+            | Message::StakeDeposit { voter_chain, .. }
+            | Message::UnstakeRequest { voter_chain, .. }
+            | Message::UnstakeWithdrawal { voter_chain } => Some(*voter_chain),
+            _ => None,
+        }
+    }
+
+    /// Check that a voter message really comes from `voter_chain`
+    ///
+    /// The claimed voter must be the message origin, and the message must be
+    /// signed (sent `with_authentication`) and not a bounced message.
+    fn authenticate_voter(&mut self, voter_chain: ChainId) -> bool {
+        if self.runtime.message_origin_chain_id() != Some(voter_chain) {
+            return false;
+        }
+        if self.runtime.authenticated_signer().is_none() {
+            return false;
+        }
+        self.runtime.message_is_bouncing() == Some(false)
+    }
+
+    /// Voting power factors of a registered voter, from our own records; None if unregistered
+    async fn voting_power_of(&mut self, voter_chain: ChainId) -> Option<PowerFactors> {
+        let curve = self.runtime.application_parameters().voting_power_curve;
+        let info = self.state.get_voter_info(voter_chain).await?;
+        Some(info.power_factors(curve))
+    }
+
     /// Start voting period untuk market - WASM SAFE
     async fn start_voting(&mut self, market_id: u64) -> CoordinatorResponse {
         let market = match self.state.get_market(market_id).await {
             Some(m) => m,
-            None => return CoordinatorResponse::Error { error_code: 6 }, // Market not found
+            None => return CoordinatorResponse::Error(CoordinatorError::MarketNotFound),
         };
 
         // Validasi status
-        if market.status != MarketStatus::Active {
-            return CoordinatorResponse::Error { error_code: 7 }; // Market not active
+        if !market.status.can_transition_to(MarketStatus::VotingStarted) {
+            return CoordinatorResponse::Error(CoordinatorError::MarketNotActive);
         }
 
         // Check deadline
         if self.runtime.system_time() < market.trading_deadline {
-            return CoordinatorResponse::Error { error_code: 8 }; // Trading period not ended
+            return CoordinatorResponse::Error(CoordinatorError::TradingPeriodNotEnded);
         }
 
-        // Update status
+        // Update status; from here on the deadlines drive the phase
         let mut updated_market = market.clone();
-        updated_market.status = MarketStatus::VotingStarted;
+        if let Err(error) = updated_market.transition(MarketStatus::VotingStarted) {
+            return CoordinatorResponse::Error(error);
+        }
+        updated_market.advance_phase(self.runtime.system_time());
         self.state.update_market(market_id, updated_market.clone()).await;
 
-        // Broadcast ke semua registered voters
+        let total_voters = self.request_votes(&updated_market).await;
+
+        CoordinatorResponse::VotingStarted { 
+            market_id,
+            total_voters_notified: total_voters,
+        }
+    }
+
+    /// Broadcast a VotingRequest for `market` ke semua registered voters
+    async fn request_votes(&mut self, market: &Market) -> u32 {
         let voters = self.state.get_all_voters().await;
         let total_voters = voters.len();
         
         for voter_chain in voters {
-            self.runtime.send_message(
-                voter_chain,
-                Message::VotingRequest {
-                    market_id,
-                    question: updated_market.question.clone(),
-                    outcomes: updated_market.outcomes.clone(),
-                    deadline: updated_market.voting_deadline,
+            let request = match market.kind {
+                MarketKind::Categorical => Message::VotingRequest {
+                    market_id: market.id,
+                    question: market.question.clone(),
+                    outcomes: market.outcomes.clone(),
+                    deadline: market.commit_deadline,
                 },
-            );
+                MarketKind::Scalar { min, max, decimals } => Message::ScalarVotingRequest {
+                    market_id: market.id,
+                    question: market.question.clone(),
+                    min: min.0,
+                    max: max.0,
+                    decimals,
+                    deadline: market.commit_deadline,
+                },
+                MarketKind::MultiSelect | MarketKind::Ranked => Message::BallotVotingRequest {
+                    market_id: market.id,
+                    question: market.question.clone(),
+                    outcomes: market.outcomes.clone(),
+                    kind: market.kind,
+                    deadline: market.commit_deadline,
+                },
+            };
+            self.runtime.send_message(voter_chain, request);
         }
+        total_voters as u32
+    }
 
-        CoordinatorResponse::VotingStarted { 
-            market_id,
-            total_voters_notified: total_voters as u32,
+    /// Load a market with its phase brought up to the current time
+    async fn market_at_now(&mut self, market_id: u64) -> Option<Market> {
+        let mut market = self.state.get_market(market_id).await?;
+        if market.advance_phase(self.runtime.system_time()) {
+            self.state.update_market(market_id, market.clone()).await;
         }
+        Some(market)
     }
 
     /// Handle commitment dari voter - WASM SAFE
@@ -315,31 +595,40 @@ impl OracleCoordinatorContract {
         voter_chain: ChainId,
         market_id: u64,
         commitment_hash: [u8; 32],
-        voting_power: u64,
-        stake_amount: Amount,
     ) {
         // Validasi voter terdaftar
-        if !self.state.is_voter_registered(&voter_chain).await {
+        let Some(power_factors) = self.voting_power_of(voter_chain).await else {
             return; // Ignore dari unregistered voter
-        }
+        };
 
         // Validasi market status
-        let market = match self.state.get_market(market_id).await {
+        let market = match self.market_at_now(market_id).await {
             Some(m) => m,
             None => return,
         };
 
-        if market.status != MarketStatus::VotingStarted 
-            && market.status != MarketStatus::CommitPhase {
-            return; // Ignore jika bukan fase commit
+        if !market.is_commit_phase() {
+            return; // Ignore jika commit sudah ditutup
+        }
+
+        // One commitment per voter per round; a resend must not lock stake again
+        if self.state.get_commitment(market_id, voter_chain).await.is_some() {
+            return;
+        }
+
+        // Lock the round's stake; a commitment must be backed by free escrowed stake
+        let stake_amount = self.round_stake(&market);
+        if !self.state.lock_voter_stake(voter_chain, stake_amount).await {
+            return;
         }
 
         // Store commitment
-        let commitment = alethea_oracle_types::VoteCommitment {
+        let commitment = VoteCommitment {
             voter_chain,
             market_id,
             commitment_hash,
-            voting_power,
+            voting_power: power_factors.voting_power(),
+            power_factors,
             stake_amount,
             committed_at: self.runtime.system_time(),
         };
@@ -347,19 +636,31 @@ impl OracleCoordinatorContract {
         self.state.add_commitment(market_id, voter_chain, commitment).await;
 
         // Update market status jika perlu
-        if market.status == MarketStatus::VotingStarted {
-            let mut updated_market = market;
-            updated_market.status = MarketStatus::CommitPhase;
-            updated_market.total_commitments = 1;
-            self.state.update_market(market_id, updated_market).await;
+        let mut updated_market = market;
+        updated_market.record_commitment();
+        self.state.update_market(market_id, updated_market).await;
+    }
+
+    /// Stake each commitment locks: the minimum stake, or a multiple of it in an appeal round
+    ///
+    /// Locking a fixed amount per vote, rather than whatever the voter chain
+    /// offers, leaves the rest of a voter's stake free for other markets.
+    fn round_stake(&mut self, market: &Market) -> Amount {
+        let params = self.runtime.application_parameters();
+        if market.dispute.is_some() {
+            params.min_stake.saturating_mul(u128::from(params.appeal_multiplier))
         } else {
-            let mut updated_market = market;
-            updated_market.total_commitments += 1;
-            self.state.update_market(market_id, updated_market).await;
+            params.min_stake
         }
+    }
 
-        // Lock stake
-        self.state.lock_voter_stake(voter_chain, stake_amount).await;
+    /// Slash up to `amount` of a voter's stake and tell its chain what was taken
+    async fn slash_voter(&mut self, voter_chain: ChainId, amount: Amount) -> Amount {
+        let slashed = self.state.slash_voter(voter_chain, amount).await;
+        if slashed > Amount::ZERO {
+            self.runtime.send_message(voter_chain, Message::StakeSlashed { amount: slashed });
+        }
+        slashed
     }
 
     /// Handle reveal dari voter - WASM SAFE
@@ -371,46 +672,106 @@ impl OracleCoordinatorContract {
         salt: [u8; 32],
         confidence: u8,
     ) {
+        self.accept_reveal(voter_chain, market_id, outcome_index, None, None, salt, confidence).await;
+    }
+
+    /// Handle a scalar market's reveal dari voter - WASM SAFE
+    async fn handle_scalar_reveal(
+        &mut self,
+        voter_chain: ChainId,
+        market_id: u64,
+        value: i128,
+        salt: [u8; 32],
+        confidence: u8,
+    ) {
+        self.accept_reveal(voter_chain, market_id, 0, Some(value), None, salt, confidence).await;
+    }
+
+    /// Handle a multi-select or ranked market's reveal dari voter - WASM SAFE
+    async fn handle_ballot_reveal(
+        &mut self,
+        voter_chain: ChainId,
+        market_id: u64,
+        ballot: Ballot,
+        salt: [u8; 32],
+        confidence: u8,
+    ) {
+        self.accept_reveal(voter_chain, market_id, 0, None, Some(ballot), salt, confidence).await;
+    }
+
+    /// Verify a reveal against its commitment and store it - WASM SAFE
+    ///
+    /// `value` is Some exactly for scalar markets and `ballot` exactly for
+    /// multi-select and ranked ones; a reveal of the wrong kind, or a ballot
+    /// that does not fit the market, is ignored, so its commitment is settled
+    /// as never revealed.
+    #[allow(clippy::too_many_arguments)]
+    async fn accept_reveal(
+        &mut self,
+        voter_chain: ChainId,
+        market_id: u64,
+        outcome_index: usize,
+        value: Option<i128>,
+        ballot: Option<Ballot>,
+        salt: [u8; 32],
+        confidence: u8,
+    ) {
+        // Reveals only count inside the reveal window
+        let mut market = match self.market_at_now(market_id).await {
+            Some(m) => m,
+            None => return,
+        };
+        if !market.is_reveal_phase() || value.is_some() != market.kind.is_scalar() {
+            return;
+        }
+        match &ballot {
+            Some(ballot) if !ballot.fits(&market.kind, market.outcomes.len()) => return,
+            None if market.kind.is_ballot() => return,
+            _ => {}
+        }
+
         // Get commitment
         let commitment = match self.state.get_commitment(market_id, voter_chain).await {
             Some(c) => c,
             None => return, // No commitment found
         };
 
-        // Verify reveal matches commitment - FIXED ARRAY
-        let mut data = [0u8; 40]; // 8 bytes outcome + 32 bytes salt
-        data[..8].copy_from_slice(&outcome_index.to_le_bytes());
-        data[8..40].copy_from_slice(&salt);
-
-        let computed_hash = self.simple_hash(&data);
-        
-        if computed_hash != commitment.commitment_hash {
-            // Invalid reveal - slash stake
-            self.state.slash_voter(voter_chain, commitment.stake_amount).await;
+        // Sudah reveal: a resend is not a second vote
+        if self.state.get_reveal(market_id, voter_chain).await.is_some() {
             return;
         }
 
-        // Valid reveal - store it
-        let reveal = alethea_oracle_types::VoteReveal {
+        let reveal = VoteReveal {
             voter_chain,
             market_id,
             outcome_index,
+            value,
+            ballot,
             salt,
             confidence,
             voting_power: commitment.voting_power,
+            power_factors: commitment.power_factors,
             revealed_at: self.runtime.system_time(),
         };
 
+        // Verify reveal matches commitment
+        if !commitment.verify_reveal(&reveal) {
+            // Invalid reveal - slash stake and settle the commitment now, so
+            // settle_non_reveals does not punish it a second time
+            let slashed = self.slash_voter(voter_chain, commitment.stake_amount).await;
+            self.state
+                .unlock_voter_stake(voter_chain, commitment.stake_amount.saturating_sub(slashed))
+                .await;
+            self.state.update_voter_reputation_no_reveal(voter_chain).await;
+            self.state.remove_commitment(market_id, voter_chain).await;
+            return;
+        }
+
+        // Valid reveal - store it
         self.state.add_reveal(market_id, voter_chain, reveal).await;
 
-        // Update market status
-        let mut market = self.state.get_market(market_id).await
-            .expect("Market not found");
-        
-        if market.status == MarketStatus::CommitPhase {
-            market.status = MarketStatus::RevealPhase;
-        }
-        market.total_reveals += 1;
+        // Update market stats
+        market.record_reveal();
         
         self.state.update_market(market_id, market).await;
     }
@@ -422,202 +783,378 @@ impl OracleCoordinatorContract {
         market_id: u64,
         outcome_index: usize,
         confidence: u8,
-        voting_power: u64,
     ) {
         // Check if voter is registered
-        if !self.state.is_voter_registered(&voter_chain).await {
+        let Some(power_factors) = self.voting_power_of(voter_chain).await else {
             return; // Ignore unregistered voter
-        }
+        };
 
         // Check if market exists
-        let mut market = match self.state.get_market(market_id).await {
+        let mut market = match self.market_at_now(market_id).await {
             Some(m) => m,
             None => return,
         };
 
-        // Accept direct votes in any active voting phase
-        if !market.is_voting_phase() {
+        // Direct votes close with the commit window; appeal rounds only take staked votes
+        if !market.is_commit_phase() || market.dispute.is_some() || market.kind != MarketKind::Categorical {
+            return;
+        }
+
+        // One vote per voter: a committed voter has to reveal, and a vote already in stays put
+        if self.state.get_commitment(market_id, voter_chain).await.is_some()
+            || self.state.get_reveal(market_id, voter_chain).await.is_some()
+        {
             return;
         }
 
         // Convert direct vote to reveal format for compatibility
-        let reveal = alethea_oracle_types::VoteReveal {
+        let reveal = VoteReveal {
             voter_chain,
             market_id,
             outcome_index,
+            value: None,
+            ballot: None,
             salt: [0u8; 32], // No salt for direct votes
             confidence,
-            voting_power,
+            voting_power: power_factors.voting_power(),
+            power_factors,
             revealed_at: self.runtime.system_time(),
         };
 
         self.state.add_reveal(market_id, voter_chain, reveal).await;
 
-        // Update market status
-        market.total_reveals += 1;
+        // Update market stats
+        market.record_direct_vote();
         self.state.update_market(market_id, market).await;
     }
 
     /// Aggregate votes dan resolve market - WASM SAFE
     async fn aggregate_votes(&mut self, market_id: u64) -> CoordinatorResponse {
-        let market = match self.state.get_market(market_id).await {
+        let market = match self.market_at_now(market_id).await {
             Some(m) => m,
-            None => return CoordinatorResponse::Error { error_code: 6 }, // Market not found
+            None => return CoordinatorResponse::Error(CoordinatorError::MarketNotFound),
         };
 
-        // Validasi status - accept if in reveal phase OR if has votes (for direct voting)
-        if market.status != MarketStatus::RevealPhase && market.total_reveals == 0 {
-            return CoordinatorResponse::Error { error_code: 9 }; // Market not in reveal phase
+        // Validasi status - commits must be closed
+        if !matches!(market.status, MarketStatus::RevealPhase | MarketStatus::Aggregating) {
+            return CoordinatorResponse::Error(CoordinatorError::NotInRevealPhase);
         }
 
-        // Check jika cukup reveals
-        if market.total_reveals < market.min_voters {
-            return CoordinatorResponse::Error { error_code: 10 }; // Not enough reveals
-        }
+        // Check jika cukup reveals - one per voter, whatever the counters say
+        let reveals = self.state.get_all_reveals(market_id).await;
+        let total_voters = reveals.len() as u32;
+        let has_quorum = total_voters >= market.min_voters;
 
-        // Check deadline
-        if self.runtime.system_time() < market.voting_deadline {
-            return CoordinatorResponse::Error { error_code: 11 }; // Voting deadline not reached
+        // Check deadline - reveals must be closed too
+        if market.status != MarketStatus::Aggregating {
+            let error = if has_quorum {
+                CoordinatorError::VotingDeadlineNotReached
+            } else {
+                CoordinatorError::NotEnoughReveals
+            };
+            return CoordinatorResponse::Error(error);
         }
 
-        // Get all reveals
-        let reveals = self.state.get_all_reveals(market_id).await;
+        // Reveals closed without quorum: no more votes can come, so the market
+        // ends Invalid and settlement releases stakes and refunds any bond
+        if !has_quorum {
+            let mut invalid_market = market;
+            if let Err(error) = invalid_market.invalidate(0) {
+                return CoordinatorResponse::Error(error);
+            }
+            self.settle_market(invalid_market).await;
 
-        // Aggregate menggunakan weighted majority
-        let (winning_outcome, confidence) = self.aggregate_weighted_majority(
-            reveals.clone(),
-            market.outcomes.len(),
-        );
+            return CoordinatorResponse::MarketInvalid {
+                market_id,
+                confidence: 0,
+                total_voters,
+            };
+        }
 
-        // Update market dengan hasil
+        // Aggregate with the method chosen for this market
         let mut updated_market = market.clone();
-        updated_market.status = MarketStatus::Resolved;
-        updated_market.winning_outcome = Some(winning_outcome);
-        updated_market.confidence_score = Some(confidence);
-
-        self.state.update_market(market_id, updated_market.clone()).await;
+        let (confidence, resolved) = if let Some(aggregator) = market.aggregation_method.scalar_aggregator() {
+            let ScalarAggregate { value, confidence } = aggregator
+                .aggregate(&reveals, &market.kind)
+                .unwrap_or(ScalarAggregate { value: 0, confidence: 0 }); // No weight behind any value
+            (confidence, updated_market.resolve_value(value, confidence))
+        } else if let Some(aggregator) = market.aggregation_method.ballot_aggregator() {
+            let BallotAggregate { ballot, confidence } = aggregator.aggregate(&reveals, market.outcomes.len());
+            (confidence, updated_market.resolve_ballot(ballot, confidence))
+        } else {
+            let Aggregate { winning_outcome, confidence } = market
+                .aggregation_method
+                .aggregator()
+                .and_then(|aggregator| aggregator.aggregate(&reveals, market.outcomes.len()))
+                .unwrap_or(Aggregate { winning_outcome: 0, confidence: 0 }); // No weight behind any outcome
+            (confidence, updated_market.resolve(winning_outcome, confidence))
+        };
+        if let Err(error) = resolved {
+            return CoordinatorResponse::Error(error);
+        }
 
-        // Update reputation untuk semua voters
-        for reveal in &reveals {
-            let was_correct = reveal.outcome_index == winning_outcome;
-            
-            if was_correct {
-                self.state.update_voter_reputation_correct(reveal.voter_chain).await;
-            } else {
-                self.state.update_voter_reputation_incorrect(reveal.voter_chain).await;
+        // Too little agreement: the market ends Invalid and is settled at once
+        let params = self.runtime.application_parameters();
+        let min_consensus = market.aggregation_method.min_consensus().unwrap_or(params.min_consensus);
+        if confidence < min_consensus {
+            let mut invalid_market = market.clone();
+            if let Err(error) = invalid_market.invalidate(confidence) {
+                return CoordinatorResponse::Error(error);
             }
+            self.settle_market(invalid_market).await;
+
+            return CoordinatorResponse::MarketInvalid {
+                market_id,
+                confidence,
+                total_voters,
+            };
         }
 
-        // Distribute rewards ke correct voters
-        self.distribute_rewards(market_id, winning_outcome, reveals.clone()).await;
+        // Settlement waits out the challenge window; an appeal's outcome is final
+        let now = self.runtime.system_time();
+        let challenge_deadline = if updated_market.dispute.is_some() {
+            now
+        } else {
+            now.saturating_add(params.challenge_period)
+        };
+        updated_market.challenge_deadline = Some(challenge_deadline);
+        self.state.update_market(market_id, updated_market.clone()).await;
 
-        // Send resolution ke market creator
-        let timestamp = self.runtime.system_time();
-        let chain_id = self.runtime.chain_id();
-        self.runtime.send_message(
-            // TODO: Get market app chain from market creator
-            chain_id,
-            Message::MarketResolved {
+        if now >= challenge_deadline {
+            self.settle_market(updated_market.clone()).await;
+        }
+
+        if let Some(value) = updated_market.winning_value {
+            return CoordinatorResponse::ScalarMarketResolved {
                 market_id,
-                outcome: winning_outcome,
+                value,
                 confidence,
-                timestamp,
-            },
-        );
-
+                total_voters,
+            };
+        }
+        if let Some(ballot) = updated_market.winning_ballot {
+            return CoordinatorResponse::BallotMarketResolved {
+                market_id,
+                ballot,
+                confidence,
+                total_voters,
+            };
+        }
         CoordinatorResponse::MarketResolved { 
             market_id,
-            winning_outcome,
+            winning_outcome: updated_market.winning_outcome.unwrap_or(0),
             confidence,
-            total_voters: reveals.len() as u32,
+            total_voters,
         }
     }
 
-    /// Weighted majority aggregation - WASM SAFE
-    fn aggregate_weighted_majority(
-        &self,
-        reveals: Vec<alethea_oracle_types::VoteReveal>,
-        num_outcomes: usize,
-    ) -> (usize, u8) {
-        use std::collections::HashMap;
-
-        let mut outcome_weights: HashMap<usize, f64> = HashMap::new();
-        let mut total_weight = 0.0;
+    /// Challenge a resolved market and open its appeal round - WASM SAFE
+    async fn dispute_resolution(&mut self, market_id: u64, bond: Amount) -> CoordinatorResponse {
+        let market = match self.state.get_market(market_id).await {
+            Some(m) => m,
+            None => return CoordinatorResponse::Error(CoordinatorError::MarketNotFound),
+        };
 
-        for reveal in reveals {
-            // Weight = voting_power * confidence
-            let weight = (reveal.voting_power as f64) 
-                * (reveal.confidence as f64 / 100.0);
-            
-            *outcome_weights.entry(reveal.outcome_index).or_insert(0.0) += weight;
-            total_weight += weight;
+        let now = self.runtime.system_time();
+        if market.dispute.is_some() {
+            return CoordinatorResponse::Error(CoordinatorError::AlreadyDisputed);
+        }
+        if !market.is_resolved() {
+            return CoordinatorResponse::Error(CoordinatorError::MarketNotResolved);
+        }
+        if !market.can_be_disputed(now) {
+            return CoordinatorResponse::Error(CoordinatorError::ChallengeWindowClosed);
         }
 
-        // Find winner
-        let (winning_outcome, winning_weight) = outcome_weights
-            .iter()
-            .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
-            .map(|(idx, weight)| (*idx, *weight))
-            .unwrap_or((0, 0.0));
+        let params = self.runtime.application_parameters();
+        if bond < params.min_dispute_bond {
+            return CoordinatorResponse::Error(CoordinatorError::DisputeBondTooLow);
+        }
+        let Some(disputer) = self.runtime.authenticated_signer() else {
+            return CoordinatorResponse::Error(CoordinatorError::Unauthenticated);
+        };
 
-        // Calculate confidence (percentage of winning outcome)
-        let confidence = if total_weight > 0.0 {
-            ((winning_weight / total_weight) * 100.0).min(100.0) as u8
-        } else {
-            0
+        // Second round: fresh deadlines, larger quorum
+        let dispute = Dispute {
+            disputer,
+            bond,
+            disputed_outcome: market.winning_outcome.unwrap_or(0),
+            disputed_value: market.winning_value,
+            disputed_ballot: market.winning_ballot.clone(),
+            disputed_at: now,
         };
+        let commit_deadline = now.saturating_add(params.commit_period);
+        let reveal_deadline = commit_deadline.saturating_add(params.reveal_period);
+        let min_voters = market.min_voters.saturating_mul(params.appeal_multiplier);
+        let mut appeal = market;
+        if let Err(error) = appeal.open_appeal(dispute, commit_deadline, reveal_deadline, min_voters) {
+            return CoordinatorResponse::Error(error);
+        }
 
-        (winning_outcome, confidence)
+        if let Err(error) = self.escrow_from_signer(bond) {
+            return CoordinatorResponse::Error(error);
+        }
+        self.state.add_escrowed(bond);
+
+        appeal.advance_phase(now);
+        self.state.archive_round(market_id).await;
+        self.state.update_market(market_id, appeal.clone()).await;
+        self.request_votes(&appeal).await;
+
+        CoordinatorResponse::DisputeOpened {
+            market_id,
+            min_voters,
+            commit_deadline,
+            reveal_deadline,
+        }
     }
 
-    /// Distribute rewards ke correct voters - WASM SAFE
-    async fn distribute_rewards(
-        &mut self,
-        market_id: u64,
-        winning_outcome: usize,
-        reveals: Vec<alethea_oracle_types::VoteReveal>,
-    ) {
-        let params = self.runtime.application_parameters();
+    /// Settle a resolved market whose challenge window has passed - WASM SAFE
+    async fn finalize_resolution(&mut self, market_id: u64) -> CoordinatorResponse {
+        let market = match self.state.get_market(market_id).await {
+            Some(m) => m,
+            None => return CoordinatorResponse::Error(CoordinatorError::MarketNotFound),
+        };
+
+        if market.finalized {
+            return CoordinatorResponse::Error(CoordinatorError::AlreadyFinalized);
+        }
+        if !market.is_resolved() {
+            return CoordinatorResponse::Error(CoordinatorError::MarketNotResolved);
+        }
+        if market.can_be_disputed(self.runtime.system_time()) {
+            return CoordinatorResponse::Error(CoordinatorError::ChallengeWindowOpen);
+        }
+
+        let (winning_outcome, winning_value) = (market.winning_outcome, market.winning_value);
+        let winning_ballot = market.winning_ballot.clone();
+        self.settle_market(market).await;
+        CoordinatorResponse::ResolutionFinalized { market_id, winning_outcome, winning_value, winning_ballot }
+    }
+
+    /// Pay out, slash and announce a resolution that can no longer be disputed
+    ///
+    /// After an appeal both rounds are settled against the appeal's outcome.
+    /// Only appeal voters share the fee pool, which also gets the bond if the
+    /// challenge failed; a successful challenger is refunded. An Invalid market
+    /// is settled without an outcome.
+    async fn settle_market(&mut self, mut market: Market) {
+        if !market.is_resolved() && !market.is_invalid() {
+            return;
+        }
+        let market_id = market.id;
+        let mut pool = self.state.take_reward_pool(market_id).await;
+
+        if let Some(dispute) = market.dispute.clone() {
+            if let Some((commitments, reveals)) = self.state.take_appealed_round(market_id).await {
+                self.settle_round(&market, &commitments, &reveals, Amount::ZERO).await;
+            }
+
+            if market.upholds(&dispute) {
+                pool = pool.saturating_add(dispute.bond);
+            } else {
+                let escrow = self.escrow_account();
+                let disputer = Account { chain_id: escrow.chain_id, owner: dispute.disputer };
+                self.runtime.transfer(escrow.owner, disputer, dispute.bond);
+                self.state.remove_escrowed(dispute.bond);
+            }
+        }
+
+        let commitments = self.state.get_all_commitments(market_id).await;
+        let reveals = self.state.get_all_reveals(market_id).await;
+        self.settle_round(&market, &commitments, &reveals, pool).await;
+
+        market.finalized = true;
+        self.state.update_market(market_id, market.clone()).await;
+
+        // Send resolution ke semua requesters
+        for requester in self.state.get_requesters(market_id).await {
+            self.send_resolution(requester, &market);
+        }
+    }
+
+    /// Reputation, rewards and slashes for one round of votes on a market
+    ///
+    /// Without a result no reveal was right or wrong: revealed stake is
+    /// released whole and the pool goes to the treasury. Withheld votes are
+    /// still slashed.
+    async fn settle_round(
+        &mut self,
+        market: &Market,
+        commitments: &[VoteCommitment],
+        reveals: &[VoteReveal],
+        pool: Amount,
+    ) {
+        if !market.is_resolved() {
+            for reveal in reveals {
+                if let Some(commitment) = commitments.iter().find(|c| c.voter_chain == reveal.voter_chain) {
+                    self.state.unlock_voter_stake(reveal.voter_chain, commitment.stake_amount).await;
+                }
+            }
+            self.state.add_to_treasury(pool);
+            self.settle_non_reveals(commitments, reveals).await;
+            return;
+        }
+
+        // Update reputation untuk semua voters
+        for reveal in reveals {
+            let was_correct = market.reveal_is_correct(reveal) == Some(true);
+            
+            if was_correct {
+                self.state.update_voter_reputation_correct(reveal.voter_chain).await;
+            } else {
+                self.state.update_voter_reputation_incorrect(reveal.voter_chain).await;
+            }
+        }
+
+        // Distribute rewards ke correct voters
+        self.distribute_rewards(market, commitments, reveals, pool).await;
+
+        // Penalize commitments that were withheld
+        self.settle_non_reveals(commitments, reveals).await;
+    }
+
+    /// Distribute rewards ke correct voters - WASM SAFE
+    async fn distribute_rewards(
+        &mut self,
+        market: &Market,
+        commitments: &[VoteCommitment],
+        reveals: &[VoteReveal],
+        pool: Amount,
+    ) {
+        let params = self.runtime.application_parameters();
+        let market_id = market.id;
+        let commitment_of = |voter: ChainId| commitments.iter().find(|c| c.voter_chain == voter);
         
         // Filter correct voters
         let correct_voters: Vec<_> = reveals
             .iter()
-            .filter(|r| r.outcome_index == winning_outcome)
+            .filter(|r| market.reveal_is_correct(r) == Some(true))
             .collect();
 
-        if correct_voters.is_empty() {
-            return;
-        }
+        // Split the fee pool between voters and the treasury
+        let voter_pool = rewards::percentage_of(pool, params.reward_pool_percentage);
+        let mut paid = Amount::ZERO;
 
-        // Calculate total voting power dari correct voters
-        let total_voting_power: u64 = correct_voters
-            .iter()
-            .map(|v| v.voting_power)
-            .sum();
-
-        // Reward pool (bisa dari market fees, untuk sekarang fixed amount)
-        let reward_pool = Amount::from_tokens(1000); // TODO: Calculate from fees
-
-        // Distribute proportionally
-        for voter in correct_voters {
-            let voter_share = (voter.voting_power as f64) 
-                / (total_voting_power as f64);
-            let reward_pool_u128: u128 = reward_pool.into();
-            let reward = Amount::from_tokens(
-                (reward_pool_u128 as f64 * voter_share) as u128
-            );
+        // Distribute proportionally to voting power, exact to the atto
+        let weights: Vec<u64> = correct_voters.iter().map(|v| v.voting_power).collect();
+        let shares = rewards::split_pro_rata(voter_pool, &weights);
 
-            // Send reward ke voter chain
-            self.runtime.send_message(
-                voter.voter_chain,
-                Message::RewardPayment {
-                    amount: reward,
-                    market_id,
-                },
-            );
+        for (voter, reward) in correct_voters.into_iter().zip(shares) {
+            // Rewards are paid into the voter's escrowed stake
+            if reward > Amount::ZERO && self.state.pay_reward(voter.voter_chain, reward).await {
+                paid = paid.saturating_add(reward);
+                self.runtime.send_message(
+                    voter.voter_chain,
+                    Message::RewardPayment {
+                        amount: reward,
+                        market_id,
+                    },
+                );
+            }
 
             // Unlock stake
-            if let Some(commitment) = self.state.get_commitment(market_id, voter.voter_chain).await {
+            if let Some(commitment) = commitment_of(voter.voter_chain) {
                 self.state.unlock_voter_stake(
                     voter.voter_chain, 
                     commitment.stake_amount
@@ -627,25 +1164,50 @@ impl OracleCoordinatorContract {
 
         // Slash incorrect voters
         for reveal in reveals {
-            if reveal.outcome_index != winning_outcome {
-                if let Some(commitment) = self.state.get_commitment(market_id, reveal.voter_chain).await {
-                    // Slash percentage dari stake
-                    let stake_u128: u128 = commitment.stake_amount.into();
-                    let slash_amount = Amount::from_tokens(
-                        (stake_u128 * params.slash_percentage as u128) / 100
-                    );
+            if market.reveal_is_correct(reveal) == Some(false) {
+                if let Some(commitment) = commitment_of(reveal.voter_chain) {
+                    // Slash percentage dari stake, release the rest
+                    let slash_amount = rewards::percentage_of(commitment.stake_amount, params.slash_percentage);
                     
-                    self.state.slash_voter(reveal.voter_chain, slash_amount).await;
+                    let slashed = self.slash_voter(reveal.voter_chain, slash_amount).await;
+                    self.state
+                        .unlock_voter_stake(reveal.voter_chain, commitment.stake_amount.saturating_sub(slashed))
+                        .await;
                 }
             }
         }
+
+        // Protocol share plus anything not paid out to voters
+        self.state.add_to_treasury(pool.saturating_sub(paid));
+    }
+
+    /// Slash and unlock every commitment in `commitments` that has no reveal
+    ///
+    /// Without this a voter could commit, watch the other reveals and quietly
+    /// walk away from a losing vote.
+    async fn settle_non_reveals(&mut self, commitments: &[VoteCommitment], reveals: &[VoteReveal]) {
+        let percentage = self.runtime.application_parameters().non_reveal_slash_percentage;
+
+        for commitment in commitments {
+            let voter = commitment.voter_chain;
+            if reveals.iter().any(|reveal| reveal.voter_chain == voter) {
+                continue;
+            }
+
+            let slash_amount = rewards::percentage_of(commitment.stake_amount, percentage);
+            let slashed = self.slash_voter(voter, slash_amount).await;
+            self.state
+                .unlock_voter_stake(voter, commitment.stake_amount.saturating_sub(slashed))
+                .await;
+            self.state.update_voter_reputation_no_reveal(voter).await;
+        }
     }
 
     /// Get market info
     async fn get_market(&mut self, market_id: u64) -> CoordinatorResponse {
         match self.state.get_market(market_id).await {
-            Some(market) => CoordinatorResponse::Market(market),
-            None => CoordinatorResponse::Error { error_code: 6 }, // Market not found
+            Some(market) => CoordinatorResponse::Market(Box::new(market)),
+            None => CoordinatorResponse::Error(CoordinatorError::MarketNotFound),
         }
     }
 
@@ -659,7 +1221,7 @@ impl OracleCoordinatorContract {
     async fn get_voter_info(&mut self, voter_chain: ChainId) -> CoordinatorResponse {
         match self.state.get_voter_info(voter_chain).await {
             Some(info) => CoordinatorResponse::VoterInfo(info),
-            None => CoordinatorResponse::Error { error_code: 12 }, // Voter not found
+            None => CoordinatorResponse::Error(CoordinatorError::VoterNotFound),
         }
     }
 
@@ -667,23 +1229,10 @@ impl OracleCoordinatorContract {
     async fn get_market_stats(&mut self, market_id: u64) -> CoordinatorResponse {
         let market = match self.state.get_market(market_id).await {
             Some(m) => m,
-            None => return CoordinatorResponse::Error { error_code: 6 }, // Market not found
+            None => return CoordinatorResponse::Error(CoordinatorError::MarketNotFound),
         };
 
-        let stats = alethea_oracle_types::MarketStats {
-            market_id,
-            total_commitments: market.total_commitments,
-            total_reveals: market.total_reveals,
-            participation_rate: if market.total_commitments > 0 {
-                (market.total_reveals as f64 / market.total_commitments as f64 * 100.0) as u8
-            } else {
-                0
-            },
-            confidence_score: market.confidence_score.unwrap_or(0),
-            status: market.status,
-        };
-
-        CoordinatorResponse::MarketStats(stats)
+        CoordinatorResponse::MarketStats(market.stats())
     }
 
     /// Handle resolution request dari prediction market app
@@ -697,160 +1246,1459 @@ impl OracleCoordinatorContract {
             None => return,
         };
 
+        let requester = ResolutionRequester { chain_id: requester, market_id };
+        self.state.add_requester(market_id, requester).await;
+
         // Jika sudah resolved, kirim hasil
-        if market.status == MarketStatus::Resolved {
-            if let Some(outcome) = market.winning_outcome {
-                let timestamp = self.runtime.system_time();
-                self.runtime.send_message(
-                    requester,
-                    Message::MarketResolved {
-                        market_id,
-                        outcome,
-                        confidence: market.confidence_score.unwrap_or(0),
-                        timestamp,
-                    },
-                );
-            }
-        }
+        self.send_resolution(requester, &market);
     }
     
     /// Handle resolution request from Market-chain - WASM SAFE
     async fn handle_market_resolution_request(
         &mut self,
-        market_id: u64,
+        origin_market_id: u64,
         question: String,
         outcomes: Vec<String>,
+        kind: MarketKind,
     ) {
-        // Check if market already exists
-        if self.state.get_market(market_id).await.is_some() {
-            // Market already exists, start voting if not started
+        let Some(origin_chain) = self.runtime.message_origin_chain_id() else {
+            return;
+        };
+        let requester = ResolutionRequester {
+            chain_id: origin_chain,
+            market_id: origin_market_id,
+        };
+
+        // Repeated request for a market we already track
+        if let Some(market_id) = self.state.get_market_for_origin(origin_chain, origin_market_id).await {
+            self.state.add_requester(market_id, requester).await;
+            if let Some(market) = self.state.get_market(market_id).await {
+                self.send_resolution(requester, &market);
+            }
             return;
         }
         
         // Create new market in Oracle Coordinator
+        let params = self.runtime.application_parameters();
         let current_time = self.runtime.system_time();
         let trading_deadline = current_time; // Trading already ended
-        // Voting deadline: use far future for now (voting starts immediately)
-        // Note: In production, calculate proper future timestamp
-        let voting_deadline = Timestamp::from(u64::MAX / 2); // Far future
+        let commit_deadline = current_time.saturating_add(params.commit_period);
+        let reveal_deadline = commit_deadline.saturating_add(params.reveal_period);
         
-        // Create market with same ID as market-chain
-        let _response = self.create_market(
+        let market_id = match self.create_market(
             question,
             outcomes,
             trading_deadline,
-            voting_deadline,
-            1, // min_voters = 1 for testing
+            commit_deadline,
+            reveal_deadline,
+            Self::MIN_VOTERS,
             "oracle".to_string(),
-        ).await;
+            None,
+            Some(kind),
+        ).await {
+            CoordinatorResponse::MarketCreated { market_id, .. } => market_id,
+            _ => return,
+        };
+
+        self.state.set_market_for_origin(origin_chain, origin_market_id, market_id).await;
+        self.state.add_requester(market_id, requester).await;
         
         // Automatically start voting
         let _ = self.start_voting(market_id).await;
     }
 
-    /// Simple hash function - WASM SAFE (no Vec allocation)
-    fn simple_hash(&self, data: &[u8]) -> [u8; 32] {
-        if data.is_empty() {
-            return [0u8; 32];
+    /// Send a settled market's outcome to one requester; no-op until it is final
+    fn send_resolution(&mut self, requester: ResolutionRequester, market: &Market) {
+        if !market.finalized {
+            return;
         }
+        let timestamp = self.runtime.system_time();
 
-        let mut result = [0u8; 32];
-        
-        // Store length in first 4 bytes
-        let len = data.len() as u64;
-        result[0] = (len & 0xFF) as u8;
-        result[1] = ((len >> 8) & 0xFF) as u8;
-        result[2] = ((len >> 16) & 0xFF) as u8;
-        result[3] = ((len >> 24) & 0xFF) as u8;
-        
-        // Safety limit to prevent infinite loops
-        let max_len = data.len().min(Self::MAX_HASH_INPUT);
-        
-        // XOR all data into result
-        for i in 0..max_len {
-            let pos = i % 32;
-            result[pos] ^= data[i];
+        if market.is_invalid() {
+            self.runtime.send_message(
+                requester.chain_id,
+                Message::MarketInvalid {
+                    market_id: requester.market_id,
+                    confidence: market.confidence_score.unwrap_or(0),
+                    timestamp,
+                },
+            );
+            return;
         }
-        
-        // Simple diffusion
-        for i in 0..32 {
-            let a = result[i];
-            let b = result[(i + 7) % 32];
-            result[i] = a.wrapping_add(b);
+        if !market.is_resolved() {
+            return;
         }
-        
-        result
+        if let Some(value) = market.winning_value {
+            self.runtime.send_message(
+                requester.chain_id,
+                Message::ScalarMarketResolved {
+                    market_id: requester.market_id,
+                    value: value.0,
+                    confidence: market.confidence_score.unwrap_or(0),
+                    timestamp,
+                },
+            );
+            return;
+        }
+        if let Some(ballot) = &market.winning_ballot {
+            self.runtime.send_message(
+                requester.chain_id,
+                Message::BallotResolved {
+                    market_id: requester.market_id,
+                    ballot: ballot.clone(),
+                    confidence: market.confidence_score.unwrap_or(0),
+                    timestamp,
+                },
+            );
+            return;
+        }
+        let Some(outcome) = market.winning_outcome else {
+            return;
+        };
+
+        self.runtime.send_message(
+            requester.chain_id,
+            Message::MarketResolved {
+                market_id: requester.market_id,
+                outcome,
+                confidence: market.confidence_score.unwrap_or(0),
+                timestamp,
+            },
+        );
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use linera_sdk::util::BlockingWait;
+    use alethea_oracle_types::commitment;
+    use linera_sdk::{
+        linera_base_types::{AccountOwner, ApplicationId, CryptoHash},
+        util::BlockingWait,
+    };
 
     #[test]
-    fn test_hash_consistency() {
-        let coordinator = create_test_coordinator();
-        
-        let data = b"test data";
-        let hash1 = coordinator.simple_hash(data);
-        let hash2 = coordinator.simple_hash(data);
-        
-        assert_eq!(hash1, hash2, "Hash harus deterministik");
+    fn test_reveal_matching_commitment_is_recorded() {
+        let mut coordinator = create_test_coordinator();
+        let voter = ChainId(CryptoHash::from([1u8; 32]));
+        let salt = [7u8; 32];
+        let market_id = open_market_with_commitment(&mut coordinator, voter, 1, &salt);
+
+        close_commits(&mut coordinator, market_id);
+        coordinator.handle_reveal(voter, market_id, 1, salt, 90).blocking_wait();
+
+        let market = coordinator.state.get_market(market_id).blocking_wait().unwrap();
+        assert_eq!(market.total_reveals, 1);
+        assert_eq!(market.status, MarketStatus::RevealPhase);
     }
 
     #[test]
-    fn test_hash_large_data() {
-        let coordinator = create_test_coordinator();
-        
-        let large_data = vec![0u8; 100_000];
-        let hash = coordinator.simple_hash(&large_data);
-        
-        assert_eq!(hash.len(), 32);
+    fn test_reveal_with_different_outcome_is_slashed() {
+        let mut coordinator = create_test_coordinator();
+        let voter = ChainId(CryptoHash::from([1u8; 32]));
+        let salt = [7u8; 32];
+        let market_id = open_market_with_commitment(&mut coordinator, voter, 1, &salt);
+
+        // Reveal outcome 0 against a commitment to outcome 1
+        close_commits(&mut coordinator, market_id);
+        coordinator.handle_reveal(voter, market_id, 0, salt, 90).blocking_wait();
+
+        let market = coordinator.state.get_market(market_id).blocking_wait().unwrap();
+        assert_eq!(market.total_reveals, 0);
+        let info = coordinator.state.get_voter_info(voter).blocking_wait().unwrap();
+        assert_eq!(info.total_stake, Amount::from_tokens(100));
     }
 
     #[test]
-    fn test_aggregate_weighted_majority() {
-        let coordinator = create_test_coordinator();
-        
-        let reveals = vec![
-            alethea_oracle_types::VoteReveal {
-                voter_chain: ChainId::root(0),
-                market_id: 1,
-                outcome_index: 0,
-                salt: [0u8; 32],
-                confidence: 95,
-                voting_power: 150,
-                revealed_at: Timestamp::from(0),
-            },
-            alethea_oracle_types::VoteReveal {
-                voter_chain: ChainId::root(1),
-                market_id: 1,
-                outcome_index: 0,
-                salt: [1u8; 32],
-                confidence: 90,
-                voting_power: 120,
-                revealed_at: Timestamp::from(0),
-            },
-            alethea_oracle_types::VoteReveal {
-                voter_chain: ChainId::root(2),
-                market_id: 1,
-                outcome_index: 1,
-                salt: [2u8; 32],
-                confidence: 80,
-                voting_power: 100,
-                revealed_at: Timestamp::from(0),
-            },
+    fn test_resent_votes_count_once() {
+        let mut coordinator = create_test_coordinator();
+        let voter = ChainId(CryptoHash::from([1u8; 32]));
+        let salt = [7u8; 32];
+        let market_id = open_market_with_commitment(&mut coordinator, voter, 1, &salt);
+
+        // The same commitment again neither counts nor locks more stake
+        let hash = commitment::compute_commitment(market_id, voter, 1, 90, &salt);
+        coordinator.handle_commitment(voter, market_id, hash).blocking_wait();
+        let market = coordinator.state.get_market(market_id).blocking_wait().unwrap();
+        assert_eq!(market.total_commitments, 1);
+        let info = coordinator.state.get_voter_info(voter).blocking_wait().unwrap();
+        assert_eq!(info.locked_stake, Amount::from_tokens(100));
+
+        close_commits(&mut coordinator, market_id);
+        for _ in 0..3 {
+            coordinator.handle_reveal(voter, market_id, 1, salt, 90).blocking_wait();
+        }
+        let mut market = coordinator.state.get_market(market_id).blocking_wait().unwrap();
+        assert_eq!(market.total_reveals, 1);
+
+        // Quorum is counted in voters, not in the running counter
+        market.total_reveals = market.min_voters;
+        coordinator.state.update_market(market_id, market).blocking_wait();
+        let response = coordinator.aggregate_votes(market_id).blocking_wait();
+        assert_error(response, CoordinatorError::NotEnoughReveals);
+        close_reveals(&mut coordinator, market_id);
+        let response = coordinator.aggregate_votes(market_id).blocking_wait();
+        assert!(matches!(response, CoordinatorResponse::MarketInvalid { total_voters: 1, .. }));
+    }
+
+    #[test]
+    fn test_reveal_with_raised_confidence_is_slashed() {
+        let mut coordinator = create_test_coordinator();
+        let voter = ChainId(CryptoHash::from([1u8; 32]));
+        let salt = [7u8; 32];
+        let market_id = open_market_with_commitment(&mut coordinator, voter, 1, &salt);
+
+        // Committed with confidence 90, revealed with 100
+        close_commits(&mut coordinator, market_id);
+        coordinator.handle_reveal(voter, market_id, 1, salt, 100).blocking_wait();
+
+        let market = coordinator.state.get_market(market_id).blocking_wait().unwrap();
+        assert_eq!(market.total_reveals, 0);
+        let info = coordinator.state.get_voter_info(voter).blocking_wait().unwrap();
+        assert_eq!(info.total_stake, Amount::from_tokens(100));
+    }
+
+    #[test]
+    fn test_voting_power_recomputed_from_voter_info() {
+        let mut coordinator = create_test_coordinator();
+        let voter = ChainId(CryptoHash::from([1u8; 32]));
+        let market_id = open_market(&mut coordinator);
+        coordinator.register_voter(voter, Amount::from_tokens(400)).blocking_wait();
+
+        // The power claimed in the message is ignored
+        let hash = commitment::compute_commitment(market_id, voter, 0, 90, &[7u8; 32]);
+        deliver_from(&mut coordinator, voter);
+        coordinator
+            .execute_message(Message::VoteCommitment {
+                voter_chain: voter,
+                market_id,
+                commitment_hash: hash,
+                voting_power: u64::MAX,
+                stake_amount: Amount::from_tokens(100),
+            })
+            .blocking_wait();
+
+        let stored = coordinator.state.get_commitment(market_id, voter).blocking_wait().unwrap();
+        // Reputation 100, no accuracy bonus, sqrt(400) = 20
+        assert_eq!(stored.voting_power, 2_000);
+    }
+
+    #[test]
+    fn test_registration_escrows_stake() {
+        let mut coordinator = create_test_coordinator();
+        let voter = test_chain(1);
+
+        coordinator.register_voter(voter, Amount::from_tokens(200)).blocking_wait();
+        let response = coordinator.deposit_stake(voter, Amount::from_tokens(50)).blocking_wait();
+        assert!(matches!(
+            response,
+            CoordinatorResponse::StakeDeposited { total_stake, .. } if total_stake == Amount::from_tokens(250)
+        ));
+
+        assert_eq!(coordinator.runtime.owner_balance(staker()), Amount::from_tokens(9_750));
+        assert_eq!(escrow_balance(&mut coordinator), Amount::from_tokens(250));
+        assert_eq!(*coordinator.state.total_escrowed.get(), Amount::from_tokens(250));
+    }
+
+    #[test]
+    fn test_registration_requires_funded_signer() {
+        let mut coordinator = create_test_coordinator();
+
+        let response = coordinator.register_voter(test_chain(1), Amount::from_tokens(20_000)).blocking_wait();
+        assert_error(response, CoordinatorError::InsufficientBalance);
+
+        coordinator.runtime.set_authenticated_signer(None);
+        let response = coordinator.register_voter(test_chain(1), Amount::from_tokens(200)).blocking_wait();
+        assert_error(response, CoordinatorError::Unauthenticated);
+
+        assert!(coordinator.state.get_voter_info(test_chain(1)).blocking_wait().is_none());
+        assert_eq!(escrow_balance(&mut coordinator), Amount::ZERO);
+    }
+
+    #[test]
+    fn test_slashed_stake_goes_to_treasury() {
+        let mut coordinator = create_test_coordinator();
+        let voter = test_chain(1);
+        let salt = [7u8; 32];
+        let market_id = open_market_with_commitment(&mut coordinator, voter, 1, &salt);
+
+        // Reveal that does not open the commitment forfeits the locked stake
+        close_commits(&mut coordinator, market_id);
+        coordinator.handle_reveal(voter, market_id, 0, salt, 90).blocking_wait();
+
+        let info = coordinator.state.get_voter_info(voter).blocking_wait().unwrap();
+        let treasury = *coordinator.state.treasury.get();
+        assert_eq!(treasury, Amount::from_tokens(100));
+        // Bookkeeping still adds up to what the escrow account holds
+        assert_eq!(info.total_stake.saturating_add(treasury), escrow_balance(&mut coordinator));
+
+        // The voter chain hears about it, so its own stake record keeps up
+        let sent = coordinator.runtime.created_send_message_requests();
+        let slashes: Vec<_> = sent
+            .iter()
+            .filter_map(|request| match request.message {
+                Message::StakeSlashed { amount } => Some((request.destination, amount)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(slashes, vec![(voter, Amount::from_tokens(100))]);
+    }
+
+    #[test]
+    fn test_bad_reveal_is_slashed_once() {
+        let mut coordinator = create_test_coordinator();
+        let market_id = open_market(&mut coordinator);
+        let (cheat, honest) = (test_chain(1), [test_chain(2), test_chain(3), test_chain(4)]);
+        commit_vote(&mut coordinator, cheat, market_id, 1, &[7u8; 32]);
+        for voter in honest {
+            commit_vote(&mut coordinator, voter, market_id, 0, &[0u8; 32]);
+        }
+
+        close_commits(&mut coordinator, market_id);
+        coordinator.handle_reveal(cheat, market_id, 0, [7u8; 32], 90).blocking_wait();
+        for voter in honest {
+            coordinator.handle_reveal(voter, market_id, 0, [0u8; 32], 90).blocking_wait();
+        }
+        close_reveals(&mut coordinator, market_id);
+        coordinator.aggregate_votes(market_id).blocking_wait();
+        finalize(&mut coordinator, market_id);
+
+        let info = coordinator.state.get_voter_info(cheat).blocking_wait().unwrap();
+        assert_eq!(info.total_stake, Amount::from_tokens(100));
+        assert_eq!(info.locked_stake, Amount::ZERO);
+        assert_eq!(info.reputation_score, alethea_oracle_types::INITIAL_REPUTATION - 10);
+        let sent = coordinator.runtime.created_send_message_requests();
+        let slashes = sent
+            .iter()
+            .filter(|request| request.destination == cheat && matches!(request.message, Message::StakeSlashed { .. }))
+            .count();
+        assert_eq!(slashes, 1);
+    }
+
+    #[test]
+    fn test_commitment_needs_free_stake() {
+        let mut coordinator = create_test_coordinator();
+        let market_id = open_market(&mut coordinator);
+        let voter = test_chain(1);
+        coordinator.register_voter(voter, Amount::from_tokens(150)).blocking_wait();
+
+        let hash = commitment::compute_commitment(market_id, voter, 0, 90, &[1u8; 32]);
+        coordinator.handle_commitment(voter, market_id, hash).blocking_wait();
+        let info = coordinator.state.get_voter_info(voter).blocking_wait().unwrap();
+        assert_eq!(info.locked_stake, Amount::from_tokens(100));
+
+        // Each commitment locks the minimum stake, so 150 backs only one open vote at a time
+        let second_market = open_market(&mut coordinator);
+        let hash = commitment::compute_commitment(second_market, voter, 0, 90, &[1u8; 32]);
+        coordinator.handle_commitment(voter, second_market, hash).blocking_wait();
+        assert!(coordinator.state.get_commitment(second_market, voter).blocking_wait().is_none());
+
+        coordinator.deposit_stake(voter, Amount::from_tokens(50)).blocking_wait();
+        coordinator.handle_commitment(voter, second_market, hash).blocking_wait();
+        let info = coordinator.state.get_voter_info(voter).blocking_wait().unwrap();
+        assert_eq!(info.locked_stake, Amount::from_tokens(200));
+    }
+
+    #[test]
+    fn test_stake_deposit_credited_once_funds_arrive() {
+        let mut coordinator = create_test_coordinator();
+        let voter = test_chain(1);
+        coordinator.register_voter(voter, Amount::from_tokens(200)).blocking_wait();
+        let deposit = Message::StakeDeposit { voter_chain: voter, amount: Amount::from_tokens(30) };
+
+        // Tokens not in the signer's account yet: nothing is credited
+        coordinator.runtime.set_owner_balance(staker(), Amount::from_tokens(10));
+        deliver_from(&mut coordinator, voter);
+        coordinator.execute_message(deposit.clone()).blocking_wait();
+        let info = coordinator.state.get_voter_info(voter).blocking_wait().unwrap();
+        assert_eq!(info.total_stake, Amount::from_tokens(200));
+
+        // Tokens sitting unaccounted in escrow are not the signer's to claim
+        let escrow = AccountOwner::from(application_id());
+        coordinator.runtime.set_owner_balance(escrow, Amount::from_tokens(260));
+        deliver_from(&mut coordinator, voter);
+        coordinator.execute_message(deposit.clone()).blocking_wait();
+        let info = coordinator.state.get_voter_info(voter).blocking_wait().unwrap();
+        assert_eq!(info.total_stake, Amount::from_tokens(200));
+
+        // Cross-chain transfer landed in the signer's account
+        coordinator.runtime.set_owner_balance(staker(), Amount::from_tokens(40));
+        deliver_from(&mut coordinator, voter);
+        coordinator.execute_message(deposit).blocking_wait();
+        let info = coordinator.state.get_voter_info(voter).blocking_wait().unwrap();
+        assert_eq!(info.total_stake, Amount::from_tokens(230));
+        assert_eq!(coordinator.runtime.owner_balance(staker()), Amount::from_tokens(10));
+        assert_eq!(escrow_balance(&mut coordinator), Amount::from_tokens(290));
+        assert_eq!(*coordinator.state.total_escrowed.get(), Amount::from_tokens(230));
+    }
+
+    #[test]
+    fn test_unstake_waits_out_unbonding_period() {
+        let mut coordinator = create_test_coordinator();
+        let voter = test_chain(1);
+        let market_id = open_market(&mut coordinator);
+        commit_vote(&mut coordinator, voter, market_id, 0, &[1u8; 32]);
+        let unstake = |amount| Message::UnstakeRequest { voter_chain: voter, amount: Amount::from_tokens(amount) };
+
+        // 100 of the 200 tokens back an open commitment
+        deliver_from(&mut coordinator, voter);
+        coordinator.execute_message(unstake(150)).blocking_wait();
+        let info = coordinator.state.get_voter_info(voter).blocking_wait().unwrap();
+        assert_eq!(info.unbonding_stake, Amount::ZERO);
+        {
+            let sent = coordinator.runtime.created_send_message_requests();
+            let rejected = sent.last().unwrap();
+            assert_eq!(rejected.destination, voter);
+            assert!(matches!(
+                rejected.message,
+                Message::UnstakeRejected { amount } if amount == Amount::from_tokens(150)
+            ));
+        }
+
+        deliver_from(&mut coordinator, voter);
+        coordinator.execute_message(unstake(100)).blocking_wait();
+        let info = coordinator.state.get_voter_info(voter).blocking_wait().unwrap();
+        assert_eq!(info.total_stake, Amount::from_tokens(100));
+        assert_eq!(info.unbonding_stake, Amount::from_tokens(100));
+
+        // Too early: nothing is paid out
+        let withdraw = Message::UnstakeWithdrawal { voter_chain: voter };
+        deliver_from(&mut coordinator, voter);
+        coordinator.execute_message(withdraw.clone()).blocking_wait();
+        assert!(coordinator.runtime.outgoing_transfers().is_empty());
+
+        let release_at = Timestamp::from(0).saturating_add(Parameters::default().unbonding_period);
+        coordinator.runtime.set_system_time(release_at);
+        deliver_from(&mut coordinator, voter);
+        coordinator.execute_message(withdraw).blocking_wait();
+
+        let destination = Account { chain_id: voter, owner: staker() };
+        assert_eq!(
+            coordinator.runtime.outgoing_transfers().get(&destination),
+            Some(&Amount::from_tokens(100))
+        );
+        let info = coordinator.state.get_voter_info(voter).blocking_wait().unwrap();
+        assert_eq!(info.unbonding_stake, Amount::ZERO);
+        assert!(coordinator.state.get_unbonding(voter).blocking_wait().is_empty());
+        let escrowed = *coordinator.state.total_escrowed.get();
+        assert_eq!(escrowed, escrow_balance(&mut coordinator));
+    }
+
+    #[test]
+    fn test_unbonding_stake_can_be_slashed() {
+        let mut coordinator = create_test_coordinator();
+        let voter = test_chain(1);
+        coordinator.register_voter(voter, Amount::from_tokens(200)).blocking_wait();
+
+        deliver_from(&mut coordinator, voter);
+        coordinator
+            .execute_message(Message::UnstakeRequest { voter_chain: voter, amount: Amount::from_tokens(150) })
+            .blocking_wait();
+
+        let slashed = coordinator.state.slash_voter(voter, Amount::from_tokens(80)).blocking_wait();
+        assert_eq!(slashed, Amount::from_tokens(80));
+
+        let info = coordinator.state.get_voter_info(voter).blocking_wait().unwrap();
+        assert_eq!(info.total_stake, Amount::ZERO);
+        assert_eq!(info.unbonding_stake, Amount::from_tokens(120));
+        let queue = coordinator.state.get_unbonding(voter).blocking_wait();
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue[0].amount, Amount::from_tokens(120));
+    }
+
+    #[test]
+    fn test_withheld_reveal_is_slashed() {
+        let mut coordinator = create_test_coordinator();
+        let market_id = open_market(&mut coordinator);
+        let votes = [(test_chain(1), 0), (test_chain(2), 0), (test_chain(3), 1), (test_chain(4), 1)];
+
+        for (voter, outcome) in votes {
+            commit_vote(&mut coordinator, voter, market_id, outcome, &[outcome as u8; 32]);
+        }
+        // Voter 4 sees the majority and keeps its reveal back
+        close_commits(&mut coordinator, market_id);
+        for (voter, outcome) in &votes[..3] {
+            coordinator.handle_reveal(*voter, market_id, *outcome, [*outcome as u8; 32], 90).blocking_wait();
+        }
+
+        close_reveals(&mut coordinator, market_id);
+        coordinator.aggregate_votes(market_id).blocking_wait();
+        finalize(&mut coordinator, market_id);
+
+        let withheld = coordinator.state.get_voter_info(test_chain(4)).blocking_wait().unwrap();
+        assert_eq!(withheld.total_stake, Amount::from_tokens(180)); // 20% of the 100 committed
+        assert_eq!(withheld.locked_stake, Amount::ZERO);
+        assert_eq!(withheld.reputation_score, INITIAL_REPUTATION - 10);
+        assert_eq!(withheld.total_votes, 0);
+
+        // Wrong but revealed: the lighter slash, and the rest is released too
+        let wrong = coordinator.state.get_voter_info(test_chain(3)).blocking_wait().unwrap();
+        assert_eq!(wrong.total_stake, Amount::from_tokens(190));
+        assert_eq!(wrong.locked_stake, Amount::ZERO);
+        assert_eq!(*coordinator.state.treasury.get(), Amount::from_tokens(30));
+    }
+
+    #[test]
+    fn test_aggregate_votes_tallies_indexed_reveals() {
+        let mut coordinator = create_test_coordinator();
+        let market_id = open_market(&mut coordinator);
+        let votes = [(test_chain(1), 0), (test_chain(2), 0), (test_chain(3), 1)];
+
+        for (voter, outcome) in votes {
+            commit_vote(&mut coordinator, voter, market_id, outcome, &[outcome as u8; 32]);
+        }
+        close_commits(&mut coordinator, market_id);
+        for (voter, outcome) in votes {
+            coordinator.handle_reveal(voter, market_id, outcome, [outcome as u8; 32], 90).blocking_wait();
+        }
+
+        assert_eq!(coordinator.state.get_all_commitments(market_id).blocking_wait().len(), 3);
+        assert_eq!(coordinator.state.get_all_reveals(market_id).blocking_wait().len(), 3);
+
+        close_reveals(&mut coordinator, market_id);
+        let response = coordinator.aggregate_votes(market_id).blocking_wait();
+        assert!(matches!(
+            response,
+            CoordinatorResponse::MarketResolved { winning_outcome: 0, total_voters: 3, .. }
+        ));
+    }
+
+    #[test]
+    fn test_active_markets_exclude_resolved() {
+        let mut coordinator = create_test_coordinator();
+        let resolved_id = open_market(&mut coordinator);
+        let open_id = open_market(&mut coordinator);
+
+        let mut market = coordinator.state.get_market(resolved_id).blocking_wait().unwrap();
+        market.status = MarketStatus::Resolved;
+        coordinator.state.update_market(resolved_id, market).blocking_wait();
+
+        let CoordinatorResponse::ActiveMarkets(markets) = coordinator.get_active_markets().blocking_wait() else {
+            panic!("Expected ActiveMarkets");
+        };
+        let ids: Vec<u64> = markets.iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec![open_id]);
+    }
+
+    #[test]
+    fn test_resolution_routed_to_requesting_chain() {
+        let mut coordinator = create_test_coordinator();
+        let market_chain = test_chain(9);
+
+        coordinator.runtime.set_message_origin_chain_id(market_chain);
+        let request = || Message::ResolutionRequest {
+            market_id: 42,
+            question: "Test?".to_string(),
+            outcomes: vec!["Yes".to_string(), "No".to_string()],
+        };
+        coordinator.execute_message(request()).blocking_wait();
+        // A repeated request must not open a second market
+        coordinator.execute_message(request()).blocking_wait();
+        assert_eq!(*coordinator.state.next_market_id.get(), 1);
+
+        let market_id = coordinator.state.get_market_for_origin(market_chain, 42).blocking_wait().unwrap();
+        for byte in 1..=3 {
+            commit_vote(&mut coordinator, test_chain(byte), market_id, 1, &[byte; 32]);
+        }
+        close_commits(&mut coordinator, market_id);
+        for byte in 1..=3 {
+            coordinator.handle_reveal(test_chain(byte), market_id, 1, [byte; 32], 90).blocking_wait();
+        }
+
+        close_reveals(&mut coordinator, market_id);
+        coordinator.aggregate_votes(market_id).blocking_wait();
+        finalize(&mut coordinator, market_id);
+
+        let sent = coordinator.runtime.created_send_message_requests();
+        let resolutions: Vec<_> = sent
+            .iter()
+            .filter_map(|request| match request.message {
+                Message::MarketResolved { market_id, outcome, .. } => {
+                    Some((request.destination, market_id, outcome))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(resolutions, vec![(market_chain, 42, 1)]);
+    }
+
+    #[test]
+    fn test_resolution_fee_funds_reward_pool() {
+        let mut coordinator = create_test_coordinator();
+        let market_chain = test_chain(9);
+        let votes = [(test_chain(1), 0), (test_chain(2), 0), (test_chain(3), 1)];
+
+        coordinator.runtime.set_message_origin_chain_id(market_chain);
+        coordinator
+            .execute_message(Message::ResolutionRequest {
+                market_id: 42,
+                question: "Test?".to_string(),
+                outcomes: vec!["Yes".to_string(), "No".to_string()],
+            })
+            .blocking_wait();
+        let market_id = coordinator.state.get_market_for_origin(market_chain, 42).blocking_wait().unwrap();
+        for (voter, outcome) in votes {
+            commit_vote(&mut coordinator, voter, market_id, outcome, &[outcome as u8; 32]);
+        }
+
+        // Fee tokens not on this chain yet: nothing is pulled and the pool stays empty
+        let fee = Message::ResolutionFee { market_id: 42, amount: Amount::from_tokens(100) };
+        deliver_from(&mut coordinator, market_chain);
+        coordinator.runtime.set_owner_balance(staker(), Amount::from_tokens(40));
+        coordinator.execute_message(fee.clone()).blocking_wait();
+        assert_eq!(coordinator.state.get_reward_pool(market_id).blocking_wait(), Amount::ZERO);
+        assert_eq!(coordinator.runtime.owner_balance(staker()), Amount::from_tokens(40));
+
+        // A fee for a market this chain never heard of stays with the payer
+        coordinator.runtime.set_owner_balance(staker(), Amount::from_tokens(140));
+        coordinator
+            .execute_message(Message::ResolutionFee { market_id: 43, amount: Amount::from_tokens(100) })
+            .blocking_wait();
+        assert_eq!(coordinator.runtime.owner_balance(staker()), Amount::from_tokens(140));
+
+        // Escrow tokens nobody accounted for are not mistaken for the fee
+        let escrow = AccountOwner::from(application_id());
+        let surplus = escrow_balance(&mut coordinator).saturating_add(Amount::from_tokens(500));
+        coordinator.runtime.set_owner_balance(escrow, surplus);
+
+        coordinator.execute_message(fee).blocking_wait();
+        assert_eq!(coordinator.runtime.owner_balance(staker()), Amount::from_tokens(40));
+        let response = coordinator
+            .execute_operation(CoordinatorOperation::GetRewardPool { market_id })
+            .blocking_wait();
+        assert!(matches!(
+            response,
+            CoordinatorResponse::RewardPool { balance, .. } if balance == Amount::from_tokens(100)
+        ));
+
+        close_commits(&mut coordinator, market_id);
+        for (voter, outcome) in votes {
+            coordinator.handle_reveal(voter, market_id, outcome, [outcome as u8; 32], 90).blocking_wait();
+        }
+        close_reveals(&mut coordinator, market_id);
+        coordinator.aggregate_votes(market_id).blocking_wait();
+        finalize(&mut coordinator, market_id);
+
+        // 80% to the two correct voters with equal power, 20% plus a 10% slash to the treasury
+        assert_eq!(coordinator.state.get_reward_pool(market_id).blocking_wait(), Amount::ZERO);
+        let mut total_stake = Amount::ZERO;
+        for (voter, outcome) in votes {
+            let info = coordinator.state.get_voter_info(voter).blocking_wait().unwrap();
+            let expected = if outcome == 0 { 240 } else { 190 };
+            assert_eq!(info.total_stake, Amount::from_tokens(expected));
+            total_stake = total_stake.saturating_add(info.total_stake);
+        }
+        let treasury = *coordinator.state.treasury.get();
+        assert_eq!(treasury, Amount::from_tokens(30));
+        // The stray 500 is still sitting in escrow, claimed by no one
+        let escrowed = total_stake.saturating_add(treasury);
+        assert_eq!(escrowed, *coordinator.state.total_escrowed.get());
+        assert_eq!(escrowed.saturating_add(Amount::from_tokens(500)), escrow_balance(&mut coordinator));
+    }
+
+    #[test]
+    fn test_settlement_waits_for_challenge_window() {
+        let mut coordinator = create_test_coordinator();
+        let market_id = resolve_market(&mut coordinator, &[(test_chain(1), 0), (test_chain(2), 0), (test_chain(3), 1)]);
+
+        // Resolved, but nothing is paid or slashed while it can still be disputed
+        let wrong = coordinator.state.get_voter_info(test_chain(3)).blocking_wait().unwrap();
+        assert_eq!((wrong.total_stake, wrong.locked_stake), (Amount::from_tokens(200), Amount::from_tokens(100)));
+        assert_error(coordinator.finalize_resolution(market_id).blocking_wait(), CoordinatorError::ChallengeWindowOpen);
+
+        finalize(&mut coordinator, market_id);
+        let wrong = coordinator.state.get_voter_info(test_chain(3)).blocking_wait().unwrap();
+        assert_eq!((wrong.total_stake, wrong.locked_stake), (Amount::from_tokens(190), Amount::ZERO));
+        assert_error(coordinator.finalize_resolution(market_id).blocking_wait(), CoordinatorError::AlreadyFinalized);
+        assert_error(
+            coordinator.dispute_resolution(market_id, Amount::from_tokens(500)).blocking_wait(),
+            CoordinatorError::ChallengeWindowClosed,
+        );
+    }
+
+    #[test]
+    fn test_overturned_dispute_refunds_bond() {
+        let mut coordinator = create_test_coordinator();
+        let market_id = resolve_market(&mut coordinator, &[(test_chain(1), 0), (test_chain(2), 0), (test_chain(3), 0)]);
+
+        assert_error(
+            coordinator.dispute_resolution(market_id, Amount::from_tokens(100)).blocking_wait(),
+            CoordinatorError::DisputeBondTooLow,
+        );
+        let balance = coordinator.runtime.owner_balance(staker());
+        let response = coordinator.dispute_resolution(market_id, Amount::from_tokens(500)).blocking_wait();
+        assert!(matches!(response, CoordinatorResponse::DisputeOpened { min_voters: 6, .. }));
+        assert_eq!(coordinator.runtime.owner_balance(staker()), balance.saturating_sub(Amount::from_tokens(500)));
+        assert_error(
+            coordinator.dispute_resolution(market_id, Amount::from_tokens(500)).blocking_wait(),
+            CoordinatorError::AlreadyDisputed,
+        );
+
+        let market = coordinator.state.get_market(market_id).blocking_wait().unwrap();
+        assert_eq!((market.status, market.winning_outcome), (MarketStatus::CommitPhase, None));
+        assert!(coordinator.state.get_all_commitments(market_id).blocking_wait().is_empty());
+
+        let response = run_appeal(&mut coordinator, market_id, 1);
+        assert!(matches!(
+            response,
+            CoordinatorResponse::MarketResolved { winning_outcome: 1, total_voters: 6, .. }
+        ));
+
+        // Appeal outcomes are final straight away; the first round was wrong after all
+        let market = coordinator.state.get_market(market_id).blocking_wait().unwrap();
+        assert!(market.finalized);
+        let registrations = Amount::from_tokens(6 * 400 + 150);
+        assert_eq!(coordinator.runtime.owner_balance(staker()), balance.saturating_sub(registrations));
+        let first_round = coordinator.state.get_voter_info(test_chain(1)).blocking_wait().unwrap();
+        assert_eq!((first_round.total_stake, first_round.locked_stake), (Amount::from_tokens(190), Amount::ZERO));
+        assert_escrow_balanced(&mut coordinator);
+    }
+
+    #[test]
+    fn test_appeal_without_quorum_ends_invalid() {
+        let mut coordinator = create_test_coordinator();
+        let market_id = resolve_market(&mut coordinator, &[(test_chain(1), 0), (test_chain(2), 0), (test_chain(3), 0)]);
+        let balance = coordinator.runtime.owner_balance(staker());
+        coordinator.dispute_resolution(market_id, Amount::from_tokens(500)).blocking_wait();
+
+        // Two of the six appeal voters commit and only one reveals
+        for byte in 4..=5 {
+            let voter = test_chain(byte);
+            coordinator.register_voter(voter, Amount::from_tokens(400)).blocking_wait();
+            let hash = commitment::compute_commitment(market_id, voter, 1, 90, &[7u8; 32]);
+            coordinator.handle_commitment(voter, market_id, hash).blocking_wait();
+        }
+        close_commits(&mut coordinator, market_id);
+        coordinator.handle_reveal(test_chain(4), market_id, 1, [7u8; 32], 90).blocking_wait();
+        assert_error(coordinator.aggregate_votes(market_id).blocking_wait(), CoordinatorError::NotEnoughReveals);
+
+        close_reveals(&mut coordinator, market_id);
+        let response = coordinator.aggregate_votes(market_id).blocking_wait();
+        assert!(matches!(response, CoordinatorResponse::MarketInvalid { confidence: 0, total_voters: 1, .. }));
+        let market = coordinator.state.get_market(market_id).blocking_wait().unwrap();
+        assert_eq!(market.status, MarketStatus::Invalid);
+        assert!(market.finalized);
+
+        // The bond is refunded and no stake stays locked in either round
+        let registrations = Amount::from_tokens(2 * 400);
+        assert_eq!(coordinator.runtime.owner_balance(staker()), balance.saturating_sub(registrations));
+        for voter in (1..=5).map(test_chain) {
+            let info = coordinator.state.get_voter_info(voter).blocking_wait().unwrap();
+            assert_eq!(info.locked_stake, Amount::ZERO);
+        }
+        let withheld = coordinator.state.get_voter_info(test_chain(5)).blocking_wait().unwrap();
+        assert!(withheld.total_stake < Amount::from_tokens(400));
+        assert_escrow_balanced(&mut coordinator);
+    }
+
+    #[test]
+    fn test_failed_dispute_forfeits_bond() {
+        let mut coordinator = create_test_coordinator();
+        let market_id = resolve_market(&mut coordinator, &[(test_chain(1), 0), (test_chain(2), 0), (test_chain(3), 0)]);
+        coordinator.dispute_resolution(market_id, Amount::from_tokens(500)).blocking_wait();
+
+        let response = run_appeal(&mut coordinator, market_id, 0);
+        assert!(matches!(response, CoordinatorResponse::MarketResolved { winning_outcome: 0, .. }));
+
+        // 80% of the bond to the six appeal voters, the rest to the treasury
+        let appeal_voter = coordinator.state.get_voter_info(test_chain(4)).blocking_wait().unwrap();
+        assert_eq!(appeal_voter.locked_stake, Amount::ZERO);
+        assert!(appeal_voter.total_stake > Amount::from_tokens(466));
+        assert_eq!(*coordinator.state.treasury.get(), Amount::from_tokens(100));
+        let first_round = coordinator.state.get_voter_info(test_chain(1)).blocking_wait().unwrap();
+        assert_eq!((first_round.total_stake, first_round.locked_stake), (Amount::from_tokens(200), Amount::ZERO));
+        assert_escrow_balanced(&mut coordinator);
+    }
+
+    #[test]
+    fn test_market_uses_its_aggregation_method() {
+        let mut coordinator = create_test_coordinator();
+        // One voter with sixteen times the stake of the other two
+        coordinator.register_voter(test_chain(3), Amount::from_tokens(3200)).blocking_wait();
+        let votes = [(test_chain(1), 0), (test_chain(2), 0), (test_chain(3), 1)];
+
+        let by_power = open_market(&mut coordinator);
+        let by_head = open_market_with(&mut coordinator, Some(AggregationMethod::SimpleMajority));
+        let market = coordinator.state.get_market(by_power).blocking_wait().unwrap();
+        assert_eq!(market.aggregation_method, Parameters::default().aggregation_method);
+
+        for market_id in [by_power, by_head] {
+            for (voter, outcome) in votes {
+                commit_vote(&mut coordinator, voter, market_id, outcome, &[outcome as u8; 32]);
+            }
+        }
+        close_commits(&mut coordinator, by_power);
+        for market_id in [by_power, by_head] {
+            for (voter, outcome) in votes {
+                coordinator.handle_reveal(voter, market_id, outcome, [outcome as u8; 32], 90).blocking_wait();
+            }
+        }
+        close_reveals(&mut coordinator, by_power);
+
+        let winner = |response| match response {
+            CoordinatorResponse::MarketResolved { winning_outcome, .. } => winning_outcome,
+            other => panic!("Expected MarketResolved, got {:?}", other),
+        };
+        assert_eq!(winner(coordinator.aggregate_votes(by_power).blocking_wait()), 1);
+        assert_eq!(winner(coordinator.aggregate_votes(by_head).blocking_wait()), 0);
+    }
+
+    #[test]
+    fn test_split_vote_leaves_market_invalid() {
+        let mut coordinator = create_test_coordinator();
+        let market_chain = test_chain(9);
+        let votes = [(test_chain(1), 0), (test_chain(2), 0), (test_chain(3), 1), (test_chain(4), 1)];
+
+        coordinator.runtime.set_message_origin_chain_id(market_chain);
+        coordinator
+            .execute_message(Message::ResolutionRequest {
+                market_id: 42,
+                question: "Test?".to_string(),
+                outcomes: vec!["Yes".to_string(), "No".to_string()],
+            })
+            .blocking_wait();
+        let market_id = coordinator.state.get_market_for_origin(market_chain, 42).blocking_wait().unwrap();
+        for (voter, outcome) in votes {
+            commit_vote(&mut coordinator, voter, market_id, outcome, &[outcome as u8; 32]);
+        }
+        commit_vote(&mut coordinator, test_chain(5), market_id, 0, &[5u8; 32]);
+        close_commits(&mut coordinator, market_id);
+        for (voter, outcome) in votes {
+            coordinator.handle_reveal(voter, market_id, outcome, [outcome as u8; 32], 90).blocking_wait();
+        }
+        close_reveals(&mut coordinator, market_id);
+
+        // Half and half is well under the 66% threshold
+        let response = coordinator.aggregate_votes(market_id).blocking_wait();
+        assert!(matches!(
+            response,
+            CoordinatorResponse::MarketInvalid { confidence: 50, total_voters: 4, .. }
+        ));
+        let market = coordinator.state.get_market(market_id).blocking_wait().unwrap();
+        assert_eq!((market.status, market.winning_outcome), (MarketStatus::Invalid, None));
+        assert!(market.finalized);
+        assert!(!coordinator.state.get_active_markets().blocking_wait().iter().any(|m| m.id == market_id));
+        assert_error(coordinator.finalize_resolution(market_id).blocking_wait(), CoordinatorError::AlreadyFinalized);
+        assert_error(
+            coordinator.dispute_resolution(market_id, Amount::from_tokens(500)).blocking_wait(),
+            CoordinatorError::MarketNotResolved,
+        );
+
+        // Nobody was wrong, so revealed stake comes back whole; withholding still costs
+        for (voter, _) in votes {
+            let info = coordinator.state.get_voter_info(voter).blocking_wait().unwrap();
+            assert_eq!((info.total_stake, info.locked_stake), (Amount::from_tokens(200), Amount::ZERO));
+            assert_eq!(info.reputation_score, INITIAL_REPUTATION);
+        }
+        let withheld = coordinator.state.get_voter_info(test_chain(5)).blocking_wait().unwrap();
+        assert_eq!((withheld.total_stake, withheld.locked_stake), (Amount::from_tokens(180), Amount::ZERO));
+        assert_escrow_balanced(&mut coordinator);
+
+        let sent = coordinator.runtime.created_send_message_requests();
+        let invalid: Vec<_> = sent
+            .iter()
+            .filter_map(|request| match request.message {
+                Message::MarketInvalid { market_id, confidence, .. } => {
+                    Some((request.destination, market_id, confidence))
+                }
+                Message::MarketResolved { .. } => panic!("Invalid market announced as resolved"),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(invalid, vec![(market_chain, 42, 50)]);
+    }
+
+    #[test]
+    fn test_scalar_market_resolves_to_median() {
+        let mut coordinator = create_test_coordinator();
+        let market_chain = test_chain(9);
+        // Answers within 100 of each other agree; the fourth is far off
+        let votes = [(test_chain(1), 5_000), (test_chain(2), 5_050), (test_chain(3), 5_020), (test_chain(4), 9_000)];
+
+        coordinator.runtime.set_message_origin_chain_id(market_chain);
+        coordinator
+            .execute_message(Message::ScalarResolutionRequest {
+                market_id: 7,
+                question: "Rainfall in mm?".to_string(),
+                min: 0,
+                max: 10_000,
+                decimals: 0,
+            })
+            .blocking_wait();
+        let market_id = coordinator.state.get_market_for_origin(market_chain, 7).blocking_wait().unwrap();
+        let market = coordinator.state.get_market(market_id).blocking_wait().unwrap();
+        assert_eq!(market.aggregation_method, AggregationMethod::Median);
+
+        for (voter, value) in votes {
+            coordinator.register_voter(voter, Amount::from_tokens(200)).blocking_wait();
+            let hash = commitment::compute_scalar_commitment(market_id, voter, value, 90, &[1u8; 32]);
+            coordinator.handle_commitment(voter, market_id, hash).blocking_wait();
+        }
+        close_commits(&mut coordinator, market_id);
+        // A categorical reveal does not count on a scalar market
+        coordinator.handle_reveal(test_chain(1), market_id, 0, [1u8; 32], 90).blocking_wait();
+        assert!(coordinator.state.get_all_reveals(market_id).blocking_wait().is_empty());
+        for (voter, value) in votes {
+            coordinator.handle_scalar_reveal(voter, market_id, value, [1u8; 32], 90).blocking_wait();
+        }
+        close_reveals(&mut coordinator, market_id);
+
+        let response = coordinator.aggregate_votes(market_id).blocking_wait();
+        assert!(
+            matches!(
+                response,
+                CoordinatorResponse::ScalarMarketResolved { value: ScalarValue(5_020), confidence: 75, total_voters: 4, .. }
+            ),
+            "{:?}",
+            response
+        );
+        finalize(&mut coordinator, market_id);
+
+        for (voter, _) in &votes[..3] {
+            let info = coordinator.state.get_voter_info(*voter).blocking_wait().unwrap();
+            assert!(info.reputation_score > INITIAL_REPUTATION);
+        }
+        let outlier = coordinator.state.get_voter_info(test_chain(4)).blocking_wait().unwrap();
+        assert!(outlier.reputation_score < INITIAL_REPUTATION);
+        assert!(outlier.total_stake < Amount::from_tokens(200));
+        assert_escrow_balanced(&mut coordinator);
+
+        let sent = coordinator.runtime.created_send_message_requests();
+        let resolved: Vec<_> = sent
+            .iter()
+            .filter_map(|request| match request.message {
+                Message::ScalarMarketResolved { market_id, value, confidence, .. } => {
+                    Some((request.destination, market_id, value, confidence))
+                }
+                Message::MarketResolved { .. } => panic!("Scalar market announced an outcome"),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(resolved, vec![(market_chain, 7, 5_020, 75)]);
+    }
+
+    #[test]
+    fn test_multi_select_market_resolves_by_approval() {
+        let mut coordinator = create_test_coordinator();
+        let market_id = match coordinator.create_market(
+            "Which teams make the playoffs?".to_string(),
+            vec!["Lions".to_string(), "Bears".to_string(), "Wolves".to_string()],
+            Timestamp::from(0),
+            Timestamp::from(1000),
+            Timestamp::from(2000),
+            3,
+            "sports".to_string(),
+            None,
+            Some(MarketKind::MultiSelect),
+        ).blocking_wait() {
+            CoordinatorResponse::MarketCreated { market_id, .. } => market_id,
+            other => panic!("Expected MarketCreated, got {:?}", other),
+        };
+        let market = coordinator.state.get_market(market_id).blocking_wait().unwrap();
+        assert_eq!(market.aggregation_method, AggregationMethod::Approval);
+
+        let votes = [
+            (test_chain(1), Ballot::Approval(0b011)),
+            (test_chain(2), Ballot::Approval(0b011)),
+            (test_chain(3), Ballot::Approval(0b111)),
+            // Not a multi-select answer, so never counted as revealed
+            (test_chain(4), Ballot::Ranking(vec![0, 1, 2])),
         ];
+        for (voter, _) in &votes {
+            coordinator.register_voter(*voter, Amount::from_tokens(200)).blocking_wait();
+        }
+        coordinator.start_voting(market_id).blocking_wait();
+        let requested = coordinator
+            .runtime
+            .created_send_message_requests()
+            .iter()
+            .filter(|request| matches!(request.message, Message::BallotVotingRequest { kind: MarketKind::MultiSelect, .. }))
+            .count();
+        assert_eq!(requested, votes.len());
 
-        let (winner, confidence) = coordinator.aggregate_weighted_majority(reveals, 2);
-        
-        assert_eq!(winner, 0, "Outcome 0 harus menang");
-        assert!(confidence > 60, "Confidence harus > 60%");
+        for (voter, ballot) in &votes {
+            let hash = commitment::compute_ballot_commitment(market_id, *voter, ballot, 90, &[5u8; 32]);
+            coordinator.handle_commitment(*voter, market_id, hash).blocking_wait();
+        }
+        close_commits(&mut coordinator, market_id);
+        // A categorical reveal does not count on a ballot market
+        coordinator.handle_reveal(test_chain(1), market_id, 0, [5u8; 32], 90).blocking_wait();
+        assert!(coordinator.state.get_all_reveals(market_id).blocking_wait().is_empty());
+        for (voter, ballot) in &votes {
+            coordinator.handle_ballot_reveal(*voter, market_id, ballot.clone(), [5u8; 32], 90).blocking_wait();
+        }
+        assert_eq!(coordinator.state.get_all_reveals(market_id).blocking_wait().len(), 3);
+        close_reveals(&mut coordinator, market_id);
+
+        // Wolves got a third of the weight, so two thirds decided it
+        let response = coordinator.aggregate_votes(market_id).blocking_wait();
+        assert!(
+            matches!(
+                &response,
+                CoordinatorResponse::BallotMarketResolved { ballot: Ballot::Approval(0b011), confidence: 66, total_voters: 3, .. }
+            ),
+            "{:?}",
+            response
+        );
+        finalize(&mut coordinator, market_id);
+
+        for voter in [test_chain(1), test_chain(2)] {
+            let info = coordinator.state.get_voter_info(voter).blocking_wait().unwrap();
+            assert!(info.reputation_score > INITIAL_REPUTATION);
+        }
+        let overreached = coordinator.state.get_voter_info(test_chain(3)).blocking_wait().unwrap();
+        assert!(overreached.reputation_score < INITIAL_REPUTATION);
+        let withheld = coordinator.state.get_voter_info(test_chain(4)).blocking_wait().unwrap();
+        assert!(withheld.total_stake < Amount::from_tokens(200));
+        assert_escrow_balanced(&mut coordinator);
+    }
+
+    #[test]
+    fn test_ballot_resolution_request_is_answered() {
+        let mut coordinator = create_test_coordinator();
+        let market_chain = test_chain(9);
+        let request = |kind| Message::BallotResolutionRequest {
+            market_id: 7,
+            question: "Finishing order?".to_string(),
+            outcomes: vec!["A".to_string(), "B".to_string(), "C".to_string()],
+            kind,
+        };
+
+        // Only ballot kinds come this way
+        coordinator.runtime.set_message_origin_chain_id(market_chain);
+        coordinator.execute_message(request(MarketKind::Categorical)).blocking_wait();
+        assert!(coordinator.state.get_market_for_origin(market_chain, 7).blocking_wait().is_none());
+
+        coordinator.execute_message(request(MarketKind::Ranked)).blocking_wait();
+        let market_id = coordinator.state.get_market_for_origin(market_chain, 7).blocking_wait().unwrap();
+        let voters = [test_chain(1), test_chain(2), test_chain(3)];
+        let ranking = Ballot::Ranking(vec![1, 0, 2]);
+        for voter in voters {
+            coordinator.register_voter(voter, Amount::from_tokens(200)).blocking_wait();
+            let hash = commitment::compute_ballot_commitment(market_id, voter, &ranking, 90, &[5u8; 32]);
+            coordinator.handle_commitment(voter, market_id, hash).blocking_wait();
+        }
+        close_commits(&mut coordinator, market_id);
+        for voter in voters {
+            coordinator.handle_ballot_reveal(voter, market_id, ranking.clone(), [5u8; 32], 90).blocking_wait();
+        }
+        close_reveals(&mut coordinator, market_id);
+        coordinator.aggregate_votes(market_id).blocking_wait();
+        finalize(&mut coordinator, market_id);
+
+        let sent = coordinator.runtime.created_send_message_requests();
+        let resolved: Vec<_> = sent
+            .iter()
+            .filter_map(|request| match &request.message {
+                Message::BallotResolved { market_id, ballot, .. } => Some((request.destination, *market_id, ballot.clone())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(resolved, vec![(market_chain, 7, ranking)]);
+    }
+
+    #[test]
+    fn test_create_market_errors() {
+        let mut coordinator = create_test_coordinator();
+        let create = |coordinator: &mut OracleCoordinatorContract, outcomes: usize, commit: u64, reveal: u64, min_voters: u32| {
+            coordinator.create_market(
+                "Test?".to_string(),
+                (0..outcomes).map(|i| i.to_string()).collect(),
+                Timestamp::from(100),
+                Timestamp::from(commit),
+                Timestamp::from(reveal),
+                min_voters,
+                "test".to_string(),
+                None,
+                None,
+            ).blocking_wait()
+        };
+
+        assert_error(create(&mut coordinator, 1, 1000, 2000, 3), CoordinatorError::InvalidOutcomes);
+        assert_error(create(&mut coordinator, 11, 1000, 2000, 3), CoordinatorError::InvalidOutcomes);
+        assert_error(create(&mut coordinator, 2, 100, 2000, 3), CoordinatorError::InvalidDeadlines);
+        assert_error(create(&mut coordinator, 2, 1000, 1000, 3), CoordinatorError::InvalidDeadlines);
+        assert_error(create(&mut coordinator, 2, 1000, 2000, 2), CoordinatorError::MinVotersTooLow);
+
+        let scalar = |coordinator: &mut OracleCoordinatorContract, outcomes: Vec<String>, max: i128, method: Option<AggregationMethod>| {
+            let kind = MarketKind::Scalar { min: ScalarValue(0), max: ScalarValue(max), decimals: 0 };
+            coordinator.create_market(
+                "How much?".to_string(),
+                outcomes,
+                Timestamp::from(100),
+                Timestamp::from(1000),
+                Timestamp::from(2000),
+                3,
+                "test".to_string(),
+                method,
+                Some(kind),
+            ).blocking_wait()
+        };
+        let yes_no = vec!["Yes".to_string(), "No".to_string()];
+        assert_error(scalar(&mut coordinator, yes_no.clone(), 100, None), CoordinatorError::InvalidScalarRange);
+        assert_error(scalar(&mut coordinator, vec![], 0, None), CoordinatorError::InvalidScalarRange);
+        assert_error(
+            scalar(&mut coordinator, vec![], 100, Some(AggregationMethod::SimpleMajority)),
+            CoordinatorError::AggregationMismatch,
+        );
+        let median = coordinator.create_market(
+            "Test?".to_string(),
+            yes_no,
+            Timestamp::from(100),
+            Timestamp::from(1000),
+            Timestamp::from(2000),
+            3,
+            "test".to_string(),
+            Some(AggregationMethod::Median),
+            None,
+        ).blocking_wait();
+        assert_error(median, CoordinatorError::AggregationMismatch);
+
+        let ballot = |coordinator: &mut OracleCoordinatorContract, outcomes: usize, kind: MarketKind, method: Option<AggregationMethod>| {
+            coordinator.create_market(
+                "Which ones?".to_string(),
+                (0..outcomes).map(|i| i.to_string()).collect(),
+                Timestamp::from(100),
+                Timestamp::from(1000),
+                Timestamp::from(2000),
+                3,
+                "test".to_string(),
+                method,
+                Some(kind),
+            ).blocking_wait()
+        };
+        assert_error(ballot(&mut coordinator, 11, MarketKind::Ranked, None), CoordinatorError::InvalidOutcomes);
+        assert_error(
+            ballot(&mut coordinator, 3, MarketKind::Ranked, Some(AggregationMethod::Approval)),
+            CoordinatorError::AggregationMismatch,
+        );
+        assert_error(
+            ballot(&mut coordinator, 3, MarketKind::MultiSelect, Some(AggregationMethod::SimpleMajority)),
+            CoordinatorError::AggregationMismatch,
+        );
+        assert!(matches!(
+            ballot(&mut coordinator, 3, MarketKind::Ranked, None),
+            CoordinatorResponse::MarketCreated { .. }
+        ));
+    }
+
+    #[test]
+    fn test_voter_errors() {
+        let mut coordinator = create_test_coordinator();
+        let voter = test_chain(1);
+
+        let response = coordinator.register_voter(voter, Amount::from_tokens(99)).blocking_wait();
+        assert_error(response, CoordinatorError::InsufficientStake);
+
+        coordinator.register_voter(voter, Amount::from_tokens(100)).blocking_wait();
+        let response = coordinator.register_voter(voter, Amount::from_tokens(100)).blocking_wait();
+        assert_error(response, CoordinatorError::VoterAlreadyRegistered);
+
+        let response = coordinator.get_voter_info(test_chain(2)).blocking_wait();
+        assert_error(response, CoordinatorError::VoterNotFound);
+    }
+
+    #[test]
+    fn test_market_lifecycle_errors() {
+        let mut coordinator = create_test_coordinator();
+
+        assert_error(coordinator.start_voting(99).blocking_wait(), CoordinatorError::MarketNotFound);
+        assert_error(coordinator.aggregate_votes(99).blocking_wait(), CoordinatorError::MarketNotFound);
+        assert_error(coordinator.get_market(99).blocking_wait(), CoordinatorError::MarketNotFound);
+        assert_error(coordinator.get_market_stats(99).blocking_wait(), CoordinatorError::MarketNotFound);
+
+        let CoordinatorResponse::MarketCreated { market_id: trading, .. } = coordinator.create_market(
+            "Test?".to_string(),
+            vec!["Yes".to_string(), "No".to_string()],
+            Timestamp::from(500),
+            Timestamp::from(1000),
+            Timestamp::from(2000),
+            3,
+            "test".to_string(),
+            None,
+            None,
+        ).blocking_wait() else {
+            panic!("Expected MarketCreated");
+        };
+        assert_error(coordinator.start_voting(trading).blocking_wait(), CoordinatorError::TradingPeriodNotEnded);
+
+        let market_id = open_market(&mut coordinator);
+        assert_error(coordinator.start_voting(market_id).blocking_wait(), CoordinatorError::MarketNotActive);
+        assert_error(coordinator.aggregate_votes(market_id).blocking_wait(), CoordinatorError::NotInRevealPhase);
+
+        for byte in 1..=3 {
+            commit_vote(&mut coordinator, test_chain(byte), market_id, 0, &[byte; 32]);
+        }
+        close_commits(&mut coordinator, market_id);
+        coordinator.handle_reveal(test_chain(1), market_id, 0, [1; 32], 90).blocking_wait();
+        assert_error(coordinator.aggregate_votes(market_id).blocking_wait(), CoordinatorError::NotEnoughReveals);
+
+        for byte in 2..=3 {
+            coordinator.handle_reveal(test_chain(byte), market_id, 0, [byte; 32], 90).blocking_wait();
+        }
+        assert_error(coordinator.aggregate_votes(market_id).blocking_wait(), CoordinatorError::VotingDeadlineNotReached);
+    }
+
+    #[test]
+    fn test_phases_follow_deadlines() {
+        let mut coordinator = create_test_coordinator();
+        let market_id = open_market(&mut coordinator);
+        let (early, late) = (test_chain(1), test_chain(2));
+        commit_vote(&mut coordinator, early, market_id, 0, &[1u8; 32]);
+
+        // A reveal while commits are open is ignored and does not end the commit phase
+        coordinator.handle_reveal(early, market_id, 0, [1u8; 32], 90).blocking_wait();
+        let market = coordinator.state.get_market(market_id).blocking_wait().unwrap();
+        assert_eq!((market.status, market.total_reveals), (MarketStatus::CommitPhase, 0));
+
+        // Commit window closed: late commitments are rejected
+        close_commits(&mut coordinator, market_id);
+        commit_vote(&mut coordinator, late, market_id, 0, &[2u8; 32]);
+        assert!(coordinator.state.get_commitment(market_id, late).blocking_wait().is_none());
+        let info = coordinator.state.get_voter_info(late).blocking_wait().unwrap();
+        assert_eq!(info.locked_stake, Amount::ZERO);
+
+        coordinator.handle_reveal(early, market_id, 0, [1u8; 32], 90).blocking_wait();
+        let market = coordinator.state.get_market(market_id).blocking_wait().unwrap();
+        assert_eq!((market.status, market.total_reveals), (MarketStatus::RevealPhase, 1));
+
+        // Reveal window closed as well
+        close_reveals(&mut coordinator, market_id);
+        coordinator.handle_direct_vote(late, market_id, 0, 90).blocking_wait();
+        let market = coordinator.state.get_market(market_id).blocking_wait().unwrap();
+        assert_eq!((market.status, market.total_reveals), (MarketStatus::Aggregating, 1));
+    }
+
+    fn assert_error(response: CoordinatorResponse, expected: CoordinatorError) {
+        match response {
+            CoordinatorResponse::Error(error) => assert_eq!(error, expected),
+            other => panic!("Expected {:?}, got {:?}", expected, other),
+        }
+    }
+
+    #[test]
+    fn test_vote_messages_require_authenticated_origin() {
+        let mut coordinator = create_test_coordinator();
+        let voter = test_chain(1);
+        let market_id = open_market(&mut coordinator);
+        coordinator.register_voter(voter, Amount::from_tokens(200)).blocking_wait();
+        let vote = || Message::DirectVote {
+            voter_chain: voter,
+            market_id,
+            outcome_index: 0,
+            confidence: 90,
+            voting_power: 100,
+        };
+        let votes = |coordinator: &OracleCoordinatorContract| {
+            coordinator.state.get_all_reveals(market_id).blocking_wait().len()
+        };
+
+        // Another chain claiming to be the voter
+        deliver_from(&mut coordinator, test_chain(2));
+        coordinator.execute_message(vote()).blocking_wait();
+        assert_eq!(votes(&coordinator), 0);
+
+        // Right origin, but not signed
+        deliver_from(&mut coordinator, voter);
+        coordinator.runtime.set_authenticated_signer(None);
+        coordinator.execute_message(vote()).blocking_wait();
+        assert_eq!(votes(&coordinator), 0);
+
+        // Right origin and signed, but bounced back
+        deliver_from(&mut coordinator, voter);
+        coordinator.runtime.set_message_is_bouncing(Some(true));
+        coordinator.execute_message(vote()).blocking_wait();
+        assert_eq!(votes(&coordinator), 0);
+
+        deliver_from(&mut coordinator, voter);
+        coordinator.execute_message(vote()).blocking_wait();
+        assert_eq!(votes(&coordinator), 1);
+    }
+
+    #[test]
+    fn test_direct_vote_cannot_replace_commit_reveal() {
+        let mut coordinator = create_test_coordinator();
+        let (committed, direct) = (test_chain(1), test_chain(2));
+        let market_id = open_market_with_commitment(&mut coordinator, committed, 0, &[1u8; 32]);
+        coordinator.register_voter(direct, Amount::from_tokens(200)).blocking_wait();
+
+        // A committed voter cannot skip the reveal with a direct vote
+        coordinator.handle_direct_vote(committed, market_id, 1, 90).blocking_wait();
+        assert!(coordinator.state.get_reveal(market_id, committed).blocking_wait().is_none());
+
+        // A second direct vote neither overwrites the first nor counts twice
+        coordinator.handle_direct_vote(direct, market_id, 0, 90).blocking_wait();
+        coordinator.handle_direct_vote(direct, market_id, 1, 90).blocking_wait();
+        let reveal = coordinator.state.get_reveal(market_id, direct).blocking_wait().unwrap();
+        assert_eq!(reveal.outcome_index, 0);
+        let market = coordinator.state.get_market(market_id).blocking_wait().unwrap();
+        assert_eq!(market.total_reveals, 1);
+
+        // Once commits close, direct votes are over
+        let late = test_chain(3);
+        coordinator.register_voter(late, Amount::from_tokens(200)).blocking_wait();
+        close_commits(&mut coordinator, market_id);
+        coordinator.handle_direct_vote(late, market_id, 0, 90).blocking_wait();
+        assert!(coordinator.state.get_reveal(market_id, late).blocking_wait().is_none());
+
+        // The committed voter still reveals normally
+        coordinator.handle_reveal(committed, market_id, 0, [1u8; 32], 90).blocking_wait();
+        let reveal = coordinator.state.get_reveal(market_id, committed).blocking_wait().unwrap();
+        assert_eq!(reveal.outcome_index, 0);
+    }
+
+    /// Makes the next message look like an authenticated, non-bouncing message from `origin`
+    fn deliver_from(coordinator: &mut OracleCoordinatorContract, origin: ChainId) {
+        coordinator.runtime.set_message_origin_chain_id(origin);
+        coordinator.runtime.set_authenticated_signer(staker());
+        coordinator.runtime.set_message_is_bouncing(Some(false));
+    }
+
+    fn test_chain(byte: u8) -> ChainId {
+        ChainId(CryptoHash::from([byte; 32]))
+    }
+
+    /// Creates a binary market and opens it for voting
+    fn open_market(coordinator: &mut OracleCoordinatorContract) -> u64 {
+        open_market_with(coordinator, None)
+    }
+
+    /// Like [`open_market`], tallied with `aggregation_method`
+    fn open_market_with(coordinator: &mut OracleCoordinatorContract, aggregation_method: Option<AggregationMethod>) -> u64 {
+        let market_id = match coordinator.create_market(
+            "Test?".to_string(),
+            vec!["Yes".to_string(), "No".to_string()],
+            Timestamp::from(0),
+            Timestamp::from(1000),
+            Timestamp::from(2000),
+            3,
+            "test".to_string(),
+            aggregation_method,
+            None,
+        ).blocking_wait() {
+            CoordinatorResponse::MarketCreated { market_id, .. } => market_id,
+            other => panic!("Expected MarketCreated, got {:?}", other),
+        };
+        coordinator.start_voting(market_id).blocking_wait();
+        market_id
+    }
+
+    /// Moves the clock to `market_id`'s commit deadline, opening the reveal window
+    fn close_commits(coordinator: &mut OracleCoordinatorContract, market_id: u64) {
+        let market = coordinator.state.get_market(market_id).blocking_wait().unwrap();
+        coordinator.runtime.set_system_time(market.commit_deadline);
+    }
+
+    /// Moves the clock to `market_id`'s reveal deadline, so it can be aggregated
+    fn close_reveals(coordinator: &mut OracleCoordinatorContract, market_id: u64) {
+        let market = coordinator.state.get_market(market_id).blocking_wait().unwrap();
+        coordinator.runtime.set_system_time(market.reveal_deadline);
+    }
+
+    /// Moves the clock past `market_id`'s challenge window and settles it
+    fn finalize(coordinator: &mut OracleCoordinatorContract, market_id: u64) {
+        let market = coordinator.state.get_market(market_id).blocking_wait().unwrap();
+        coordinator.runtime.set_system_time(market.challenge_deadline.unwrap());
+        let response = coordinator.finalize_resolution(market_id).blocking_wait();
+        assert!(matches!(response, CoordinatorResponse::ResolutionFinalized { .. }), "{:?}", response);
+    }
+
+    /// Opens a market, collects `votes` through commit and reveal and aggregates it
+    fn resolve_market(coordinator: &mut OracleCoordinatorContract, votes: &[(ChainId, usize)]) -> u64 {
+        let market_id = open_market(coordinator);
+        for (voter, outcome) in votes {
+            commit_vote(coordinator, *voter, market_id, *outcome, &[*outcome as u8; 32]);
+        }
+        close_commits(coordinator, market_id);
+        for (voter, outcome) in votes {
+            coordinator.handle_reveal(*voter, market_id, *outcome, [*outcome as u8; 32], 90).blocking_wait();
+        }
+        close_reveals(coordinator, market_id);
+        coordinator.aggregate_votes(market_id).blocking_wait();
+        market_id
+    }
+
+    /// Six new voters commit to `outcome_index` in the appeal round, locking 200 tokens each, then it is aggregated
+    fn run_appeal(coordinator: &mut OracleCoordinatorContract, market_id: u64, outcome_index: usize) -> CoordinatorResponse {
+        let voters: Vec<ChainId> = (4..=10).map(test_chain).collect();
+        for (stake, voter) in voters.iter().enumerate().map(|(i, voter)| (if i < 6 { 400 } else { 150 }, *voter)) {
+            coordinator.register_voter(voter, Amount::from_tokens(stake)).blocking_wait();
+            let hash = commitment::compute_commitment(market_id, voter, outcome_index, 90, &[7u8; 32]);
+            coordinator.handle_commitment(voter, market_id, hash).blocking_wait();
+        }
+        let appeal_voter = coordinator.state.get_voter_info(voters[0]).blocking_wait().unwrap();
+        assert_eq!(appeal_voter.locked_stake, Amount::from_tokens(200));
+        // The seventh has less than the appeal stake
+        assert!(coordinator.state.get_commitment(market_id, voters[6]).blocking_wait().is_none());
+
+        close_commits(coordinator, market_id);
+        for voter in &voters[..6] {
+            coordinator.handle_reveal(*voter, market_id, outcome_index, [7u8; 32], 90).blocking_wait();
+        }
+        close_reveals(coordinator, market_id);
+        coordinator.aggregate_votes(market_id).blocking_wait()
+    }
+
+    /// Stake plus treasury accounts for every token in the escrow account
+    fn assert_escrow_balanced(coordinator: &mut OracleCoordinatorContract) {
+        let mut total = *coordinator.state.treasury.get();
+        for voter in coordinator.state.get_all_voters().blocking_wait() {
+            let info = coordinator.state.get_voter_info(voter).blocking_wait().unwrap();
+            total = total.saturating_add(info.total_stake);
+        }
+        assert_eq!(total, *coordinator.state.total_escrowed.get());
+        assert_eq!(total, escrow_balance(coordinator));
+    }
+
+    /// Registers `voter` and stores its commitment to `outcome_index` with confidence 90
+    fn commit_vote(
+        coordinator: &mut OracleCoordinatorContract,
+        voter: ChainId,
+        market_id: u64,
+        outcome_index: usize,
+        salt: &[u8; 32],
+    ) {
+        coordinator.register_voter(voter, Amount::from_tokens(200)).blocking_wait();
+        let hash = commitment::compute_commitment(market_id, voter, outcome_index, 90, salt);
+        coordinator.handle_commitment(voter, market_id, hash).blocking_wait();
+    }
+
+    /// Registers `voter`, opens a market for voting and stores a commitment to `outcome_index`
+    fn open_market_with_commitment(
+        coordinator: &mut OracleCoordinatorContract,
+        voter: ChainId,
+        outcome_index: usize,
+        salt: &[u8; 32],
+    ) -> u64 {
+        let market_id = open_market(coordinator);
+        commit_vote(coordinator, voter, market_id, outcome_index, salt);
+        market_id
+    }
+
+    /// Signer of test operations, funded with 10_000 tokens
+    fn staker() -> AccountOwner {
+        AccountOwner::from(CryptoHash::from([0xAAu8; 32]))
+    }
+
+    fn application_id() -> ApplicationId {
+        ApplicationId::new(CryptoHash::from([2u8; 32]))
+    }
+
+    fn escrow_balance(coordinator: &mut OracleCoordinatorContract) -> Amount {
+        coordinator.runtime.owner_balance(AccountOwner::from(application_id()))
     }
 
     fn create_test_coordinator() -> OracleCoordinatorContract {
-        let runtime = ContractRuntime::new();
+        let runtime = ContractRuntime::new()
+            .with_chain_id(ChainId(CryptoHash::from([0u8; 32])))
+            .with_system_time(Timestamp::from(0))
+            .with_authenticated_signer(staker())
+            .with_application_id(application_id().with_abi())
+            .with_owner_balances([
+                (staker(), Amount::from_tokens(10_000)),
+                (AccountOwner::from(application_id()), Amount::ZERO),
+            ])
+            .with_application_parameters(Parameters::default());
         let state = OracleCoordinatorState::load(runtime.root_view_storage_context())
             .blocking_wait()
             .expect("Failed to load state");
@@ -4,15 +4,17 @@
 #![cfg_attr(target_arch = "wasm32", no_main)]
 
 mod state;
-mod types;
 
 use async_graphql::{EmptySubscription, Object, Request, Response, Schema};
 use linera_sdk::{
     graphql::GraphQLMutationRoot,
-    linera_base_types::WithServiceAbi,
+    linera_base_types::{Amount, ChainId, WithServiceAbi},
+    views::View,
     Service, ServiceRuntime,
 };
-use alethea_oracle_types::{OracleCoordinatorAbi, CoordinatorOperation};
+use alethea_oracle_types::{
+    OracleCoordinatorAbi, CoordinatorOperation, Market, MarketStats, UnbondingEntry, VoterInfo,
+};
 use std::sync::Arc;
 
 use self::state::OracleCoordinatorState;
@@ -75,4 +77,44 @@ impl QueryRoot {
     async fn total_markets_resolved(&self) -> u64 {
         *self.state.total_markets_resolved.get()
     }
+
+    /// Tokens held in escrow for voters and the treasury
+    async fn total_escrowed(&self) -> Amount {
+        *self.state.total_escrowed.get()
+    }
+    
+    /// Slashed stake held by the treasury
+    async fn treasury(&self) -> Amount {
+        *self.state.treasury.get()
+    }
+
+    /// Get market by ID
+    async fn market(&self, market_id: u64) -> Option<Market> {
+        self.state.get_market(market_id).await
+    }
+
+    /// Get markets that are not resolved yet
+    async fn active_markets(&self) -> Vec<Market> {
+        self.state.get_active_markets().await
+    }
+
+    /// Get market participation statistics
+    async fn market_stats(&self, market_id: u64) -> Option<MarketStats> {
+        self.state.get_market(market_id).await.map(|market| market.stats())
+    }
+
+    /// Get registered voter info
+    async fn voter(&self, voter_chain: ChainId) -> Option<VoterInfo> {
+        self.state.get_voter_info(voter_chain).await
+    }
+
+    /// Undistributed fees in a market's reward pool
+    async fn reward_pool(&self, market_id: u64) -> Amount {
+        self.state.get_reward_pool(market_id).await
+    }
+
+    /// Stake a voter is waiting to withdraw, with release times
+    async fn unbonding(&self, voter_chain: ChainId) -> Vec<UnbondingEntry> {
+        self.state.get_unbonding(voter_chain).await
+    }
 }
@@ -2,12 +2,13 @@
 // SPDX-License-Identifier: MIT
 
 use linera_sdk::{
-    linera_base_types::{AccountOwner, ChainId, Amount, Timestamp},
-    views::{linera_views, MapView, RegisterView, RootView, View, ViewStorageContext},
+    linera_base_types::{ChainId, Amount, Timestamp},
+    views::{linera_views, MapView, RegisterView, RootView, SetView, ViewStorageContext},
 };
-use serde::{Deserialize, Serialize};
 
-use crate::types::{VoterInfo, Market, MarketStatus, VoteCommitment, VoteReveal};
+use alethea_oracle_types::{
+    VoterInfo, Market, ResolutionRequester, UnbondingEntry, VoteCommitment, VoteReveal,
+};
 
 /// The application state for Oracle Coordinator
 #[derive(RootView)]
@@ -20,8 +21,30 @@ pub struct OracleCoordinatorState {
     pub markets: MapView<u64, Market>,
     pub commitments: MapView<(u64, ChainId), VoteCommitment>,
     pub reveals: MapView<(u64, ChainId), VoteReveal>,
+    /// Voters that committed per market, so commitments can be listed without a full scan
+    pub market_committers: MapView<u64, Vec<ChainId>>,
+    /// Voters that revealed per market, so reveals can be listed without a full scan
+    pub market_revealers: MapView<u64, Vec<ChainId>>,
+    /// Ids of markets that are not yet resolved
+    pub active_market_ids: SetView<u64>,
+    /// Chains to notify when a market resolves
+    pub market_requesters: MapView<u64, Vec<ResolutionRequester>>,
+    /// Coordinator market id for each (origin chain, origin market id) resolution request
+    pub origin_markets: MapView<(ChainId, u64), u64>,
+    /// Tokens held in the application account on behalf of voters and the treasury
+    pub total_escrowed: RegisterView<Amount>,
+    /// Slashed stake, still held in the application account
+    pub treasury: RegisterView<Amount>,
+    /// Stake each voter asked to withdraw, oldest request first
+    pub unbonding: MapView<ChainId, Vec<UnbondingEntry>>,
+    /// Fees paid in for each market, held until the market resolves
+    pub reward_pools: MapView<u64, Amount>,
+    /// First-round commitments and reveals of disputed markets, settled once the appeal resolves
+    pub appealed_rounds: MapView<u64, (Vec<VoteCommitment>, Vec<VoteReveal>)>,
 }
 
+// Mutators are only called from the contract binary, not the service
+#[allow(dead_code)]
 impl OracleCoordinatorState {
     pub async fn initialize(&mut self) {
         self.next_market_id.set(0);
@@ -36,6 +59,7 @@ impl OracleCoordinatorState {
     }
     
     pub async fn add_market(&mut self, market_id: u64, market: Market) {
+        self.index_market_status(&market);
         let _ = self.markets.insert(&market_id, market);
         let current = *self.total_markets_created.get();
         self.total_markets_created.set(current.saturating_add(1));
@@ -46,9 +70,39 @@ impl OracleCoordinatorState {
     }
     
     pub async fn update_market(&mut self, market_id: u64, market: Market) {
+        self.index_market_status(&market);
         let _ = self.markets.insert(&market_id, market);
     }
     
+    /// Keep `active_market_ids` in sync with the market's status
+    fn index_market_status(&mut self, market: &Market) {
+        if market.is_resolved() || market.is_invalid() {
+            let _ = self.active_market_ids.remove(&market.id);
+        } else {
+            let _ = self.active_market_ids.insert(&market.id);
+        }
+    }
+    
+    pub async fn add_requester(&mut self, market_id: u64, requester: ResolutionRequester) {
+        if let Ok(requesters) = self.market_requesters.get_mut_or_default(&market_id).await {
+            if !requesters.contains(&requester) {
+                requesters.push(requester);
+            }
+        }
+    }
+    
+    pub async fn get_requesters(&self, market_id: u64) -> Vec<ResolutionRequester> {
+        self.market_requesters.get(&market_id).await.ok().flatten().unwrap_or_default()
+    }
+    
+    pub async fn get_market_for_origin(&self, origin_chain: ChainId, origin_market_id: u64) -> Option<u64> {
+        self.origin_markets.get(&(origin_chain, origin_market_id)).await.ok().flatten()
+    }
+    
+    pub async fn set_market_for_origin(&mut self, origin_chain: ChainId, origin_market_id: u64, market_id: u64) {
+        let _ = self.origin_markets.insert(&(origin_chain, origin_market_id), market_id);
+    }
+    
     pub async fn register_voter(&mut self, chain_id: ChainId, info: VoterInfo) {
         let _ = self.voters.insert(&chain_id, info);
     }
@@ -66,32 +120,166 @@ impl OracleCoordinatorState {
     }
     
     pub async fn add_commitment(&mut self, market_id: u64, voter: ChainId, commitment: VoteCommitment) {
-        let _ = self.commitments.insert(&(market_id, voter), commitment);
+        if self.commitments.insert(&(market_id, voter), commitment).is_ok() {
+            Self::index_voter(&mut self.market_committers, market_id, voter).await;
+        }
     }
     
     pub async fn get_commitment(&self, market_id: u64, voter: ChainId) -> Option<VoteCommitment> {
         self.commitments.get(&(market_id, voter)).await.ok().flatten()
     }
     
+    /// Drop a commitment that has been settled before the round ends
+    pub async fn remove_commitment(&mut self, market_id: u64, voter: ChainId) {
+        let _ = self.commitments.remove(&(market_id, voter));
+        if let Ok(Some(voters)) = self.market_committers.get_mut(&market_id).await {
+            voters.retain(|committer| *committer != voter);
+        }
+    }
+    
+    pub async fn get_all_commitments(&self, market_id: u64) -> Vec<VoteCommitment> {
+        let voters = self.market_committers.get(&market_id).await.ok().flatten().unwrap_or_default();
+        let mut commitments = Vec::new();
+        for voter in voters {
+            if let Some(commitment) = self.get_commitment(market_id, voter).await {
+                commitments.push(commitment);
+            }
+        }
+        commitments
+    }
+    
     pub async fn add_reveal(&mut self, market_id: u64, voter: ChainId, reveal: VoteReveal) {
-        let _ = self.reveals.insert(&(market_id, voter), reveal);
+        if self.reveals.insert(&(market_id, voter), reveal).is_ok() {
+            Self::index_voter(&mut self.market_revealers, market_id, voter).await;
+        }
+    }
+    
+    pub async fn get_reveal(&self, market_id: u64, voter: ChainId) -> Option<VoteReveal> {
+        self.reveals.get(&(market_id, voter)).await.ok().flatten()
+    }
+    
+    pub async fn get_all_reveals(&self, market_id: u64) -> Vec<VoteReveal> {
+        let voters = self.market_revealers.get(&market_id).await.ok().flatten().unwrap_or_default();
+        let mut reveals = Vec::new();
+        for voter in voters {
+            if let Some(reveal) = self.get_reveal(market_id, voter).await {
+                reveals.push(reveal);
+            }
+        }
+        reveals
+    }
+    
+    /// Move a market's commitments and reveals aside so an appeal round starts empty
+    pub async fn archive_round(&mut self, market_id: u64) {
+        let commitments = self.get_all_commitments(market_id).await;
+        let reveals = self.get_all_reveals(market_id).await;
+        for commitment in &commitments {
+            let _ = self.commitments.remove(&(market_id, commitment.voter_chain));
+        }
+        for reveal in &reveals {
+            let _ = self.reveals.remove(&(market_id, reveal.voter_chain));
+        }
+        let _ = self.market_committers.remove(&market_id);
+        let _ = self.market_revealers.remove(&market_id);
+        let _ = self.appealed_rounds.insert(&market_id, (commitments, reveals));
     }
     
-    pub async fn get_all_reveals(&self, _market_id: u64) -> Vec<VoteReveal> {
-        Vec::new()
+    /// Remove and return the first round archived for `market_id`, if any
+    pub async fn take_appealed_round(&mut self, market_id: u64) -> Option<(Vec<VoteCommitment>, Vec<VoteReveal>)> {
+        let round = self.appealed_rounds.get(&market_id).await.ok().flatten()?;
+        let _ = self.appealed_rounds.remove(&market_id);
+        Some(round)
+    }
+    
+    /// Record `voter` under `market_id` in a per-market index, once
+    async fn index_voter(index: &mut MapView<u64, Vec<ChainId>>, market_id: u64, voter: ChainId) {
+        if let Ok(voters) = index.get_mut_or_default(&market_id).await {
+            if !voters.contains(&voter) {
+                voters.push(voter);
+            }
+        }
     }
     
     pub async fn get_active_markets(&self) -> Vec<Market> {
-        Vec::new()
+        let ids = self.active_market_ids.indices().await.unwrap_or_default();
+        let mut markets = Vec::new();
+        for market_id in ids {
+            if let Some(market) = self.get_market(market_id).await {
+                markets.push(market);
+            }
+        }
+        markets
     }
     
-    pub async fn lock_voter_stake(&mut self, voter: ChainId, amount: Amount) {
-        if let Some(mut info) = self.get_voter_info(voter).await {
-            info.locked_stake = info.locked_stake.saturating_add(amount);
-            let _ = self.voters.insert(&voter, info);
+    /// Record `amount` of escrowed tokens as `voter`'s stake
+    pub async fn credit_voter_stake(&mut self, voter: ChainId, amount: Amount) -> Option<Amount> {
+        let mut info = self.get_voter_info(voter).await?;
+        info.total_stake = info.total_stake.saturating_add(amount);
+        let total_stake = info.total_stake;
+        let _ = self.voters.insert(&voter, info);
+        self.add_escrowed(amount);
+        Some(total_stake)
+    }
+    
+    pub fn add_escrowed(&mut self, amount: Amount) {
+        let total = self.total_escrowed.get().saturating_add(amount);
+        self.total_escrowed.set(total);
+    }
+    
+    /// Record `amount` as having left the escrow account
+    pub fn remove_escrowed(&mut self, amount: Amount) {
+        let total = self.total_escrowed.get().saturating_sub(amount);
+        self.total_escrowed.set(total);
+    }
+    
+    /// Pay already-escrowed tokens (a reward) into a voter's stake; false if not registered
+    pub async fn pay_reward(&mut self, voter: ChainId, amount: Amount) -> bool {
+        let Some(mut info) = self.get_voter_info(voter).await else {
+            return false;
+        };
+        info.total_stake = info.total_stake.saturating_add(amount);
+        let _ = self.voters.insert(&voter, info);
+        true
+    }
+    
+    pub fn add_to_treasury(&mut self, amount: Amount) {
+        let treasury = self.treasury.get().saturating_add(amount);
+        self.treasury.set(treasury);
+    }
+    
+    /// Add escrowed fee tokens to a market's reward pool
+    pub async fn add_to_reward_pool(&mut self, market_id: u64, amount: Amount) {
+        if let Ok(pool) = self.reward_pools.get_mut_or_default(&market_id).await {
+            *pool = pool.saturating_add(amount);
+            self.add_escrowed(amount);
         }
     }
     
+    pub async fn get_reward_pool(&self, market_id: u64) -> Amount {
+        self.reward_pools.get(&market_id).await.ok().flatten().unwrap_or_default()
+    }
+    
+    /// Empty a market's reward pool, returns what it held
+    pub async fn take_reward_pool(&mut self, market_id: u64) -> Amount {
+        let pool = self.get_reward_pool(market_id).await;
+        let _ = self.reward_pools.remove(&market_id);
+        pool
+    }
+    
+    /// Lock part of a voter's free stake; false if not registered or not enough is free
+    pub async fn lock_voter_stake(&mut self, voter: ChainId, amount: Amount) -> bool {
+        let Some(mut info) = self.get_voter_info(voter).await else {
+            return false;
+        };
+        let free = info.total_stake.saturating_sub(info.locked_stake);
+        if amount > free {
+            return false;
+        }
+        info.locked_stake = info.locked_stake.saturating_add(amount);
+        let _ = self.voters.insert(&voter, info);
+        true
+    }
+    
     pub async fn unlock_voter_stake(&mut self, voter: ChainId, amount: Amount) {
         if let Some(mut info) = self.get_voter_info(voter).await {
             info.locked_stake = info.locked_stake.saturating_sub(amount);
@@ -99,12 +287,83 @@ impl OracleCoordinatorState {
         }
     }
     
-    pub async fn slash_voter(&mut self, voter: ChainId, amount: Amount) {
-        if let Some(mut info) = self.get_voter_info(voter).await {
-            info.total_stake = info.total_stake.saturating_sub(amount);
-            info.locked_stake = info.locked_stake.saturating_sub(amount);
-            let _ = self.voters.insert(&voter, info);
+    /// Move free stake into the unbonding queue; false if not registered or not enough is free
+    pub async fn begin_unbonding(&mut self, voter: ChainId, amount: Amount, release_at: Timestamp) -> bool {
+        let Some(mut info) = self.get_voter_info(voter).await else {
+            return false;
+        };
+        let free = info.total_stake.saturating_sub(info.locked_stake);
+        if amount == Amount::ZERO || amount > free {
+            return false;
+        }
+        info.total_stake = info.total_stake.saturating_sub(amount);
+        info.unbonding_stake = info.unbonding_stake.saturating_add(amount);
+        let _ = self.voters.insert(&voter, info);
+        
+        if let Ok(entries) = self.unbonding.get_mut_or_default(&voter).await {
+            entries.push(UnbondingEntry { amount, release_at });
         }
+        true
+    }
+    
+    pub async fn get_unbonding(&self, voter: ChainId) -> Vec<UnbondingEntry> {
+        self.unbonding.get(&voter).await.ok().flatten().unwrap_or_default()
+    }
+    
+    /// Drop entries released by `now` from the queue and escrow, returns their total
+    pub async fn release_unbonded(&mut self, voter: ChainId, now: Timestamp) -> Amount {
+        let Some(mut info) = self.get_voter_info(voter).await else {
+            return Amount::ZERO;
+        };
+        let (released, pending): (Vec<_>, Vec<_>) = self
+            .get_unbonding(voter)
+            .await
+            .into_iter()
+            .partition(|entry| entry.release_at <= now);
+        let amount = released
+            .iter()
+            .fold(Amount::ZERO, |total, entry| total.saturating_add(entry.amount));
+        if amount == Amount::ZERO {
+            return amount;
+        }
+        
+        info.unbonding_stake = info.unbonding_stake.saturating_sub(amount);
+        let _ = self.voters.insert(&voter, info);
+        let _ = self.unbonding.insert(&voter, pending);
+        
+        self.remove_escrowed(amount);
+        amount
+    }
+    
+    /// Move up to `amount` of a voter's stake to the treasury, returns what was taken
+    ///
+    /// Active stake is taken first, then unbonding stake, newest request first.
+    pub async fn slash_voter(&mut self, voter: ChainId, amount: Amount) -> Amount {
+        let Some(mut info) = self.get_voter_info(voter).await else {
+            return Amount::ZERO;
+        };
+        let from_stake = amount.min(info.total_stake);
+        info.total_stake = info.total_stake.saturating_sub(from_stake);
+        info.locked_stake = info.locked_stake.saturating_sub(from_stake);
+        
+        let mut remaining = amount.saturating_sub(from_stake);
+        if remaining > Amount::ZERO {
+            let mut entries = self.get_unbonding(voter).await;
+            for entry in entries.iter_mut().rev() {
+                let taken = remaining.min(entry.amount);
+                entry.amount = entry.amount.saturating_sub(taken);
+                remaining = remaining.saturating_sub(taken);
+            }
+            entries.retain(|entry| entry.amount > Amount::ZERO);
+            let _ = self.unbonding.insert(&voter, entries);
+        }
+        let from_unbonding = amount.saturating_sub(from_stake).saturating_sub(remaining);
+        info.unbonding_stake = info.unbonding_stake.saturating_sub(from_unbonding);
+        let _ = self.voters.insert(&voter, info);
+        
+        let slashed = from_stake.saturating_add(from_unbonding);
+        self.add_to_treasury(slashed);
+        slashed
     }
     
     pub async fn update_voter_reputation_correct(&mut self, voter: ChainId) {
@@ -118,6 +377,15 @@ impl OracleCoordinatorState {
         }
     }
     
+    /// Committed but never revealed: no vote is counted, but reputation and streak suffer
+    pub async fn update_voter_reputation_no_reveal(&mut self, voter: ChainId) {
+        if let Some(mut info) = self.get_voter_info(voter).await {
+            info.correct_streak = 0;
+            info.reputation_score = info.reputation_score.saturating_sub(10);
+            let _ = self.voters.insert(&voter, info);
+        }
+    }
+    
     pub async fn update_voter_reputation_incorrect(&mut self, voter: ChainId) {
         if let Some(mut info) = self.get_voter_info(voter).await {
             info.total_votes = info.total_votes.saturating_add(1);
@@ -6,13 +6,15 @@
 mod state;
 
 use linera_sdk::{
-    linera_base_types::{WithContractAbi, AccountOwner, Amount, ChainId, Timestamp},
+    linera_base_types::{WithContractAbi, Account, AccountOwner, Amount, ChainId, Timestamp},
     views::{RootView, View},
     Contract, ContractRuntime,
 };
+use alethea_oracle_types::{commitment, voting_power};
+use sha2::{Digest, Sha256};
 use alethea_voter_chain::{
-    VoterChainAbi, VoterOperation, VoterResponse, InitialState, Message,
-    Parameters, VoterInfo, VoteRecord,
+    Ballot, VoterChainAbi, VoterError, VoterOperation, VoterResponse, InitialState, MarketKind, Message,
+    Parameters, ScalarValue, VoteRecord,
 };
 
 use self::state::{VoterState, VotingRequest};
@@ -68,22 +70,38 @@ impl Contract for VoterChainContract {
                 self.initialize_voter(oracle_chain, stake).await
             }
             
-            VoterOperation::CommitVote { market_id, outcome_index } => {
-                self.commit_vote(market_id, outcome_index).await
+            VoterOperation::CommitVote { market_id, outcome_index, confidence, seed } => {
+                self.commit_vote(market_id, outcome_index, None, None, confidence, seed).await
+            }
+            
+            VoterOperation::CommitScalarVote { market_id, value, confidence, seed } => {
+                self.commit_vote(market_id, 0, Some(value.0), None, confidence, seed).await
+            }
+            
+            VoterOperation::CommitBallot { market_id, ballot, confidence, seed } => {
+                self.commit_vote(market_id, 0, None, Some(ballot), confidence, seed).await
             }
             
             VoterOperation::RevealVote { market_id } => {
                 self.reveal_vote(market_id).await
             }
             
-            VoterOperation::SubmitVote { market_id, outcome_index } => {
-                self.submit_vote(market_id, outcome_index).await
+            VoterOperation::SubmitVote { market_id, outcome_index, confidence } => {
+                self.submit_vote(market_id, outcome_index, confidence).await
             }
             
             VoterOperation::AddStake { amount } => {
                 self.add_stake(amount).await
             }
             
+            VoterOperation::RequestUnstake { amount } => {
+                self.request_unstake(amount).await
+            }
+            
+            VoterOperation::CompleteUnstake => {
+                self.complete_unstake().await
+            }
+            
             VoterOperation::GetVoterInfo => {
                 self.get_voter_info().await
             }
@@ -98,10 +116,19 @@ impl Contract for VoterChainContract {
         }
     }
 
-    async fn execute_message(&mut self, message: Message) -> () {
+    async fn execute_message(&mut self, message: Message) {
         match message {
             Message::VotingRequest { market_id, question, outcomes, deadline } => {
-                self.handle_voting_request(market_id, question, outcomes, deadline).await;
+                self.handle_voting_request(market_id, question, outcomes, MarketKind::Categorical, deadline).await;
+            }
+            
+            Message::ScalarVotingRequest { market_id, question, min, max, decimals, deadline } => {
+                let kind = MarketKind::Scalar { min: ScalarValue(min), max: ScalarValue(max), decimals };
+                self.handle_voting_request(market_id, question, Vec::new(), kind, deadline).await;
+            }
+            
+            Message::BallotVotingRequest { market_id, question, outcomes, kind, deadline } => {
+                self.handle_voting_request(market_id, question, outcomes, kind, deadline).await;
             }
             
             Message::ReputationUpdate { increase, amount } => {
@@ -112,16 +139,20 @@ impl Contract for VoterChainContract {
                 self.handle_reward_payment(amount, market_id).await;
             }
             
-            Message::VoteCommitmentToOracle { .. } => {
-                // Message sent to Oracle, no action needed here
+            Message::StakeWithdrawn { amount } => {
+                self.handle_stake_withdrawn(amount).await;
+            }
+            
+            Message::StakeSlashed { amount } => {
+                self.handle_stake_slashed(amount).await;
             }
             
-            Message::VoteRevealToOracle { .. } => {
-                // Message sent to Oracle, no action needed here
+            Message::UnstakeRejected { amount } => {
+                self.handle_unstake_rejected(amount).await;
             }
             
-            Message::DirectVote { .. } => {
-                // Message sent to Oracle, no action needed here
+            _ => {
+                // Votes and resolution traffic are for other chains, no action needed here
             }
         }
     }
@@ -132,71 +163,27 @@ impl Contract for VoterChainContract {
 }
 
 impl VoterChainContract {
-    /// Generate salt untuk commit-reveal (WASM-compatible, deterministic)
-    fn generate_salt_for_market(&mut self, market_id: u64) -> [u8; 32] {
-        // Use market_id as primary input for deterministic salt
-        let app_id_bytes = bcs::to_bytes(&self.runtime.application_id())
-            .unwrap_or_default();
-        
-        let mut salt = [0u8; 32];
-        // Use market_id for first 8 bytes
-        salt[..8].copy_from_slice(&market_id.to_le_bytes());
-        // Use app_id for next 16 bytes (max)
-        let mix_len = app_id_bytes.len().min(16);
-        for i in 0..mix_len {
-            salt[8 + i] = app_id_bytes[i];
-        }
-        // Remaining bytes are zero (already initialized)
-        
-        salt
-    }
-    
-    /// Verify commitment hash - WASM SAFE (no Vec, no panic)
-    pub fn verify_commitment(
-        commitment_hash: &[u8; 32],
-        outcome_index: usize,
-        salt: &[u8; 32],
-    ) -> bool {
-        let mut data = [0u8; 40]; // Fixed array: 8 bytes outcome + 32 bytes salt
-        data[..8].copy_from_slice(&outcome_index.to_le_bytes());
-        data[8..40].copy_from_slice(salt);
-        
-        let computed = Self::simple_hash(&data);
-        &computed == commitment_hash
-    }
-    
-    /// Hash deterministik yang aman untuk WASM - ULTRA MINIMAL
-    fn simple_hash(data: &[u8]) -> [u8; 32] {
-        let mut result = [0u8; 32];
-        
-        if data.is_empty() {
-            return result;
-        }
-        
-        // Store length in first 4 bytes
-        let len = data.len() as u64;
-        result[0] = (len & 0xFF) as u8;
-        result[1] = ((len >> 8) & 0xFF) as u8;
-        result[2] = ((len >> 16) & 0xFF) as u8;
-        result[3] = ((len >> 24) & 0xFF) as u8;
-        
-        // Safety limit to prevent infinite loops
-        let max_len = data.len().min(10000);
-        
-        // XOR all data into result
-        for i in 0..max_len {
-            let pos = i % 32;
-            result[pos] ^= data[i];
-        }
-        
-        // Simple diffusion
-        for i in 0..32 {
-            let a = result[i];
-            let b = result[(i + 7) % 32];
-            result[i] = a.wrapping_add(b);
-        }
-        
-        result
+    /// Domain separation tag untuk salt derivation
+    const SALT_DOMAIN: &'static [u8] = b"alethea-network/vote-salt";
+
+    /// Generate salt untuk commit-reveal
+    ///
+    /// The client `seed` is the only input an observer cannot know; block height,
+    /// timestamp, chain id and the salt nonce make sure a reused seed still never
+    /// yields the same salt twice.
+    fn generate_salt_for_market(&mut self, market_id: u64, seed: &[u8; 32]) -> [u8; 32] {
+        let nonce = self.state.next_salt_nonce();
+        let chain_id = self.runtime.chain_id();
+
+        let mut hasher = Sha256::new();
+        hasher.update(Self::SALT_DOMAIN);
+        hasher.update(seed);
+        hasher.update(chain_id.0.as_bytes().as_slice());
+        hasher.update(market_id.to_le_bytes());
+        hasher.update(self.runtime.block_height().0.to_le_bytes());
+        hasher.update(self.runtime.system_time().micros().to_le_bytes());
+        hasher.update(nonce.to_le_bytes());
+        hasher.finalize().into()
     }
     
     async fn initialize_voter(&mut self, oracle_chain: ChainId, stake: Amount) -> VoterResponse {
@@ -211,26 +198,110 @@ impl VoterChainContract {
         VoterResponse::Initialized
     }
     
+    /// Send a vote to the coordinator, signed and tracked
+    ///
+    /// The coordinator ignores vote messages without an authenticated signer;
+    /// tracking bounces a rejected vote back here instead of dropping it.
+    fn send_to_oracle(&mut self, oracle_chain: ChainId, message: Message) {
+        self.runtime
+            .prepare_message(message)
+            .with_authentication()
+            .with_tracking()
+            .send_to(oracle_chain);
+    }
+
+    /// Voting power from local reputation and stake, same formula as the coordinator
+    fn voting_power(&mut self) -> u64 {
+        let curve = self.runtime.application_parameters().voting_power_curve;
+        let reputation = self.state.reputation.get();
+        voting_power::voting_power(
+            reputation.score,
+            reputation.accuracy_bps(),
+            *self.state.total_stake.get(),
+            curve,
+        )
+    }
+
+    /// Check a vote against the VotingRequest received for the market - WASM SAFE
+    ///
+    /// `value` is the answer on a scalar market and `ballot` the answer on a
+    /// multi-select or ranked one; categorical votes pass None for both.
+    async fn validate_vote(
+        &mut self,
+        market_id: u64,
+        outcome_index: usize,
+        value: Option<i128>,
+        ballot: Option<&Ballot>,
+        confidence: u8,
+    ) -> Result<(), VoterError> {
+        if self.state.owner.get().is_none() {
+            return Err(VoterError::NotInitialized);
+        }
+
+        if confidence > 100 {
+            return Err(VoterError::InvalidConfidence);
+        }
+
+        // Satu vote per market: commit-reveal atau direct vote
+        let committed = self.state.pending_commitments.contains_key(&market_id).await;
+        let voted = self.state.vote_history.contains_key(&market_id).await;
+        match (committed, voted) {
+            (Ok(false), Ok(false)) => {}
+            (Ok(_), Ok(_)) => return Err(VoterError::AlreadyVoted),
+            _ => return Err(VoterError::StorageFailure),
+        }
+
+        let request = match self.state.pending_requests.get(&market_id).await {
+            Ok(Some(request)) => request,
+            Ok(None) => return Err(VoterError::NoPendingRequest),
+            Err(_) => return Err(VoterError::StorageFailure),
+        };
+
+        match (value, ballot) {
+            (Some(value), _) if !request.kind.contains(value) => return Err(VoterError::ValueOutOfRange),
+            (_, Some(ballot)) if !ballot.fits(&request.kind, request.outcomes.len()) => {
+                return Err(VoterError::InvalidBallot);
+            }
+            (None, None) if request.kind != MarketKind::Categorical || outcome_index >= request.outcomes.len() => {
+                return Err(VoterError::InvalidOutcome);
+            }
+            _ => {}
+        }
+
+        if self.runtime.system_time() > request.deadline {
+            return Err(VoterError::DeadlinePassed);
+        }
+        
+        Ok(())
+    }
+
     /// COMMIT PHASE - WASM SAFE (no Vec, no panic, no string alloc)
-    async fn commit_vote(&mut self, market_id: u64, outcome_index: usize) -> VoterResponse {
-        // QUICK FIX: Skip validation for testing
-        // Binary markets only (0 or 1)
-        if outcome_index > 1 {
-            return VoterResponse::CommitmentSubmitted {
-                market_id: 999, // Error indicator
-                commitment_hash: [0u8; 32],
-            };
+    ///
+    /// Scalar markets commit to `value` and multi-select or ranked markets to
+    /// `ballot`; `outcome_index` is then unused.
+    async fn commit_vote(
+        &mut self,
+        market_id: u64,
+        outcome_index: usize,
+        value: Option<i128>,
+        ballot: Option<Ballot>,
+        confidence: u8,
+        seed: [u8; 32],
+    ) -> VoterResponse {
+        if let Err(error) = self.validate_vote(market_id, outcome_index, value, ballot.as_ref(), confidence).await {
+            return VoterResponse::Error(error);
         }
         
         // Generate salt
-        let salt = self.generate_salt_for_market(market_id);
+        let salt = self.generate_salt_for_market(market_id, &seed);
         
-        // Create commitment hash - FIXED ARRAY ONLY
-        let mut data = [0u8; 40];
-        data[..8].copy_from_slice(&outcome_index.to_le_bytes());
-        data[8..40].copy_from_slice(&salt);
-        
-        let commitment_hash_bytes = Self::simple_hash(&data);
+        // Create commitment hash bound to this market and voter chain
+        let voter_chain = self.runtime.chain_id();
+        let commitment_hash_bytes = match (value, &ballot) {
+            (_, Some(ballot)) => commitment::compute_ballot_commitment(market_id, voter_chain, ballot, confidence, &salt),
+            (Some(value), None) => commitment::compute_scalar_commitment(market_id, voter_chain, value, confidence, &salt),
+            (None, None) => commitment::compute_commitment(market_id, voter_chain, outcome_index, confidence, &salt),
+        };
         
         // Store commitment
         let commitment = alethea_voter_chain::VoteCommitment {
@@ -238,26 +309,28 @@ impl VoterChainContract {
             commitment_hash: commitment_hash_bytes,
             outcome_index,
             salt,
+            confidence,
             committed_at: self.runtime.system_time(),
+            value,
+            ballot,
         };
         
         // Store without panic
-        if let Err(_) = self.state.pending_commitments.insert(&market_id, commitment) {
-            return VoterResponse::CommitmentSubmitted {
-                market_id: 998,
-                commitment_hash: [0u8; 32],
-            };
+        if self.state.pending_commitments.insert(&market_id, commitment).is_err() {
+            return VoterResponse::Error(VoterError::StorageFailure);
         }
         
         // Send commitment to Oracle Coordinator if configured
         if let Some(oracle_chain) = *self.state.oracle_chain.get() {
-            // Calculate voting power (simplified for now)
-            let voting_power = 100; // TODO: Calculate from reputation and stake
-            let stake_amount = *self.state.total_stake.get();
+            let voting_power = self.voting_power();
+            // The coordinator locks what the round asks for, at least this much
+            let stake_amount = self.runtime.application_parameters().min_stake;
             
-            self.runtime.send_message(
+            self.send_to_oracle(
                 oracle_chain,
-                Message::VoteCommitmentToOracle {
+                Message::VoteCommitment {
+                    voter_chain,
+                    market_id,
                     commitment_hash: commitment_hash_bytes,
                     voting_power,
                     stake_amount,
@@ -276,55 +349,93 @@ impl VoterChainContract {
         // Get commitment - no panic
         let commitment = match self.state.pending_commitments.get(&market_id).await {
             Ok(Some(c)) => c,
-            _ => {
-                return VoterResponse::VoteRevealed {
-                    market_id,
-                    outcome_index: 0,
-                    verified: false,
-                };
-            }
+            Ok(None) => return VoterResponse::Error(VoterError::NoCommitment),
+            Err(_) => return VoterResponse::Error(VoterError::StorageFailure),
         };
         
-        // Verify - FIXED ARRAY
-        let mut data = [0u8; 40];
-        data[..8].copy_from_slice(&commitment.outcome_index.to_le_bytes());
-        data[8..40].copy_from_slice(&commitment.salt);
-        
-        let computed_hash = Self::simple_hash(&data);
-        let verified = computed_hash == commitment.commitment_hash;
+        // Verify stored commitment still opens with the stored vote
+        let voter_chain = self.runtime.chain_id();
+        let verified = match (commitment.value, &commitment.ballot) {
+            (_, Some(ballot)) => commitment::verify_ballot_commitment(
+                &commitment.commitment_hash,
+                market_id,
+                voter_chain,
+                ballot,
+                commitment.confidence,
+                &commitment.salt,
+            ),
+            (Some(value), None) => commitment::verify_scalar_commitment(
+                &commitment.commitment_hash,
+                market_id,
+                voter_chain,
+                value,
+                commitment.confidence,
+                &commitment.salt,
+            ),
+            (None, None) => commitment::verify_commitment(
+                &commitment.commitment_hash,
+                market_id,
+                voter_chain,
+                commitment.outcome_index,
+                commitment.confidence,
+                &commitment.salt,
+            ),
+        };
         
         if !verified {
-            return VoterResponse::VoteRevealed {
-                market_id,
-                outcome_index: commitment.outcome_index,
-                verified: false,
-            };
+            return VoterResponse::Error(VoterError::CommitmentMismatch);
         }
         
         // Record vote - WASM SAFE (no String allocation)
         let record = state::VoteRecord {
             market_id,
             outcome_index: commitment.outcome_index,
-            confidence: 95,
+            confidence: commitment.confidence,
             timestamp: self.runtime.system_time(),
             was_correct: None,
             reward_received: Amount::ZERO,
+            value: commitment.value.map(ScalarValue),
+            ballot: commitment.ballot.clone(),
         };
         
-        self.state.record_vote(market_id, record).await;
+        if !self.state.record_vote(market_id, record).await {
+            return VoterResponse::Error(VoterError::StorageFailure);
+        }
         
         // Send reveal to Oracle Coordinator if configured
         if let Some(oracle_chain) = *self.state.oracle_chain.get() {
-            self.runtime.send_message(
-                oracle_chain,
-                Message::VoteRevealToOracle {
+            let reveal = match (commitment.value, &commitment.ballot) {
+                (_, Some(ballot)) => Message::BallotVoteReveal {
+                    voter_chain,
+                    market_id,
+                    ballot: ballot.clone(),
+                    salt: commitment.salt,
+                    confidence: commitment.confidence,
+                },
+                (Some(value), None) => Message::ScalarVoteReveal {
+                    voter_chain,
+                    market_id,
+                    value,
+                    salt: commitment.salt,
+                    confidence: commitment.confidence,
+                },
+                (None, None) => Message::VoteReveal {
+                    voter_chain,
+                    market_id,
                     outcome_index: commitment.outcome_index,
                     salt: commitment.salt,
-                    confidence: 95,
+                    confidence: commitment.confidence,
                 },
-            );
+            };
+            self.send_to_oracle(oracle_chain, reveal);
         }
         
+        if let Some(ballot) = commitment.ballot {
+            return VoterResponse::BallotRevealed { market_id, ballot, verified };
+        }
+        if let Some(value) = commitment.value {
+            return VoterResponse::ScalarVoteRevealed { market_id, value: ScalarValue(value), verified };
+        }
         VoterResponse::VoteRevealed {
             market_id,
             outcome_index: commitment.outcome_index,
@@ -332,45 +443,48 @@ impl VoterChainContract {
         }
     }
 
-    async fn submit_vote(&mut self, market_id: u64, outcome_index: usize) -> VoterResponse {
-        // Simple validation
-        if outcome_index > 1 {
-            return VoterResponse::VoteSubmitted { 
-                market_id: 999, 
-                outcome_index: 0 
-            };
+    async fn submit_vote(
+        &mut self,
+        market_id: u64,
+        outcome_index: usize,
+        confidence: u8,
+    ) -> VoterResponse {
+        if let Err(error) = self.validate_vote(market_id, outcome_index, None, None, confidence).await {
+            return VoterResponse::Error(error);
         }
         
         // Create vote record - WASM SAFE (no String allocation)
         let record = state::VoteRecord {
             market_id,
             outcome_index,
-            confidence: 95,
+            confidence,
             timestamp: self.runtime.system_time(),
             was_correct: None,
             reward_received: Amount::ZERO,
+            value: None,
+            ballot: None,
         };
         
         // Record vote
-        self.state.record_vote(market_id, record).await;
+        if !self.state.record_vote(market_id, record).await {
+            return VoterResponse::Error(VoterError::StorageFailure);
+        }
         
         // Update reputation to increment total_votes
-        self.state.update_reputation_correct(95).await;
+        self.state.update_reputation_correct(confidence).await;
 
         // Send direct vote to Oracle Coordinator if configured
         if let Some(oracle_chain) = *self.state.oracle_chain.get() {
-            // Calculate voting power based on stake
-            let stake: u128 = (*self.state.total_stake.get()).into();
-            let voting_power = u64::try_from(stake).unwrap_or(100);
+            let voting_power = self.voting_power();
             let voter_chain = self.runtime.chain_id();
             
-            self.runtime.send_message(
+            self.send_to_oracle(
                 oracle_chain,
                 Message::DirectVote {
                     voter_chain,
                     market_id,
                     outcome_index,
-                    confidence: 95,
+                    confidence,
                     voting_power,
                 },
             );
@@ -379,16 +493,77 @@ impl VoterChainContract {
         VoterResponse::VoteSubmitted { market_id, outcome_index }
     }
 
+    /// Move `amount` from the signer to its own account on the oracle chain and
+    /// ask the coordinator to escrow it
+    ///
+    /// The coordinator takes the deposit out of the signer's account when the
+    /// StakeDeposit message arrives, so only the signer's own tokens can be
+    /// credited to this voter.
     async fn add_stake(&mut self, amount: Amount) -> VoterResponse {
+        let Some(signer) = self.runtime.authenticated_signer() else {
+            return VoterResponse::Error(VoterError::Unauthenticated);
+        };
+        let Some(oracle_chain) = *self.state.oracle_chain.get() else {
+            return VoterResponse::Error(VoterError::EscrowNotConfigured);
+        };
+        if self.runtime.owner_balance(signer) < amount {
+            return VoterResponse::Error(VoterError::InsufficientBalance);
+        }
+        
+        let deposit = Account {
+            chain_id: oracle_chain,
+            owner: signer,
+        };
+        self.runtime.transfer(signer, deposit, amount);
         self.state.add_stake(amount).await;
+        
+        let voter_chain = self.runtime.chain_id();
+        self.send_to_oracle(oracle_chain, Message::StakeDeposit { voter_chain, amount });
+        
         let new_total = *self.state.total_stake.get();
         VoterResponse::StakeAdded { new_total }
     }
 
+    /// Ask the coordinator to move `amount` into its unbonding queue
+    ///
+    /// The stake leaves `total_stake` straight away. The coordinator has the
+    /// final say: if the stake is locked behind open commitments it refuses
+    /// and the stake comes back with UnstakeRejected.
+    async fn request_unstake(&mut self, amount: Amount) -> VoterResponse {
+        if self.runtime.authenticated_signer().is_none() {
+            return VoterResponse::Error(VoterError::Unauthenticated);
+        }
+        let Some(oracle_chain) = *self.state.oracle_chain.get() else {
+            return VoterResponse::Error(VoterError::EscrowNotConfigured);
+        };
+        if amount == Amount::ZERO || amount > *self.state.total_stake.get() {
+            return VoterResponse::Error(VoterError::InsufficientStake);
+        }
+        
+        self.state.begin_unbonding(amount).await;
+        let voter_chain = self.runtime.chain_id();
+        self.send_to_oracle(oracle_chain, Message::UnstakeRequest { voter_chain, amount });
+        VoterResponse::UnstakeRequested { amount }
+    }
+    
+    /// Ask the coordinator to pay unbonded stake to the signer on this chain
+    async fn complete_unstake(&mut self) -> VoterResponse {
+        if self.runtime.authenticated_signer().is_none() {
+            return VoterResponse::Error(VoterError::Unauthenticated);
+        }
+        let Some(oracle_chain) = *self.state.oracle_chain.get() else {
+            return VoterResponse::Error(VoterError::EscrowNotConfigured);
+        };
+        
+        let voter_chain = self.runtime.chain_id();
+        self.send_to_oracle(oracle_chain, Message::UnstakeWithdrawal { voter_chain });
+        VoterResponse::UnstakeCompletionRequested
+    }
+
     async fn get_voter_info(&mut self) -> VoterResponse {
         match self.state.get_voter_info_sync() {
             Some(info) => VoterResponse::VoterInfo(info),
-            None => VoterResponse::Initialized, // Return default if not initialized
+            None => VoterResponse::Error(VoterError::NotInitialized),
         }
     }
 
@@ -405,6 +580,8 @@ impl VoterChainContract {
                         confidence: rec.confidence,
                         timestamp: rec.timestamp,
                         was_correct: rec.was_correct,
+                        value: rec.value,
+                        ballot: rec.ballot,
                     });
                 }
             }
@@ -438,28 +615,78 @@ impl VoterChainContract {
         market_id: u64,
         question: String,
         outcomes: Vec<String>,
+        kind: MarketKind,
         deadline: Timestamp,
     ) {
+        // A repeat request from the coordinator means the market went to an appeal round
+        let voted = self.state.vote_history.contains_key(&market_id).await.unwrap_or(false)
+            || self.state.pending_commitments.contains_key(&market_id).await.unwrap_or(false);
+        if voted && self.sent_by_oracle_chain() {
+            self.state.clear_vote(market_id).await;
+        }
+
         let request = VotingRequest {
             market_id,
             question,
             outcomes,
             deadline,
             received_at: self.runtime.system_time(),
+            kind,
         };
         
         self.state.add_voting_request(market_id, request).await;
     }
 
+    /// Coordinator scored one of our votes; only trusted from our oracle chain
+    ///
+    /// Reputation feeds the voting power reported with each commitment.
     async fn handle_reputation_update(&mut self, increase: bool, amount: u64) {
+        if !self.sent_by_oracle_chain() {
+            return;
+        }
+        let confidence = u8::try_from(amount).unwrap_or(u8::MAX);
         if increase {
-            self.state.update_reputation_correct(amount as u8).await;
+            self.state.update_reputation_correct(confidence).await;
         } else {
-            self.state.update_reputation_incorrect(amount as u8).await;
+            self.state.update_reputation_incorrect(confidence).await;
+        }
+    }
+
+    /// Coordinator paid out unbonded stake; only trusted from our oracle chain
+    async fn handle_stake_withdrawn(&mut self, amount: Amount) {
+        if !self.sent_by_oracle_chain() {
+            return;
+        }
+        self.state.release_unbonding(amount).await;
+    }
+
+    /// Coordinator refused an unstake request; only trusted from our oracle chain
+    async fn handle_unstake_rejected(&mut self, amount: Amount) {
+        if !self.sent_by_oracle_chain() {
+            return;
+        }
+        self.state.cancel_unbonding(amount).await;
+    }
+
+    /// Coordinator slashed some of our stake; only trusted from our oracle chain
+    async fn handle_stake_slashed(&mut self, amount: Amount) {
+        if !self.sent_by_oracle_chain() {
+            return;
         }
+        self.state.slash_stake(amount).await;
+    }
+
+    /// Whether the message being executed comes from our oracle chain
+    fn sent_by_oracle_chain(&mut self) -> bool {
+        let oracle_chain = *self.state.oracle_chain.get();
+        oracle_chain.is_some() && self.runtime.message_origin_chain_id() == oracle_chain
     }
 
+    /// Coordinator paid a reward into our escrowed stake; only trusted from our oracle chain
     async fn handle_reward_payment(&mut self, amount: Amount, market_id: u64) {
+        if !self.sent_by_oracle_chain() {
+            return;
+        }
         self.state.add_stake(amount).await;
         
         // Update vote record dengan reward
@@ -473,44 +700,641 @@ impl VoterChainContract {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alethea_voter_chain::StakeCurve;
+    use linera_sdk::{
+        linera_base_types::{ApplicationId, BlockHeight, CryptoHash},
+        util::BlockingWait,
+    };
 
     #[test]
-    fn test_simple_hash_deterministic() {
-        let data = b"same input";
-        let hash1 = VoterChainContract::simple_hash(data);
-        let hash2 = VoterChainContract::simple_hash(data);
-        assert_eq!(hash1, hash2);
+    fn test_commit_then_reveal_verifies() {
+        let mut contract = create_test_contract();
+        receive_request(&mut contract, 1, 2);
+
+        let response = contract
+            .execute_operation(VoterOperation::CommitVote {
+                market_id: 1,
+                outcome_index: 1,
+                confidence: 80,
+                seed: [3u8; 32],
+            })
+            .blocking_wait();
+        let commitment_hash = match response {
+            VoterResponse::CommitmentSubmitted { market_id, commitment_hash } => {
+                assert_eq!(market_id, 1);
+                commitment_hash
+            }
+            other => panic!("Expected CommitmentSubmitted, got {:?}", other),
+        };
+
+        let stored = contract.state.pending_commitments.get(&1)
+            .blocking_wait()
+            .expect("Failed to read commitment")
+            .expect("Commitment should be stored");
+        assert!(commitment::verify_commitment(
+            &commitment_hash,
+            1,
+            test_chain(),
+            1,
+            80,
+            &stored.salt,
+        ));
+
+        let response = contract
+            .execute_operation(VoterOperation::RevealVote { market_id: 1 })
+            .blocking_wait();
+        assert!(matches!(
+            response,
+            VoterResponse::VoteRevealed { market_id: 1, outcome_index: 1, verified: true }
+        ));
     }
-    
+
     #[test]
-    fn test_simple_hash_different_inputs() {
-        let hash1 = VoterChainContract::simple_hash(b"input1");
-        let hash2 = VoterChainContract::simple_hash(b"input2");
-        assert_ne!(hash1, hash2);
+    fn test_salt_depends_on_seed_and_nonce() {
+        let mut contract = create_test_contract();
+
+        let first = contract.generate_salt_for_market(1, &[3u8; 32]);
+        let other_seed = contract.generate_salt_for_market(1, &[4u8; 32]);
+        let same_seed_again = contract.generate_salt_for_market(1, &[3u8; 32]);
+
+        assert_ne!(first, other_seed);
+        assert_ne!(first, same_seed_again, "Nonce must make every salt unique");
+        assert_eq!(*contract.state.salt_nonce.get(), 3);
     }
-    
+
     #[test]
-    fn test_verify_commitment() {
-        let outcome_index = 1;
-        let salt = [42u8; 32];
-        
-        // Use fixed array for test too
-        let mut data = [0u8; 40];
-        data[..8].copy_from_slice(&outcome_index.to_le_bytes());
-        data[8..40].copy_from_slice(&salt);
-        
-        let hash = VoterChainContract::simple_hash(&data);
-        
-        assert!(VoterChainContract::verify_commitment(&hash, outcome_index, &salt));
-        
-        // Wrong salt should fail
-        let wrong_salt = [43u8; 32];
-        assert!(!VoterChainContract::verify_commitment(&hash, outcome_index, &wrong_salt));
+    fn test_salt_not_exposed_in_pending_commitments() {
+        let mut contract = create_test_contract();
+        receive_request(&mut contract, 7, 2);
+        contract
+            .execute_operation(VoterOperation::CommitVote {
+                market_id: 7,
+                outcome_index: 0,
+                confidence: 80,
+                seed: [9u8; 32],
+            })
+            .blocking_wait();
+
+        let response = contract
+            .execute_operation(VoterOperation::GetPendingCommitments)
+            .blocking_wait();
+        let VoterResponse::PendingCommitments(infos) = response else {
+            panic!("Expected PendingCommitments");
+        };
+        assert_eq!(infos.len(), 1);
+        // CommitmentInfo carries no salt or outcome; serialized size pins that
+        assert_eq!(bcs::to_bytes(&infos[0]).unwrap().len(), 8 + 32 + 8 + 1);
     }
-    
+
+    #[test]
+    fn test_sent_votes_decode_as_shared_protocol() {
+        let mut contract = create_test_contract();
+        let oracle_chain = ChainId(CryptoHash::from([8u8; 32]));
+        contract.state.oracle_chain.set(Some(oracle_chain));
+        receive_request(&mut contract, 4, 2);
+
+        contract
+            .execute_operation(VoterOperation::CommitVote {
+                market_id: 4,
+                outcome_index: 1,
+                confidence: 80,
+                seed: [5u8; 32],
+            })
+            .blocking_wait();
+        contract
+            .execute_operation(VoterOperation::RevealVote { market_id: 4 })
+            .blocking_wait();
+
+        let sent = contract.runtime.created_send_message_requests();
+        assert_eq!(sent.len(), 2);
+        let decoded: Vec<alethea_oracle_types::Message> = sent
+            .iter()
+            .map(|request| {
+                assert_eq!(request.destination, oracle_chain);
+                assert!(request.authenticated && request.is_tracked);
+                let bytes = bcs::to_bytes(&request.message).expect("Message should serialize");
+                bcs::from_bytes(&bytes).expect("Coordinator should decode voter messages")
+            })
+            .collect();
+
+        assert!(matches!(
+            decoded[0],
+            // Reputation 100, no accuracy bonus, sqrt(100 tokens) = 10
+            alethea_oracle_types::Message::VoteCommitment { voter_chain, market_id: 4, voting_power: 1_000, .. }
+                if voter_chain == test_chain()
+        ));
+        assert!(matches!(
+            decoded[1],
+            alethea_oracle_types::Message::VoteReveal { voter_chain, market_id: 4, outcome_index: 1, confidence: 80, .. }
+                if voter_chain == test_chain()
+        ));
+    }
+
     #[test]
-    fn test_hash_empty_data() {
-        let hash = VoterChainContract::simple_hash(&[]);
-        assert_eq!(hash, [0u8; 32]);
+    fn test_vote_errors() {
+        let mut contract = create_uninitialized_contract();
+        let response = contract
+            .execute_operation(VoterOperation::CommitVote { market_id: 1, outcome_index: 0, confidence: 80, seed: [1u8; 32] })
+            .blocking_wait();
+        assert_error(response, VoterError::NotInitialized);
+        let response = contract
+            .execute_operation(VoterOperation::SubmitVote { market_id: 1, outcome_index: 0, confidence: 80 })
+            .blocking_wait();
+        assert_error(response, VoterError::NotInitialized);
+        assert_error(
+            contract.execute_operation(VoterOperation::GetVoterInfo).blocking_wait(),
+            VoterError::NotInitialized,
+        );
+
+        let mut contract = create_test_contract();
+        let response = contract
+            .execute_operation(VoterOperation::CommitVote { market_id: 1, outcome_index: 0, confidence: 80, seed: [1u8; 32] })
+            .blocking_wait();
+        assert_error(response, VoterError::NoPendingRequest);
+        let response = contract
+            .execute_operation(VoterOperation::SubmitVote { market_id: 1, outcome_index: 0, confidence: 80 })
+            .blocking_wait();
+        assert_error(response, VoterError::NoPendingRequest);
+    }
+
+    #[test]
+    fn test_votes_validated_against_request() {
+        let mut contract = create_test_contract();
+        receive_request(&mut contract, 1, 4);
+        receive_request(&mut contract, 2, 4);
+
+        let response = contract
+            .execute_operation(VoterOperation::CommitVote { market_id: 1, outcome_index: 4, confidence: 80, seed: [1u8; 32] })
+            .blocking_wait();
+        assert_error(response, VoterError::InvalidOutcome);
+        let response = contract
+            .execute_operation(VoterOperation::SubmitVote { market_id: 2, outcome_index: 4, confidence: 80 })
+            .blocking_wait();
+        assert_error(response, VoterError::InvalidOutcome);
+
+        // Multi-outcome markets accept any listed outcome
+        let response = contract
+            .execute_operation(VoterOperation::CommitVote { market_id: 1, outcome_index: 3, confidence: 80, seed: [1u8; 32] })
+            .blocking_wait();
+        assert!(matches!(response, VoterResponse::CommitmentSubmitted { market_id: 1, .. }));
+        let response = contract
+            .execute_operation(VoterOperation::SubmitVote { market_id: 2, outcome_index: 3, confidence: 80 })
+            .blocking_wait();
+        assert!(matches!(response, VoterResponse::VoteSubmitted { market_id: 2, outcome_index: 3 }));
+
+        // One vote per market, whichever way it was cast
+        let response = contract
+            .execute_operation(VoterOperation::SubmitVote { market_id: 1, outcome_index: 0, confidence: 80 })
+            .blocking_wait();
+        assert_error(response, VoterError::AlreadyVoted);
+        let response = contract
+            .execute_operation(VoterOperation::CommitVote { market_id: 2, outcome_index: 0, confidence: 80, seed: [2u8; 32] })
+            .blocking_wait();
+        assert_error(response, VoterError::AlreadyVoted);
+    }
+
+    #[test]
+    fn test_appeal_request_reopens_market() {
+        let mut contract = create_test_contract();
+        let oracle_chain = ChainId(CryptoHash::from([8u8; 32]));
+        contract.state.oracle_chain.set(Some(oracle_chain));
+        receive_request(&mut contract, 1, 2);
+        contract
+            .execute_operation(VoterOperation::SubmitVote { market_id: 1, outcome_index: 0, confidence: 80 })
+            .blocking_wait();
+
+        // Anyone else repeating the request does not wipe our vote
+        contract.runtime.set_message_origin_chain_id(ChainId(CryptoHash::from([9u8; 32])));
+        receive_request(&mut contract, 1, 2);
+        let response = contract
+            .execute_operation(VoterOperation::SubmitVote { market_id: 1, outcome_index: 1, confidence: 80 })
+            .blocking_wait();
+        assert_error(response, VoterError::AlreadyVoted);
+
+        contract.runtime.set_message_origin_chain_id(oracle_chain);
+        receive_request(&mut contract, 1, 2);
+        let response = contract
+            .execute_operation(VoterOperation::CommitVote { market_id: 1, outcome_index: 1, confidence: 80, seed: [1u8; 32] })
+            .blocking_wait();
+        assert!(matches!(response, VoterResponse::CommitmentSubmitted { market_id: 1, .. }));
+    }
+
+    #[test]
+    fn test_confidence_validated_and_recorded() {
+        let mut contract = create_test_contract();
+        receive_request(&mut contract, 1, 2);
+        receive_request(&mut contract, 2, 2);
+
+        let response = contract
+            .execute_operation(VoterOperation::CommitVote { market_id: 1, outcome_index: 0, confidence: 101, seed: [1u8; 32] })
+            .blocking_wait();
+        assert_error(response, VoterError::InvalidConfidence);
+        let response = contract
+            .execute_operation(VoterOperation::SubmitVote { market_id: 2, outcome_index: 0, confidence: 101 })
+            .blocking_wait();
+        assert_error(response, VoterError::InvalidConfidence);
+
+        contract
+            .execute_operation(VoterOperation::CommitVote { market_id: 1, outcome_index: 0, confidence: 0, seed: [1u8; 32] })
+            .blocking_wait();
+        contract.execute_operation(VoterOperation::RevealVote { market_id: 1 }).blocking_wait();
+        contract
+            .execute_operation(VoterOperation::SubmitVote { market_id: 2, outcome_index: 1, confidence: 100 })
+            .blocking_wait();
+
+        let confidence = |contract: &VoterChainContract, market_id| {
+            contract.state.vote_history.get(&market_id).blocking_wait().unwrap().unwrap().confidence
+        };
+        assert_eq!(confidence(&contract, 1), 0);
+        assert_eq!(confidence(&contract, 2), 100);
+    }
+
+    #[test]
+    fn test_vote_after_deadline_rejected() {
+        let mut contract = create_test_contract();
+        receive_request(&mut contract, 1, 2);
+
+        contract.runtime.set_system_time(Timestamp::from(1001));
+        let response = contract
+            .execute_operation(VoterOperation::CommitVote { market_id: 1, outcome_index: 0, confidence: 80, seed: [1u8; 32] })
+            .blocking_wait();
+        assert_error(response, VoterError::DeadlinePassed);
+        let response = contract
+            .execute_operation(VoterOperation::SubmitVote { market_id: 1, outcome_index: 0, confidence: 80 })
+            .blocking_wait();
+        assert_error(response, VoterError::DeadlinePassed);
+    }
+
+    #[test]
+    fn test_reveal_errors() {
+        let mut contract = create_test_contract();
+        receive_request(&mut contract, 1, 2);
+        let response = contract
+            .execute_operation(VoterOperation::RevealVote { market_id: 1 })
+            .blocking_wait();
+        assert_error(response, VoterError::NoCommitment);
+
+        contract
+            .execute_operation(VoterOperation::CommitVote { market_id: 1, outcome_index: 0, confidence: 80, seed: [1u8; 32] })
+            .blocking_wait();
+        let mut stored = contract.state.pending_commitments.get(&1).blocking_wait().unwrap().unwrap();
+        stored.outcome_index = 1;
+        contract.state.pending_commitments.insert(&1, stored).unwrap();
+
+        let response = contract
+            .execute_operation(VoterOperation::RevealVote { market_id: 1 })
+            .blocking_wait();
+        assert_error(response, VoterError::CommitmentMismatch);
+    }
+
+    #[test]
+    fn test_scalar_vote_commits_and_reveals_value() {
+        let mut contract = create_test_contract();
+        let oracle_chain = ChainId(CryptoHash::from([8u8; 32]));
+        contract.state.oracle_chain.set(Some(oracle_chain));
+        receive_request(&mut contract, 1, 2);
+        contract
+            .execute_message(Message::ScalarVotingRequest {
+                market_id: 2,
+                question: "Rainfall in mm?".to_string(),
+                min: 0,
+                max: 10_000,
+                decimals: 0,
+                deadline: Timestamp::from(1000),
+            })
+            .blocking_wait();
+
+        let commit = |contract: &mut VoterChainContract, market_id, value| {
+            contract
+                .execute_operation(VoterOperation::CommitScalarVote {
+                    market_id,
+                    value: ScalarValue(value),
+                    confidence: 80,
+                    seed: [1u8; 32],
+                })
+                .blocking_wait()
+        };
+        assert_error(commit(&mut contract, 2, 10_001), VoterError::ValueOutOfRange);
+        assert_error(commit(&mut contract, 1, 5), VoterError::ValueOutOfRange);
+        let response = contract
+            .execute_operation(VoterOperation::CommitVote { market_id: 2, outcome_index: 0, confidence: 80, seed: [1u8; 32] })
+            .blocking_wait();
+        assert_error(response, VoterError::InvalidOutcome);
+
+        let VoterResponse::CommitmentSubmitted { commitment_hash, .. } = commit(&mut contract, 2, 4_250) else {
+            panic!("Expected CommitmentSubmitted");
+        };
+        let stored = contract.state.pending_commitments.get(&2).blocking_wait().unwrap().unwrap();
+        assert!(commitment::verify_scalar_commitment(&commitment_hash, 2, test_chain(), 4_250, 80, &stored.salt));
+
+        let response = contract.execute_operation(VoterOperation::RevealVote { market_id: 2 }).blocking_wait();
+        assert!(matches!(
+            response,
+            VoterResponse::ScalarVoteRevealed { market_id: 2, value: ScalarValue(4_250), verified: true }
+        ));
+        let record = contract.state.vote_history.get(&2).blocking_wait().unwrap().unwrap();
+        assert_eq!(record.value, Some(ScalarValue(4_250)));
+
+        let sent = contract.runtime.created_send_message_requests();
+        assert!(matches!(
+            sent.last().unwrap().message,
+            Message::ScalarVoteReveal { voter_chain, market_id: 2, value: 4_250, confidence: 80, .. }
+                if voter_chain == test_chain()
+        ));
+    }
+
+    #[test]
+    fn test_ballot_vote_commits_and_reveals_ranking() {
+        let mut contract = create_test_contract();
+        let oracle_chain = ChainId(CryptoHash::from([8u8; 32]));
+        contract.state.oracle_chain.set(Some(oracle_chain));
+        receive_request(&mut contract, 1, 3);
+        contract
+            .execute_message(Message::BallotVotingRequest {
+                market_id: 2,
+                question: "Final standings?".to_string(),
+                outcomes: vec!["A".to_string(), "B".to_string(), "C".to_string()],
+                kind: MarketKind::Ranked,
+                deadline: Timestamp::from(1000),
+            })
+            .blocking_wait();
+
+        let commit = |contract: &mut VoterChainContract, market_id, ballot| {
+            contract
+                .execute_operation(VoterOperation::CommitBallot { market_id, ballot, confidence: 70, seed: [4u8; 32] })
+                .blocking_wait()
+        };
+        // A ranking has to cover every outcome once, and only on a ranked market
+        assert_error(commit(&mut contract, 2, Ballot::Ranking(vec![2, 0])), VoterError::InvalidBallot);
+        assert_error(commit(&mut contract, 2, Ballot::Approval(0b101)), VoterError::InvalidBallot);
+        assert_error(commit(&mut contract, 1, Ballot::Ranking(vec![2, 0, 1])), VoterError::InvalidBallot);
+        let response = contract
+            .execute_operation(VoterOperation::CommitVote { market_id: 2, outcome_index: 0, confidence: 70, seed: [4u8; 32] })
+            .blocking_wait();
+        assert_error(response, VoterError::InvalidOutcome);
+
+        let ranking = Ballot::Ranking(vec![2, 0, 1]);
+        let VoterResponse::CommitmentSubmitted { commitment_hash, .. } = commit(&mut contract, 2, ranking.clone()) else {
+            panic!("Expected CommitmentSubmitted");
+        };
+        let stored = contract.state.pending_commitments.get(&2).blocking_wait().unwrap().unwrap();
+        assert!(commitment::verify_ballot_commitment(&commitment_hash, 2, test_chain(), &ranking, 70, &stored.salt));
+
+        let response = contract.execute_operation(VoterOperation::RevealVote { market_id: 2 }).blocking_wait();
+        assert!(matches!(&response, VoterResponse::BallotRevealed { market_id: 2, ballot, verified: true } if *ballot == ranking));
+        let record = contract.state.vote_history.get(&2).blocking_wait().unwrap().unwrap();
+        assert_eq!(record.ballot, Some(ranking.clone()));
+
+        let sent = contract.runtime.created_send_message_requests();
+        assert!(matches!(
+            &sent.last().unwrap().message,
+            Message::BallotVoteReveal { voter_chain, market_id: 2, ballot, confidence: 70, .. }
+                if *voter_chain == test_chain() && *ballot == ranking
+        ));
+    }
+
+    fn assert_error(response: VoterResponse, expected: VoterError) {
+        match response {
+            VoterResponse::Error(error) => assert_eq!(error, expected),
+            other => panic!("Expected {:?}, got {:?}", expected, other),
+        }
+    }
+
+    /// Delivers a VotingRequest with `num_outcomes` outcomes and deadline 1000
+    fn receive_request(contract: &mut VoterChainContract, market_id: u64, num_outcomes: usize) {
+        contract
+            .execute_message(Message::VotingRequest {
+                market_id,
+                question: "Test?".to_string(),
+                outcomes: (0..num_outcomes).map(|i| i.to_string()).collect(),
+                deadline: Timestamp::from(1000),
+            })
+            .blocking_wait();
+    }
+
+    #[test]
+    fn test_add_stake_transfers_to_escrow() {
+        let mut contract = create_test_contract();
+        let staker = AccountOwner::from(CryptoHash::from([0xAAu8; 32]));
+        let oracle_chain = ChainId(CryptoHash::from([8u8; 32]));
+
+        // Not signed
+        contract.runtime.set_authenticated_signer(None);
+        let response = contract
+            .execute_operation(VoterOperation::AddStake { amount: Amount::from_tokens(5) })
+            .blocking_wait();
+        assert_error(response, VoterError::Unauthenticated);
+
+        // Signed, but no oracle chain to escrow into yet
+        contract.runtime.set_authenticated_signer(staker);
+        contract.runtime.set_owner_balance(staker, Amount::from_tokens(10));
+        let response = contract
+            .execute_operation(VoterOperation::AddStake { amount: Amount::from_tokens(5) })
+            .blocking_wait();
+        assert_error(response, VoterError::EscrowNotConfigured);
+
+        contract.state.oracle_chain.set(Some(oracle_chain));
+        let response = contract
+            .execute_operation(VoterOperation::AddStake { amount: Amount::from_tokens(50) })
+            .blocking_wait();
+        assert_error(response, VoterError::InsufficientBalance);
+
+        let response = contract
+            .execute_operation(VoterOperation::AddStake { amount: Amount::from_tokens(5) })
+            .blocking_wait();
+        assert!(matches!(
+            response,
+            VoterResponse::StakeAdded { new_total } if new_total == Amount::from_tokens(105)
+        ));
+        assert_eq!(contract.runtime.owner_balance(staker), Amount::from_tokens(5));
+
+        // Into the signer's own account on the oracle chain, for the coordinator to escrow
+        let deposit = Account {
+            chain_id: oracle_chain,
+            owner: staker,
+        };
+        assert_eq!(
+            contract.runtime.outgoing_transfers().get(&deposit),
+            Some(&Amount::from_tokens(5))
+        );
+
+        let sent = contract.runtime.created_send_message_requests();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].destination, oracle_chain);
+        assert!(matches!(
+            sent[0].message,
+            Message::StakeDeposit { voter_chain, amount }
+                if voter_chain == test_chain() && amount == Amount::from_tokens(5)
+        ));
+    }
+
+    #[test]
+    fn test_unstake_round_trip() {
+        let mut contract = create_test_contract();
+        let oracle_chain = ChainId(CryptoHash::from([8u8; 32]));
+        contract.state.oracle_chain.set(Some(oracle_chain));
+        contract.runtime.set_authenticated_signer(AccountOwner::CHAIN);
+
+        let response = contract
+            .execute_operation(VoterOperation::RequestUnstake { amount: Amount::from_tokens(101) })
+            .blocking_wait();
+        assert_error(response, VoterError::InsufficientStake);
+
+        contract
+            .execute_operation(VoterOperation::RequestUnstake { amount: Amount::from_tokens(40) })
+            .blocking_wait();
+        contract.execute_operation(VoterOperation::CompleteUnstake).blocking_wait();
+        assert_eq!(*contract.state.total_stake.get(), Amount::from_tokens(60));
+        assert_eq!(*contract.state.unbonding_stake.get(), Amount::from_tokens(40));
+
+        // Unbonding stake cannot be asked back twice
+        let response = contract
+            .execute_operation(VoterOperation::RequestUnstake { amount: Amount::from_tokens(61) })
+            .blocking_wait();
+        assert_error(response, VoterError::InsufficientStake);
+
+        {
+            let sent = contract.runtime.created_send_message_requests();
+            assert_eq!(sent.len(), 2);
+            assert!(sent.iter().all(|request| request.destination == oracle_chain && request.authenticated));
+            assert!(matches!(
+                sent[0].message,
+                Message::UnstakeRequest { voter_chain, amount }
+                    if voter_chain == test_chain() && amount == Amount::from_tokens(40)
+            ));
+            assert!(matches!(sent[1].message, Message::UnstakeWithdrawal { voter_chain } if voter_chain == test_chain()));
+        }
+
+        // Only the oracle chain can report a payout
+        let withdrawn = Message::StakeWithdrawn { amount: Amount::from_tokens(40) };
+        contract.runtime.set_message_origin_chain_id(ChainId(CryptoHash::from([9u8; 32])));
+        contract.execute_message(withdrawn.clone()).blocking_wait();
+        assert_eq!(*contract.state.unbonding_stake.get(), Amount::from_tokens(40));
+
+        contract.runtime.set_message_origin_chain_id(oracle_chain);
+        contract.execute_message(withdrawn).blocking_wait();
+        assert_eq!(*contract.state.total_stake.get(), Amount::from_tokens(60));
+        assert_eq!(*contract.state.unbonding_stake.get(), Amount::ZERO);
+    }
+
+    #[test]
+    fn test_rejected_unstake_is_rebonded() {
+        let mut contract = create_test_contract();
+        let oracle_chain = ChainId(CryptoHash::from([8u8; 32]));
+        contract.state.oracle_chain.set(Some(oracle_chain));
+        contract.runtime.set_authenticated_signer(AccountOwner::CHAIN);
+
+        contract
+            .execute_operation(VoterOperation::RequestUnstake { amount: Amount::from_tokens(70) })
+            .blocking_wait();
+        let rejected = Message::UnstakeRejected { amount: Amount::from_tokens(70) };
+
+        contract.runtime.set_message_origin_chain_id(ChainId(CryptoHash::from([9u8; 32])));
+        contract.execute_message(rejected.clone()).blocking_wait();
+        assert_eq!(*contract.state.total_stake.get(), Amount::from_tokens(30));
+
+        contract.runtime.set_message_origin_chain_id(oracle_chain);
+        contract.execute_message(rejected).blocking_wait();
+        assert_eq!(*contract.state.total_stake.get(), Amount::from_tokens(100));
+        assert_eq!(*contract.state.unbonding_stake.get(), Amount::ZERO);
+
+        // A slash reaches into unbonding stake once the bonded stake is gone
+        contract
+            .execute_operation(VoterOperation::RequestUnstake { amount: Amount::from_tokens(60) })
+            .blocking_wait();
+        contract.execute_message(Message::StakeSlashed { amount: Amount::from_tokens(50) }).blocking_wait();
+        assert_eq!(*contract.state.total_stake.get(), Amount::ZERO);
+        assert_eq!(*contract.state.unbonding_stake.get(), Amount::from_tokens(50));
+    }
+
+    #[test]
+    fn test_slash_reduces_local_stake() {
+        let mut contract = create_test_contract();
+        let oracle_chain = ChainId(CryptoHash::from([8u8; 32]));
+        contract.state.oracle_chain.set(Some(oracle_chain));
+        let slashed = Message::StakeSlashed { amount: Amount::from_tokens(10) };
+
+        contract.runtime.set_message_origin_chain_id(ChainId(CryptoHash::from([9u8; 32])));
+        contract.execute_message(slashed.clone()).blocking_wait();
+        assert_eq!(*contract.state.total_stake.get(), Amount::from_tokens(100));
+
+        contract.runtime.set_message_origin_chain_id(oracle_chain);
+        contract.execute_message(slashed).blocking_wait();
+        assert_eq!(*contract.state.total_stake.get(), Amount::from_tokens(90));
+
+        // Commitments offer the minimum stake, not everything this chain holds
+        receive_request(&mut contract, 1, 2);
+        contract
+            .execute_operation(VoterOperation::CommitVote { market_id: 1, outcome_index: 0, confidence: 80, seed: [1u8; 32] })
+            .blocking_wait();
+        let sent = contract.runtime.created_send_message_requests();
+        assert!(matches!(
+            sent.last().unwrap().message,
+            Message::VoteCommitment { stake_amount, .. } if stake_amount == Amount::from_tokens(1)
+        ));
+    }
+
+    #[test]
+    fn test_reward_only_trusted_from_oracle_chain() {
+        let mut contract = create_test_contract();
+        let oracle_chain = ChainId(CryptoHash::from([8u8; 32]));
+        contract.state.oracle_chain.set(Some(oracle_chain));
+        let reward = Message::RewardPayment { amount: Amount::from_tokens(10), market_id: 1 };
+
+        contract.runtime.set_message_origin_chain_id(ChainId(CryptoHash::from([9u8; 32])));
+        contract.execute_message(reward.clone()).blocking_wait();
+        assert_eq!(*contract.state.total_stake.get(), Amount::from_tokens(100));
+
+        contract.runtime.set_message_origin_chain_id(oracle_chain);
+        contract.execute_message(reward).blocking_wait();
+        assert_eq!(*contract.state.total_stake.get(), Amount::from_tokens(110));
+    }
+
+    #[test]
+    fn test_reputation_only_trusted_from_oracle_chain() {
+        let mut contract = create_test_contract();
+        let oracle_chain = ChainId(CryptoHash::from([8u8; 32]));
+        contract.state.oracle_chain.set(Some(oracle_chain));
+        let update = Message::ReputationUpdate { increase: true, amount: 300 };
+
+        contract.runtime.set_message_origin_chain_id(ChainId(CryptoHash::from([9u8; 32])));
+        contract.execute_message(update.clone()).blocking_wait();
+        assert_eq!(contract.state.reputation.get().total_votes, 0);
+
+        // Out-of-range confidence saturates instead of wrapping to 44
+        contract.runtime.set_message_origin_chain_id(oracle_chain);
+        contract.execute_message(update).blocking_wait();
+        let reputation = contract.state.reputation.get();
+        assert_eq!((reputation.correct_votes, reputation.average_confidence), (1, u8::MAX));
+    }
+
+    fn test_chain() -> ChainId {
+        ChainId(CryptoHash::from([1u8; 32]))
+    }
+
+    fn create_test_contract() -> VoterChainContract {
+        let mut contract = create_uninitialized_contract();
+        contract
+            .state
+            .initialize(AccountOwner::CHAIN, None, Amount::from_tokens(100))
+            .blocking_wait();
+        contract
+    }
+
+    fn create_uninitialized_contract() -> VoterChainContract {
+        let runtime = ContractRuntime::new()
+            .with_chain_id(test_chain())
+            .with_application_id(ApplicationId::new(CryptoHash::from([2u8; 32])).with_abi())
+            .with_block_height(BlockHeight(5))
+            .with_system_time(Timestamp::from(0))
+            .with_application_parameters(Parameters {
+                min_stake: Amount::from_tokens(1),
+                voting_power_curve: StakeCurve::Sqrt,
+            });
+        let state = VoterState::load(runtime.root_view_storage_context())
+            .blocking_wait()
+            .expect("Failed to load state");
+
+        VoterChainContract { state, runtime }
     }
 }
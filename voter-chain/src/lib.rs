@@ -14,12 +14,18 @@ use linera_sdk::{
 };
 use serde::{Deserialize, Serialize};
 
+pub use alethea_oracle_types::{Ballot, MarketKind, ScalarValue, StakeCurve, VoterError};
+
 pub struct VoterChainAbi;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Parameters {
     /// Minimum stake required to vote
     pub min_stake: Amount,
+    
+    /// How stake is weighted in voting power
+    #[serde(default)]
+    pub voting_power_curve: StakeCurve,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -39,9 +45,15 @@ pub enum VoterOperation {
     },
     
     /// PHASE 1: Commit vote (hash only, keeps vote secret)
+    ///
+    /// `seed` must be fresh client-side randomness; it is mixed into the salt
+    /// so the commitment cannot be brute-forced before reveal. `confidence`
+    /// (0-100) is part of the commitment and cannot change on reveal.
     CommitVote {
         market_id: u64,
         outcome_index: usize,
+        confidence: u8,
+        seed: [u8; 32],
     },
     
     /// PHASE 2: Reveal vote (outcome + salt for verification)
@@ -53,13 +65,22 @@ pub enum VoterOperation {
     SubmitVote {
         market_id: u64,
         outcome_index: usize,
+        confidence: u8,
     },
     
-    /// Add more stake
+    /// Add more stake, transferred from the signer to the coordinator's escrow
     AddStake {
         amount: Amount,
     },
     
+    /// Ask the coordinator to start unbonding `amount` of free stake
+    RequestUnstake {
+        amount: Amount,
+    },
+    
+    /// Withdraw unbonded stake to the signer once the unbonding period has passed
+    CompleteUnstake,
+    
     /// Query voter info
     GetVoterInfo,
     
@@ -68,6 +89,26 @@ pub enum VoterOperation {
     
     /// Query pending commitments
     GetPendingCommitments,
+    
+    /// PHASE 1 on a scalar market: commit to a number within its range
+    ///
+    /// Revealed with `RevealVote` like any other commitment.
+    CommitScalarVote {
+        market_id: u64,
+        value: ScalarValue,
+        confidence: u8,
+        seed: [u8; 32],
+    },
+    
+    /// PHASE 1 on a multi-select or ranked market: commit to a whole ballot
+    ///
+    /// Revealed with `RevealVote` like any other commitment.
+    CommitBallot {
+        market_id: u64,
+        ballot: Ballot,
+        confidence: u8,
+        seed: [u8; 32],
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -94,6 +135,12 @@ pub enum VoterResponse {
     /// Stake added
     StakeAdded { new_total: Amount },
     
+    /// Unstake request sent to the coordinator
+    UnstakeRequested { amount: Amount },
+    
+    /// Withdrawal request sent to the coordinator
+    UnstakeCompletionRequested,
+    
     /// Voter info
     VoterInfo(VoterInfo),
     
@@ -105,6 +152,23 @@ pub enum VoterResponse {
     
     /// Generic OK
     Ok,
+    
+    /// Operation rejected
+    Error(VoterError),
+    
+    /// Scalar vote revealed (Phase 2)
+    ScalarVoteRevealed {
+        market_id: u64,
+        value: ScalarValue,
+        verified: bool,
+    },
+    
+    /// Ballot revealed (Phase 2)
+    BallotRevealed {
+        market_id: u64,
+        ballot: Ballot,
+        verified: bool,
+    },
 }
 
 /// Information about a pending commitment
@@ -150,6 +214,11 @@ impl Reputation {
         (self.correct_votes as f64 / self.total_votes as f64) * 100.0
     }
     
+    /// Accuracy in basis points, what voting power is computed from
+    pub fn accuracy_bps(&self) -> u64 {
+        alethea_oracle_types::voting_power::accuracy_bps(self.correct_votes, self.total_votes)
+    }
+    
     pub fn update_for_correct(&mut self, confidence: u8) {
         self.correct_votes += 1;
         self.total_votes += 1;
@@ -185,6 +254,8 @@ pub struct VoterInfo {
     pub owner: AccountOwner,
     pub oracle_chain: Option<ChainId>,
     pub total_stake: Amount,
+    /// Asked back and waiting out the unbonding period
+    pub unbonding_stake: Amount,
     pub reputation: Reputation,
 }
 
@@ -193,9 +264,19 @@ pub struct VoterInfo {
 pub struct VoteCommitment {
     pub market_id: u64,
     pub commitment_hash: [u8; 32],
+    #[graphql(skip)]
     pub outcome_index: usize,  // Stored locally, not revealed
+    #[graphql(skip)]
     pub salt: [u8; 32],  // Stored locally, not revealed
+    #[graphql(skip)]
+    pub confidence: u8,  // Stored locally, not revealed
     pub committed_at: Timestamp,
+    #[graphql(skip)]
+    #[serde(default)]
+    pub value: Option<i128>,  // Scalar markets only, stored locally, not revealed
+    #[graphql(skip)]
+    #[serde(default)]
+    pub ballot: Option<Ballot>,  // Multi-select and ranked markets only, stored locally, not revealed
 }
 
 /// Revealed vote data (Phase 2)
@@ -216,53 +297,14 @@ pub struct VoteRecord {
     pub confidence: u8,
     pub timestamp: Timestamp,
     pub was_correct: Option<bool>,
+    /// Answer given on a scalar market
+    pub value: Option<ScalarValue>,
+    /// Answer given on a multi-select or ranked market
+    pub ballot: Option<Ballot>,
 }
 
-    #[derive(Debug, Serialize, Deserialize)]
-    pub enum Message {
-    /// Voting request from oracle/market
-    VotingRequest {
-            market_id: u64,
-        question: String,
-        outcomes: Vec<String>,
-        deadline: Timestamp,
-    },
-    
-    /// Reputation update from oracle
-    ReputationUpdate {
-        increase: bool,
-        amount: u64,
-    },
-    
-    /// Reward payment from oracle
-    RewardPayment {
-        amount: Amount,
-            market_id: u64,
-    },
-    
-    /// Commit vote to Oracle Coordinator
-    VoteCommitmentToOracle {
-        commitment_hash: [u8; 32],
-        voting_power: u64,
-        stake_amount: Amount,
-    },
-    
-    /// Reveal vote to Oracle Coordinator
-    VoteRevealToOracle {
-        outcome_index: usize,
-        salt: [u8; 32],
-        confidence: u8,
-    },
-    
-    /// Direct vote to Oracle Coordinator (for testnet, bypass commit-reveal)
-    DirectVote {
-        voter_chain: ChainId,
-        market_id: u64,
-        outcome_index: usize,
-        confidence: u8,
-        voting_power: u64,
-    },
-}
+/// Cross-chain messages are shared with the coordinator and market chains
+pub use alethea_oracle_types::Message;
 
 impl ContractAbi for VoterChainAbi {
     type Operation = VoterOperation;
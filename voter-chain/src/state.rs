@@ -20,6 +20,9 @@ pub struct VoterState {
     /// Total stake locked
     pub total_stake: RegisterView<Amount>,
     
+    /// Stake asked back from the coordinator and not paid out yet; not part of `total_stake`
+    pub unbonding_stake: RegisterView<Amount>,
+    
     /// Advanced reputation with streak tracking
     pub reputation: RegisterView<alethea_voter_chain::Reputation>,
     
@@ -31,6 +34,9 @@ pub struct VoterState {
     
     /// Vote history
     pub vote_history: MapView<u64, VoteRecord>,
+    
+    /// Counter mixed into every salt so no two commitments share one
+    pub salt_nonce: RegisterView<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
@@ -40,6 +46,8 @@ pub struct VotingRequest {
     pub outcomes: Vec<String>,
     pub deadline: Timestamp,
     pub received_at: Timestamp,
+    #[serde(default)]
+    pub kind: alethea_voter_chain::MarketKind,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
@@ -51,8 +59,16 @@ pub struct VoteRecord {
     pub timestamp: Timestamp,
     pub was_correct: Option<bool>,
     pub reward_received: Amount,
+    /// Answer given on a scalar market
+    #[serde(default)]
+    pub value: Option<alethea_voter_chain::ScalarValue>,
+    /// Answer given on a multi-select or ranked market
+    #[serde(default)]
+    pub ballot: Option<alethea_voter_chain::Ballot>,
 }
 
+// Mutators are only called from the contract binary, not the service
+#[allow(dead_code)]
 impl VoterState {
     /// Initialize voter state
     pub async fn initialize(
@@ -69,30 +85,25 @@ impl VoterState {
     
     /// Get voter info - WASM safe (no expect)
     pub fn get_voter_info_sync(&self) -> Option<alethea_voter_chain::VoterInfo> {
-        if let Some(owner) = self.owner.get().clone() {
-            Some(alethea_voter_chain::VoterInfo {
-                owner,
-                oracle_chain: self.oracle_chain.get().clone(),
-                total_stake: *self.total_stake.get(),
-                reputation: self.reputation.get().clone(),
-            })
-        } else {
-            None
-        }
+        self.owner.get().map(|owner| alethea_voter_chain::VoterInfo {
+            owner,
+            oracle_chain: *self.oracle_chain.get(),
+            total_stake: *self.total_stake.get(),
+            unbonding_stake: *self.unbonding_stake.get(),
+            reputation: self.reputation.get().clone(),
+        })
     }
     
     /// Add voting request - WASM safe (no panic)
     pub async fn add_voting_request(&mut self, market_id: u64, request: VotingRequest) {
-        if let Err(_) = self.pending_requests.insert(&market_id, request) {
-            return;
-        }
+        let _ = self.pending_requests.insert(&market_id, request);
     }
     
-    /// Record vote
-    pub async fn record_vote(&mut self, market_id: u64, record: VoteRecord) {
+    /// Record vote, returns false if the record could not be stored
+    pub async fn record_vote(&mut self, market_id: u64, record: VoteRecord) -> bool {
         // Insert vote record - skip if error (avoid panic)
-        if let Err(_) = self.vote_history.insert(&market_id, record) {
-            return;
+        if self.vote_history.insert(&market_id, record).is_err() {
+            return false;
         }
         
         // Remove from pending
@@ -100,6 +111,13 @@ impl VoterState {
         
         // Remove commitment if exists
         let _ = self.pending_commitments.remove(&market_id);
+        true
+    }
+    
+    /// Forget our vote on a market so it can be voted on again
+    pub async fn clear_vote(&mut self, market_id: u64) {
+        let _ = self.vote_history.remove(&market_id);
+        let _ = self.pending_commitments.remove(&market_id);
     }
     
     /// Update reputation for correct vote
@@ -116,12 +134,49 @@ impl VoterState {
         self.reputation.set(rep);
     }
     
+    /// Return the next salt nonce and advance the counter
+    pub fn next_salt_nonce(&mut self) -> u64 {
+        let nonce = *self.salt_nonce.get();
+        self.salt_nonce.set(nonce.wrapping_add(1));
+        nonce
+    }
+    
     /// Add stake
     pub async fn add_stake(&mut self, amount: Amount) {
         let current = *self.total_stake.get();
         self.total_stake.set(current.saturating_add(amount));
     }
     
+    /// Move stake into unbonding as soon as it is asked back
+    pub async fn begin_unbonding(&mut self, amount: Amount) {
+        let current = *self.total_stake.get();
+        self.total_stake.set(current.saturating_sub(amount));
+        let unbonding = *self.unbonding_stake.get();
+        self.unbonding_stake.set(unbonding.saturating_add(amount));
+    }
+    
+    /// Return stake from unbonding after the coordinator refused to release it
+    pub async fn cancel_unbonding(&mut self, amount: Amount) {
+        let unbonding = *self.unbonding_stake.get();
+        let returned = amount.min(unbonding);
+        self.unbonding_stake.set(unbonding.saturating_sub(returned));
+        self.add_stake(returned).await;
+    }
+    
+    /// Forget unbonding stake that was paid back out
+    pub async fn release_unbonding(&mut self, amount: Amount) {
+        let unbonding = *self.unbonding_stake.get();
+        self.unbonding_stake.set(unbonding.saturating_sub(amount));
+    }
+    
+    /// Remove slashed stake, bonded first and then unbonding, the order the coordinator takes it in
+    pub async fn slash_stake(&mut self, amount: Amount) {
+        let current = *self.total_stake.get();
+        let from_stake = amount.min(current);
+        self.total_stake.set(current.saturating_sub(from_stake));
+        self.release_unbonding(amount.saturating_sub(from_stake)).await;
+    }
+    
     /// Record correct vote - WASM safe (no expect)
     pub async fn mark_vote_correct(&mut self, market_id: u64, confidence: u8) {
         if let Ok(Some(mut record)) = self.vote_history.get(&market_id).await {